@@ -1,25 +1,23 @@
 use cumulus_primitives_core::ParaId;
 use sc_chain_spec::{ChainSpecExtension, ChainSpecGroup, Properties};
-use sc_service::ChainType;
-use serde::{Deserialize, Serialize};
+use sc_network::config::MultiaddrWithPeerId;
+use sc_service::{ChainSpec as _, ChainType};
+use serde::{Deserialize, Deserializer, Serialize};
 use sp_core::{sr25519, Pair, Public};
-use sp_runtime::traits::{IdentifyAccount, Verify};
+use sp_runtime::{traits::{IdentifyAccount, Verify}, Perbill};
 use sp_core::crypto::Ss58Codec;
 use kpron_runtime::constants::currency::{EXISTENTIAL_DEPOSIT, SYMBOL, DECIMALS};
-use kpron_runtime::constants::address::{SS58_PREFIX};
-use statemint_common::{
-	Signature, AccountId, AuraId,
+use kpron_runtime::constants::address::{SS58_PREFIX, SUBSTRATE_SS58_PREFIX};
+use parachains_common::{
+	Signature, AccountId, AuraId, Balance, BlockNumber,
 };
+use codec::Decode;
+use sp_core::{bytes::from_hex, hexdisplay::HexDisplay, twox_128};
+use std::{collections::BTreeSet, path::Path};
 
 /// Specialized `ChainSpec` for the normal Kpron runtime.
 pub type ChainSpec = sc_service::GenericChainSpec<kpron_runtime::GenesisConfig, Extensions>;
 
-/// Helper function to generate a crypto pair from seed
-pub fn get_from_dev_seed<TPublic: Public>(seed: &str) -> <TPublic::Pair as Pair>::Public {
-	TPublic::Pair::from_string(&format!("//{}", seed), None)
-		.expect("static values are valid; qed")
-		.public()
-}
 /// Helper function to generate a crypto pair from seed
 pub fn get_from_seed<TPublic: Public>(seed: &str) -> <TPublic::Pair as Pair>::Public {
 	TPublic::Pair::from_string(seed, None)
@@ -42,37 +40,91 @@ impl Extensions {
 	pub fn try_get(chain_spec: &dyn sc_service::ChainSpec) -> Option<&Self> {
 		sc_chain_spec::get_extension(chain_spec.extensions())
 	}
-}
 
-type AccountPublic = <Signature as Verify>::Signer;
+	/// Get the extension from the given `ChainSpec`, which must be a kpron chain spec.
+	pub fn get(chain_spec: &dyn sc_service::ChainSpec) -> Result<&Self, String> {
+		Self::try_get(chain_spec).ok_or_else(|| format!(
+			"Chain spec {} has no `relay_chain` and `para_id` extensions",
+			chain_spec.id(),
+		))
+	}
 
-/// Helper function to generate an account ID from seed
-pub fn get_account_id_from_dev_seed<TPublic: Public>(seed: &str) -> AccountId
-where
-	AccountPublic: From<<TPublic::Pair as Pair>::Public>,
-{
-	AccountPublic::from(get_from_dev_seed::<TPublic>(seed)).into_account()
-}
-pub fn get_account_id_from_seed<TPublic: Public>(seed: &str) -> AccountId
-where
-	AccountPublic: From<<TPublic::Pair as Pair>::Public>,
-{
-	AccountPublic::from(get_from_seed::<TPublic>(seed)).into_account()
+	/// Check that a JSON chain spec has both extensions, naming the missing one otherwise.
+	pub fn check_json(spec: &serde_json::Value) -> Result<(), String> {
+		match spec.get("relay_chain") {
+			Some(serde_json::Value::String(_)) => {}
+			Some(_) => return Err("Chain spec field `relay_chain` must be a string".into()),
+			None => return Err("Chain spec is missing the `relay_chain` field, \
+				naming the relay chain the parachain connects to".into()),
+		}
+		match spec.get("para_id") {
+			Some(id) if id.as_u64().map_or(false, |id| id <= u32::MAX as u64) => {}
+			Some(_) => return Err("Chain spec field `para_id` must be a 32-bit unsigned integer".into()),
+			None => return Err("Chain spec is missing the `para_id` field, \
+				naming the para id the parachain is registered with".into()),
+		}
+		Self::check_genesis_para_id(spec)
+	}
+
+	/// Check that the `ParachainInfo` genesis of a plain or raw JSON chain spec, if it sets one,
+	/// agrees with the `para_id` extension.
+	fn check_genesis_para_id(spec: &serde_json::Value) -> Result<(), String> {
+		let para_id = spec["para_id"].as_u64();
+		let genesis = &spec["genesis"];
+		let key = [twox_128(b"ParachainInfo"), twox_128(b"ParachainId")].concat();
+		let raw_key = format!("0x{}", HexDisplay::from(&key));
+		let genesis_para_id = match genesis["raw"]["top"][&raw_key].as_str() {
+			Some(value) => {
+				let value = from_hex(value)
+					.map_err(|e| format!("Invalid `parachainInfo.parachainId` in genesis: {}", e))?;
+				let id = u32::decode(&mut &value[..])
+					.map_err(|e| format!("Invalid `parachainInfo.parachainId` in genesis: {}", e))?;
+				Some(id.into())
+			}
+			None => genesis["runtime"]["parachainInfo"]["parachainId"].as_u64(),
+		};
+		match genesis_para_id {
+			Some(id) if Some(id) != para_id => Err(format!(
+				"Chain spec genesis `parachainInfo.parachainId` {} disagrees with its `para_id` {}",
+				id, spec["para_id"],
+			)),
+			_ => Ok(()),
+		}
+	}
 }
 
-pub fn get_account_id_from_str<TPublic: Public>(s: &str) -> AccountId
-	where AccountId: From<TPublic>
-{
-	let public = TPublic::from_string(s).unwrap();
-	AccountId::from(public)
+/// Load a chain spec from a JSON file, reporting missing extensions by name.
+pub fn from_json_file(path: &Path) -> Result<ChainSpec, String> {
+	let json = std::fs::read(path)
+		.map_err(|e| format!("Error reading chain spec {}: {}", path.display(), e))?;
+	let value = serde_json::from_slice(&json)
+		.map_err(|e| format!("Error parsing chain spec {}: {}", path.display(), e))?;
+	Extensions::check_json(&value)?;
+	ChainSpec::from_json_bytes(json)
 }
-/// Generate collator keys from seed.
+
+/// The para id of `chain_spec`, after checking that the para id on the command line, if given,
+/// agrees with its `para_id` extension.
 ///
-/// This function's return type must always match the session keys of the chain in tuple format.
-pub fn get_collator_keys_from_dev_seed(seed: &str) -> AuraId {
-	get_from_dev_seed::<AuraId>(seed)
+/// The genesis of `ParachainInfo` is built from the same para id by [`NetworkConfig`], and checked
+/// against it by [`from_json_file`] for chain specs read from a file.
+pub fn checked_para_id(
+	chain_spec: &dyn sc_service::ChainSpec,
+	cli_para_id: Option<u32>,
+) -> Result<ParaId, String> {
+	let para_id = Extensions::get(chain_spec)?.para_id;
+	if let Some(cli_para_id) = cli_para_id {
+		if cli_para_id != para_id {
+			return Err(format!(
+				"--parachain-id {} disagrees with the chain spec's `para_id` {}",
+				cli_para_id, para_id,
+			))
+		}
+	}
+	Ok(para_id.into())
 }
 
+type AccountPublic = <Signature as Verify>::Signer;
 
 /// Generate the session keys from individual elements.
 ///
@@ -81,225 +133,256 @@ pub fn kpron_session_keys(keys: AuraId) -> kpron_runtime::opaque::SessionKeys {
 	kpron_runtime::opaque::SessionKeys { aura: keys }
 }
 
+/// The network configs of the built-in chains, by chain id.
+pub fn builtin_network(id: &str) -> Option<&'static str> {
+	Some(match id {
+		"" | "kpron" => include_str!("../res/kpron.toml"),
+		"dev" | "kpron-dev" => include_str!("../res/kpron-dev.toml"),
+		"kpron-local" => include_str!("../res/kpron-local.toml"),
+		"kpron-testnet" => include_str!("../res/kpron-testnet.toml"),
+		_ => return None,
+	})
+}
 
-pub fn kpron_config(id: ParaId) -> ChainSpec {
-	ChainSpec::from_genesis(
-		// Name
-		"Kpron",
-		// ID
-		"kpron",
-		ChainType::Live,
-		move || {
-			generate_genesis(
-				vec![
-					(
-						AccountId::from_string("5Dh7s4b8rs2emsq7hvvpTJTtJwNXoGxHbbrxwBGeNd9VuXo1").unwrap(),
-						AuraId::from_string("5Dh7s4b8rs2emsq7hvvpTJTtJwNXoGxHbbrxwBGeNd9VuXo1").unwrap()
-					),
-					(
-						AccountId::from_string("5GYqdDCfzTExVaUbZ3neycG6mR8iFrYFK6HPJhJictBuksRj").unwrap(),
-						AuraId::from_string("5GYqdDCfzTExVaUbZ3neycG6mR8iFrYFK6HPJhJictBuksRj").unwrap()
-					),
-				],
-				vec![
-					(get_account_id_from_str::<sr25519::Public>("5DoJDZNU84uLQz19kj4KhpFDxdnaQv9mNw8QTDSGDaPWdxfE"), 1_000_000_000_000_000_000_000),
-				],
-				id,
-			)
-		},
-		Vec::new(),
-		None,
-		Some("kpron"),
-		chain_properties(),
-		Extensions {
-			relay_chain: "kusama".into(),
-			para_id: id.into(),
-		},
-	)
+/// The parameters of a network's chain spec, read from a TOML or JSON file.
+///
+/// Accounts and session keys are given as SS58 addresses, or as secret URIs starting with `//`
+/// for development keys. Balances are given in the smallest unit, as strings or integers.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkConfig {
+	pub name: String,
+	pub id: String,
+	pub chain_type: ChainType,
+	#[serde(default)]
+	pub protocol_id: Option<String>,
+	pub relay_chain: String,
+	/// Defaults to the id given on the command line.
+	#[serde(default)]
+	pub para_id: Option<u32>,
+	#[serde(default)]
+	pub bootnodes: Vec<MultiaddrWithPeerId>,
+	#[serde(default)]
+	pub token: TokenConfig,
+	#[serde(default)]
+	pub staking: StakingConfig,
+	/// The invulnerable collators and their session keys.
+	pub collators: Vec<CollatorConfig>,
+	pub endowments: Vec<EndowmentConfig>,
+	/// Endowments which unlock over time.
+	#[serde(default)]
+	pub vesting: Vec<VestingConfig>,
+	/// The endowments must add up to this, if given.
+	#[serde(default, deserialize_with = "deserialize_option_balance")]
+	pub total_issuance: Option<Balance>,
 }
 
+/// Token properties shown by wallets.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TokenConfig {
+	pub symbol: String,
+	pub decimals: u8,
+	pub ss58_format: u16,
+}
 
-pub fn kpron_testnet_config(id: ParaId) -> ChainSpec {
-	ChainSpec::from_genesis(
-		// Name
-		"Kpron testnet",
-		// ID
-		"kpron_testnet",
-		ChainType::Custom(String::from("Test")),
-		move || {
-			generate_genesis(
-				vec![
-					(
-						AccountId::from_string("5Dh7s4b8rs2emsq7hvvpTJTtJwNXoGxHbbrxwBGeNd9VuXo1").unwrap(),
-						AuraId::from_string("5Dh7s4b8rs2emsq7hvvpTJTtJwNXoGxHbbrxwBGeNd9VuXo1").unwrap()
-					),
-					(
-						AccountId::from_string("5GYqdDCfzTExVaUbZ3neycG6mR8iFrYFK6HPJhJictBuksRj").unwrap(),
-						AuraId::from_string("5GYqdDCfzTExVaUbZ3neycG6mR8iFrYFK6HPJhJictBuksRj").unwrap()
-					),
-				],
-				vec![
-					(get_account_id_from_str::<sr25519::Public>("5DoJDZNU84uLQz19kj4KhpFDxdnaQv9mNw8QTDSGDaPWdxfE"), 1_000_000_000_000_000_000_000),
-				],
-				id,
-			)
-		},
-		Vec::new(),
-		None,
-		Some("kpron"),
-		chain_properties(),
-		Extensions {
-			relay_chain: "westend".into(),
-			para_id: id.into(),
-		},
-	)
+impl Default for TokenConfig {
+	fn default() -> Self {
+		Self { symbol: SYMBOL.into(), decimals: DECIMALS, ss58_format: SS58_PREFIX }
+	}
 }
 
-pub fn kpron_dev_config(id: ParaId) -> ChainSpec {
-	ChainSpec::from_genesis(
-		// Name
-		"Kpron Development",
-		// ID
-		"kpron_dev",
-		ChainType::Development,
-		move || {
-			test_generate_genesis(
-				get_account_id_from_dev_seed::<sr25519::Public>("Alice"),
-				vec![
-					(
-						get_account_id_from_dev_seed::<sr25519::Public>("Alice"),
-						get_collator_keys_from_dev_seed("Alice"),
-					),
-					(
-						get_account_id_from_dev_seed::<sr25519::Public>("Bob"),
-						get_collator_keys_from_dev_seed("Bob"),
-					)
-				],
-				vec![
-					(get_account_id_from_dev_seed::<sr25519::Public>("Alice"), 1 << 60),
-					(get_account_id_from_dev_seed::<sr25519::Public>("Bob"), 1 << 60),
-					(get_account_id_from_dev_seed::<sr25519::Public>("Alice//stash"), 1 << 60),
-					(get_account_id_from_dev_seed::<sr25519::Public>("Bob//stash"), 1 << 60),
-				],
-				id,
-			)
-		},
-		Vec::new(),
-		None,
-		Some("kpron"),
-		chain_properties(),
-		Extensions {
-			relay_chain: "kusama-dev".into(),
-			para_id: id.into(),
-		},
-	)
+/// Genesis parameters of collator staking.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StakingConfig {
+	/// Bond candidates must have, which invulnerables must be endowed with.
+	#[serde(deserialize_with = "deserialize_balance")]
+	pub min_candidate_bond: Balance,
+	pub desired_candidates: u32,
+	pub inflation_percent: u32,
 }
 
-pub fn kpron_local_config(id: ParaId) -> ChainSpec {
-	ChainSpec::from_genesis(
-		// Name
-		"Kpron Local",
-		// ID
-		"kpron_local",
-		ChainType::Local,
-		move || {
-			test_generate_genesis(
-				get_account_id_from_dev_seed::<sr25519::Public>("Alice"),
-				vec![
-					(
-						get_account_id_from_dev_seed::<sr25519::Public>("Alice"),
-						get_collator_keys_from_dev_seed("Alice"),
-					),
-					(
-						get_account_id_from_dev_seed::<sr25519::Public>("Bob"),
-						get_collator_keys_from_dev_seed("Bob"),
-					)
-				],
-				vec![
-					(get_account_id_from_dev_seed::<sr25519::Public>("Alice"), 1 << 60),
-					(get_account_id_from_dev_seed::<sr25519::Public>("Bob"), 1 << 60),
-					(get_account_id_from_dev_seed::<sr25519::Public>("Charlie"), 1 << 60),
-					(get_account_id_from_dev_seed::<sr25519::Public>("Dave"), 1 << 60),
-					(get_account_id_from_dev_seed::<sr25519::Public>("Eve"), 1 << 60),
-					(get_account_id_from_dev_seed::<sr25519::Public>("Ferdie"), 1 << 60),
-					(get_account_id_from_dev_seed::<sr25519::Public>("Alice//stash"), 1 << 60),
-					(get_account_id_from_dev_seed::<sr25519::Public>("Bob//stash"), 1 << 60),
-					(get_account_id_from_dev_seed::<sr25519::Public>("Charlie//stash"), 1 << 60),
-					(get_account_id_from_dev_seed::<sr25519::Public>("Dave//stash"), 1 << 60),
-					(get_account_id_from_dev_seed::<sr25519::Public>("Eve//stash"), 1 << 60),
-					(get_account_id_from_dev_seed::<sr25519::Public>("Ferdie//stash"), 1 << 60),
-				],
-				id,
-			)
-		},
-		Vec::new(),
-		None,
-		Some("kpron"),
-		chain_properties(),
-		Extensions {
-			relay_chain: "kusama-local".into(),
-			para_id: id.into(),
-		},
-	)
+impl Default for StakingConfig {
+	fn default() -> Self {
+		Self {
+			min_candidate_bond: EXISTENTIAL_DEPOSIT * 16, // 16KPN
+			desired_candidates: 0,
+			inflation_percent: 5,
+		}
+	}
 }
 
-fn generate_genesis(
-	invulnerables: Vec<(AccountId, AuraId)>,
-	endowed_accounts: Vec<(AccountId, u128)>,
-	id: ParaId,
-) -> kpron_runtime::GenesisConfig {
-	// TODO check invulnerables balance > STATEMINE_ED * 16
-	kpron_runtime::GenesisConfig {
-		system: kpron_runtime::SystemConfig {
-			code: kpron_runtime::WASM_BINARY
-				.expect("WASM binary was not build, please build it!")
-				.to_vec(),
-			changes_trie_config: Default::default(),
-		},
-		balances: kpron_runtime::BalancesConfig {
-			balances: endowed_accounts,
-		},
-		parachain_info: kpron_runtime::ParachainInfoConfig { parachain_id: id },
-		collator_selection: kpron_runtime::CollatorSelectionConfig {
-			invulnerables: invulnerables.iter().cloned().map(|(acc, _)| acc).collect(),
-			candidacy_bond: EXISTENTIAL_DEPOSIT * 16, //16KPN
-			..Default::default()
-		},
-		session: kpron_runtime::SessionConfig {
-			keys: invulnerables.iter().cloned().map(|(acc, aura)| (
-				acc.clone(), // account id
-				acc.clone(), // validator id
-				kpron_session_keys(aura), // session keys
-			)).collect()
-		},
-		aura: Default::default(),
-		aura_ext: Default::default(),
-		parachain_system: Default::default(),
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CollatorConfig {
+	pub account: String,
+	pub aura: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EndowmentConfig {
+	pub account: String,
+	#[serde(deserialize_with = "deserialize_balance")]
+	pub balance: Balance,
+}
+
+/// A vesting schedule of an endowed account: all of its endowment but `liquid` is locked, and
+/// unlocks linearly over `length` blocks from block `begin` on.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VestingConfig {
+	pub account: String,
+	pub begin: BlockNumber,
+	pub length: BlockNumber,
+	#[serde(deserialize_with = "deserialize_balance")]
+	pub liquid: Balance,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BalanceRepr {
+	Number(u64),
+	Text(String),
+}
+
+impl BalanceRepr {
+	fn into_balance<E: serde::de::Error>(self) -> Result<Balance, E> {
+		match self {
+			BalanceRepr::Number(n) => Ok(n.into()),
+			BalanceRepr::Text(s) => s.parse().map_err(|_| E::custom(format!("invalid balance {:?}", s))),
+		}
 	}
 }
 
-fn test_generate_genesis(
-	root_key: AccountId,
+fn deserialize_balance<'de, D: Deserializer<'de>>(d: D) -> Result<Balance, D::Error> {
+	BalanceRepr::deserialize(d)?.into_balance()
+}
+
+fn deserialize_option_balance<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Balance>, D::Error> {
+	Option::<BalanceRepr>::deserialize(d)?.map(BalanceRepr::into_balance).transpose()
+}
+
+/// A public key from a kpron or generic Substrate SS58 address, or from a secret URI starting
+/// with `//`.
+fn public_from_uri<TPublic: Public + Ss58Codec>(uri: &str) -> Result<TPublic, String>
+where
+	TPublic::Pair: Pair<Public = TPublic>,
+{
+	if uri.starts_with("//") {
+		return TPublic::Pair::from_string(uri, None)
+			.map(|pair| pair.public())
+			.map_err(|e| format!("Invalid secret URI {}: {:?}", uri, e))
+	}
+	let (public, format) = TPublic::from_ss58check_with_version(uri)
+		.map_err(|e| format!("Invalid address {}: {:?}", uri, e))?;
+	let prefix = u16::from(format);
+	if prefix != SS58_PREFIX && prefix != SUBSTRATE_SS58_PREFIX {
+		return Err(format!("Address {} has SS58 prefix {}, not that of kpron", uri, prefix))
+	}
+	Ok(public)
+}
+
+fn parse_account(s: &str) -> Result<AccountId, String> {
+	public_from_uri::<sr25519::Public>(s).map(|public| AccountPublic::from(public).into_account())
+}
+
+fn parse_aura(s: &str) -> Result<AuraId, String> {
+	public_from_uri::<AuraId>(s)
+}
+
+impl NetworkConfig {
+	/// Parse a network config in TOML.
+	pub fn from_toml(config: &str) -> Result<Self, String> {
+		toml::from_str(config).map_err(|e| format!("Invalid network config: {}", e))
+	}
+
+	/// Read a network config from a `.json` file, or a TOML file otherwise.
+	pub fn from_file(path: &Path) -> Result<Self, String> {
+		let config = std::fs::read_to_string(path)
+			.map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+		if path.extension().map_or(false, |ext| ext == "json") {
+			serde_json::from_str(&config).map_err(|e| format!("Invalid network config: {}", e))
+		} else {
+			Self::from_toml(&config)
+		}
+	}
+
+	/// Validate the genesis and build the chain spec, for `default_para_id` unless the config
+	/// sets a para id.
+	pub fn into_chain_spec(self, default_para_id: ParaId) -> Result<ChainSpec, String> {
+		let NetworkConfig {
+			name, id, chain_type, protocol_id, relay_chain, para_id, bootnodes, token, staking,
+			collators, endowments, vesting, total_issuance,
+		} = self;
+
+		let invulnerables = collators.iter()
+			.map(|c| Ok((parse_account(&c.account)?, parse_aura(&c.aura)?)))
+			.collect::<Result<Vec<_>, String>>()?;
+		let endowed_accounts = endowments.iter()
+			.map(|e| Ok((parse_account(&e.account)?, e.balance)))
+			.collect::<Result<Vec<_>, String>>()?;
+		let vesting = vesting.iter()
+			.map(|v| Ok((parse_account(&v.account)?, v.begin, v.length, v.liquid)))
+			.collect::<Result<Vec<_>, String>>()?;
+		validate_genesis(&invulnerables, &endowed_accounts, staking.min_candidate_bond, total_issuance)?;
+		validate_vesting(&vesting, &endowed_accounts)?;
+
+		let para_id = para_id.map(ParaId::from).unwrap_or(default_para_id);
+		let mut properties = Properties::new();
+		properties.insert("tokenSymbol".into(), token.symbol.into());
+		properties.insert("tokenDecimals".into(), token.decimals.into());
+		properties.insert("ss58Format".into(), token.ss58_format.into());
+
+		Ok(ChainSpec::from_genesis(
+			&name,
+			&id,
+			chain_type,
+			move || generate_genesis(
+				invulnerables.clone(),
+				endowed_accounts.clone(),
+				vesting.clone(),
+				&staking,
+				para_id,
+			),
+			bootnodes,
+			None,
+			protocol_id.as_deref(),
+			None,
+			Some(properties),
+			Extensions {
+				relay_chain,
+				para_id: para_id.into(),
+			},
+		))
+	}
+}
+
+fn generate_genesis(
 	invulnerables: Vec<(AccountId, AuraId)>,
-	endowed_accounts: Vec<(AccountId, u128)>,
+	endowed_accounts: Vec<(AccountId, Balance)>,
+	vesting: Vec<(AccountId, BlockNumber, BlockNumber, Balance)>,
+	staking: &StakingConfig,
 	id: ParaId,
 ) -> kpron_runtime::GenesisConfig {
-	// TODO check invulnerables balance > EXISTENTIAL_DEPOSIT * 16
 	kpron_runtime::GenesisConfig {
 		system: kpron_runtime::SystemConfig {
 			code: kpron_runtime::WASM_BINARY
 				.expect("WASM binary was not build, please build it!")
 				.to_vec(),
-			changes_trie_config: Default::default(),
 		},
 		balances: kpron_runtime::BalancesConfig {
 			balances: endowed_accounts,
 		},
 		parachain_info: kpron_runtime::ParachainInfoConfig { parachain_id: id },
-		collator_selection: kpron_runtime::CollatorSelectionConfig {
+		indices: kpron_runtime::IndicesConfig { indices: vec![] },
+		vesting: kpron_runtime::VestingConfig { vesting },
+		collator_staking: kpron_runtime::CollatorStakingConfig {
 			invulnerables: invulnerables.iter().cloned().map(|(acc, _)| acc).collect(),
-			candidacy_bond: EXISTENTIAL_DEPOSIT * 16, // 16KPN
-			..Default::default()
+			desired_candidates: staking.desired_candidates,
+			min_candidate_bond: staking.min_candidate_bond,
+			inflation_rate: Perbill::from_percent(staking.inflation_percent),
 		},
 		session: kpron_runtime::SessionConfig {
 			keys: invulnerables.iter().cloned().map(|(acc, aura)| (
@@ -311,49 +394,286 @@ fn test_generate_genesis(
 		aura: Default::default(),
 		aura_ext: Default::default(),
 		parachain_system: Default::default(),
+		polkadot_xcm: kpron_runtime::PolkadotXcmConfig {
+			safe_xcm_version: Some(kpron_runtime::SAFE_XCM_VERSION),
+		},
 	}
 }
 
-fn chain_properties() -> Option<Properties> {
-	let mut p = Properties::new();
-	p.insert("tokenSymbol".into(), SYMBOL.into());
-	p.insert("tokenDecimals".into(), DECIMALS.into());
-	p.insert("ss58Format".into(), SS58_PREFIX.into());
-	Some(p)
+/// Check that every invulnerable is endowed with `candidacy_bond`, that collators and session
+/// keys are unique, that every endowment is at least the existential deposit and that the
+/// endowments add up to `expected_issuance`, if given.
+pub fn validate_genesis(
+	invulnerables: &[(AccountId, AuraId)],
+	endowed_accounts: &[(AccountId, Balance)],
+	candidacy_bond: Balance,
+	expected_issuance: Option<Balance>,
+) -> Result<(), String> {
+	let mut accounts = BTreeSet::new();
+	for (account, balance) in endowed_accounts {
+		if !accounts.insert(account) {
+			return Err(format!("Account {} is endowed more than once", account))
+		}
+		if *balance < EXISTENTIAL_DEPOSIT {
+			return Err(format!(
+				"Account {} is endowed with {}, below the existential deposit of {}",
+				account, balance, EXISTENTIAL_DEPOSIT,
+			))
+		}
+	}
+
+	let mut collators = BTreeSet::new();
+	let mut keys = BTreeSet::new();
+	for (account, aura) in invulnerables {
+		if !collators.insert(account) {
+			return Err(format!("Invulnerable {} is listed more than once", account))
+		}
+		let key = aura.to_ss58check();
+		if keys.contains(&key) {
+			return Err(format!("Session key {} is used by more than one invulnerable", key))
+		}
+		keys.insert(key);
+		let balance = endowed_accounts.iter()
+			.find(|(endowed, _)| endowed == account)
+			.map_or(0, |(_, balance)| *balance);
+		if balance < candidacy_bond {
+			return Err(format!(
+				"Invulnerable {} is endowed with {}, below the candidacy bond of {}",
+				account, balance, candidacy_bond,
+			))
+		}
+	}
+
+	let issuance = endowed_accounts.iter()
+		.try_fold(0 as Balance, |total, (_, balance)| total.checked_add(*balance))
+		.ok_or_else(|| "Endowments overflow the total issuance".to_string())?;
+	match expected_issuance {
+		Some(expected) if issuance != expected => Err(format!(
+			"Endowments add up to {}, expected a total issuance of {}",
+			issuance, expected,
+		)),
+		_ => Ok(()),
+	}
 }
 
+/// Check that every vesting account is endowed, with more than its liquid part, and that the
+/// locked part unlocks at least one unit per block.
+pub fn validate_vesting(
+	vesting: &[(AccountId, BlockNumber, BlockNumber, Balance)],
+	endowed_accounts: &[(AccountId, Balance)],
+) -> Result<(), String> {
+	for (account, _begin, length, liquid) in vesting {
+		let balance = endowed_accounts.iter()
+			.find(|(endowed, _)| endowed == account)
+			.map(|(_, balance)| *balance)
+			.ok_or_else(|| format!("Vesting account {} is not endowed", account))?;
+		let locked = balance.checked_sub(*liquid).filter(|locked| *locked > 0).ok_or_else(|| format!(
+			"Vesting account {} is endowed with {}, which leaves nothing locked beyond {} liquid",
+			account, balance, liquid,
+		))?;
+		if *length == 0 || locked < Balance::from(*length) {
+			return Err(format!(
+				"Vesting account {} unlocks {} over {} blocks, less than one unit per block",
+				account, locked, length,
+			))
+		}
+	}
+	Ok(())
+}
 
 #[cfg(test)]
 mod spec_tests {
 	use super::*;
+	use codec::Encode;
+	use sc_chain_spec::ChainSpec as _;
 	use std::str::FromStr;
-	use sp_core::crypto::Ss58Codec;
-	use sp_core::ed25519;
+	use sp_core::{
+		crypto::{Ss58AddressFormat, Ss58AddressFormatRegistry},
+		ed25519,
+	};
+
+	const BOND: Balance = EXISTENTIAL_DEPOSIT * 16;
 
 	#[test]
 	fn test_get_account() {
-		let addr_str = "xx";
-		let public_str = "xx";
-		let seed_str = "xx";
+		let addr_str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+		let public_str = "0xd43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d";
+		let seed_str = "//Alice";
+		let alice = hex_literal::hex!("d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d");
+
+		let account = AccountId::from_str(addr_str).unwrap();
+		assert_eq!(account, AccountId::from(alice));
+		assert_eq!(AccountId::from_str(public_str).unwrap(), account);
 
-		let result = AccountId::from_str(addr_str);
-		println!("from str: {}", result.unwrap());
-		let result = AccountId::from_str(public_str);
-		let account = result.unwrap();
-		println!("from str: {}", account);
+		let public = AuraId::from_string(addr_str).unwrap();
+		assert_eq!(public.to_ss58check(), addr_str);
 
-		let result1 = AuraId::from_string(addr_str);
-		let public = result1.unwrap();
-		println!("AuraId, {}", public.to_ss58check());
+		let public = sr25519::Public::from_string(addr_str).unwrap();
+		assert_eq!(public, sr25519::Public::from_raw(alice));
+
+		assert_eq!(get_from_seed::<AuraId>(seed_str).to_ss58check(), addr_str);
+		assert_eq!(get_from_seed::<sr25519::Public>(seed_str), sr25519::Public::from_raw(alice));
+		assert_eq!(
+			get_from_seed::<ed25519::Public>(seed_str),
+			ed25519::Public::from_raw(hex_literal::hex!(
+				"88dc3417d5058ec4b4503e0c12ea1a0a89be200fe98922423d4334014fa6b0ee"
+			)),
+		);
+		assert_eq!(parse_account(seed_str), Ok(account));
+	}
+
+	#[test]
+	fn addresses_are_kpron_or_generic() {
+		let alice = get_from_seed::<sr25519::Public>("//Alice");
+		let account = parse_account("//Alice").unwrap();
 
-		let result1 = sr25519::Public::from_string(public_str);
-		let public = result1.unwrap();
-		println!("sr25519::Public, {}", public.to_ss58check());
+		let kpron = alice.to_ss58check_with_version(Ss58AddressFormat::custom(SS58_PREFIX));
+		assert_eq!(parse_account(&kpron), Ok(account.clone()));
+		assert_eq!(parse_account("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"), Ok(account));
 
-		let addr1 = get_from_seed::<AuraId>(seed_str);
-		let addr2 = get_from_seed::<sr25519::Public>(seed_str);
-		let addr3 = get_from_seed::<ed25519::Public>(seed_str);
-		println!("addr1: {} \naddr2: {}\naddr3: {}", addr1, addr2, addr3);
+		let kusama = alice.to_ss58check_with_version(Ss58AddressFormatRegistry::KusamaAccount.into());
+		assert!(parse_account(&kusama).is_err());
+	}
+
+	#[test]
+	fn builtin_networks_are_valid() {
+		for id in &["kpron", "kpron-dev", "kpron-local", "kpron-testnet"] {
+			let config = NetworkConfig::from_toml(builtin_network(id).unwrap()).unwrap();
+			assert!(config.into_chain_spec(2019.into()).is_ok(), "{} is invalid", id);
+		}
+	}
+
+	#[test]
+	fn config_sets_para_id_and_properties() {
+		let config = NetworkConfig::from_toml(r#"
+			name = "Custom"
+			id = "custom"
+			chain_type = "Local"
+			relay_chain = "rococo-local"
+			para_id = 3000
+			bootnodes = ["/ip4/127.0.0.1/tcp/30333/p2p/12D3KooWEyoppNCUx8Yx66oV9fJnriXwCcXwDDUA2kj6vnc6iDEp"]
+
+			[token]
+			symbol = "TKPN"
+			decimals = 10
+			ss58_format = 42
+
+			[[collators]]
+			account = "//Alice"
+			aura = "//Alice"
+
+			[[endowments]]
+			account = "//Alice"
+			balance = 16000000000000
+		"#).unwrap();
+		let spec = config.into_chain_spec(2019.into()).unwrap();
+		assert_eq!(Extensions::try_get(&spec).unwrap().para_id, 3000);
+		assert_eq!(spec.boot_nodes().len(), 1);
+		assert_eq!(spec.properties().get("tokenSymbol"), Some(&"TKPN".into()));
+	}
+
+	#[test]
+	fn builtin_networks_use_the_kpron_prefix() {
+		let config = NetworkConfig::from_toml(builtin_network("kpron").unwrap()).unwrap();
+		let spec = config.into_chain_spec(2019.into()).unwrap();
+		assert_eq!(spec.properties().get("ss58Format"), Some(&SS58_PREFIX.into()));
+	}
+
+	#[test]
+	fn missing_extensions_are_named() {
+		let missing_para_id = serde_json::json!({ "name": "Custom", "relay_chain": "kusama" });
+		assert!(Extensions::check_json(&missing_para_id).unwrap_err().contains("`para_id`"));
+		let missing_relay_chain = serde_json::json!({ "name": "Custom", "para_id": 2019 });
+		assert!(Extensions::check_json(&missing_relay_chain).unwrap_err().contains("`relay_chain`"));
+		let negative = serde_json::json!({ "relay_chain": "kusama", "para_id": -1 });
+		assert!(Extensions::check_json(&negative).unwrap_err().contains("32-bit"));
+		let complete = serde_json::json!({ "relay_chain": "kusama", "para_id": 2019 });
+		assert_eq!(Extensions::check_json(&complete), Ok(()));
+	}
+
+	#[test]
+	fn cli_para_id_must_match_spec() {
+		let config = NetworkConfig::from_toml(builtin_network("kpron-dev").unwrap()).unwrap();
+		let spec = config.into_chain_spec(2019.into()).unwrap();
+		assert!(checked_para_id(&spec, Some(2000)).unwrap_err().contains("--parachain-id 2000"));
+	}
+
+	#[test]
+	fn invalid_genesis_is_refused() {
+		let alice = parse_account("//Alice").unwrap();
+		let bob = parse_account("//Bob").unwrap();
+		let invulnerables = vec![
+			(alice.clone(), parse_aura("//Alice").unwrap()),
+			(bob.clone(), parse_aura("//Bob").unwrap()),
+		];
+
+		let unbonded = vec![(alice.clone(), BOND), (bob.clone(), BOND - 1)];
+		assert!(validate_genesis(&invulnerables, &unbonded, BOND, None).unwrap_err().contains("candidacy bond"));
+
+		let shared_keys = vec![invulnerables[0].clone(), (bob.clone(), invulnerables[0].1.clone())];
+		let endowed = vec![(alice.clone(), BOND), (bob.clone(), BOND)];
+		assert!(validate_genesis(&shared_keys, &endowed, BOND, None).unwrap_err().contains("Session key"));
+
+		let dust = vec![(alice.clone(), BOND), (bob.clone(), BOND), (AccountId::new([0; 32]), 1)];
+		assert!(validate_genesis(&invulnerables, &dust, BOND, None).unwrap_err().contains("existential deposit"));
+
+		assert!(validate_genesis(&invulnerables, &endowed, BOND, Some(BOND)).unwrap_err().contains("expected"));
+		assert_eq!(validate_genesis(&invulnerables, &endowed, BOND, Some(2 * BOND)), Ok(()));
+	}
+
+	#[test]
+	fn invalid_vesting_is_refused() {
+		let alice = parse_account("//Alice").unwrap();
+		let endowed = vec![(alice.clone(), 1_000)];
+
+		assert_eq!(validate_vesting(&[(alice.clone(), 0, 100, 900)], &endowed), Ok(()));
+		let bob = parse_account("//Bob").unwrap();
+		assert!(validate_vesting(&[(bob, 0, 100, 0)], &endowed).unwrap_err().contains("not endowed"));
+		assert!(validate_vesting(&[(alice.clone(), 0, 100, 1_000)], &endowed).unwrap_err().contains("nothing locked"));
+		assert!(validate_vesting(&[(alice.clone(), 0, 101, 900)], &endowed).unwrap_err().contains("per block"));
+		assert!(validate_vesting(&[(alice, 0, 0, 900)], &endowed).unwrap_err().contains("per block"));
+	}
+
+	#[test]
+	fn json_genesis_para_id_must_match_extension() {
+		let plain = serde_json::json!({
+			"relay_chain": "kusama",
+			"para_id": 2019,
+			"genesis": { "runtime": { "parachainInfo": { "parachainId": 2000 } } },
+		});
+		assert!(Extensions::check_json(&plain).unwrap_err().contains("parachainId` 2000"));
+
+		let key = [twox_128(b"ParachainInfo"), twox_128(b"ParachainId")].concat();
+		let raw = |id: u32| {
+			let mut top = serde_json::Map::new();
+			top.insert(
+				format!("0x{}", HexDisplay::from(&key)),
+				format!("0x{}", HexDisplay::from(&id.encode())).into(),
+			);
+			serde_json::json!({
+				"relay_chain": "kusama",
+				"para_id": 2019,
+				"genesis": { "raw": { "top": top } },
+			})
+		};
+		assert!(Extensions::check_json(&raw(2000)).is_err());
+		assert_eq!(Extensions::check_json(&raw(2019)), Ok(()));
+	}
+
+	#[test]
+	fn bad_accounts_are_reported() {
+		assert!(parse_account("not an address").is_err());
+		let config = NetworkConfig::from_toml(r#"
+			name = "Custom"
+			id = "custom"
+			chain_type = "Local"
+			relay_chain = "rococo-local"
+			collators = []
 
+			[[endowments]]
+			account = "//Alice"
+			balance = "lots"
+		"#);
+		assert!(config.unwrap_err().contains("invalid balance"));
 	}
 }
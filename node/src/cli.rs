@@ -1,22 +1,31 @@
-use crate::chain_spec;
-use sc_cli;
+use crate::{chain_spec, service::Sealing};
+use kpron_runtime::constants::address::{KUSAMA_SS58_PREFIX, SS58_PREFIX, SUBSTRATE_SS58_PREFIX};
+use clap::Parser;
+use sp_core::crypto::Ss58AddressFormat;
 use std::path::PathBuf;
-use structopt::StructOpt;
 
 /// Sub-commands supported by the collator.
-#[derive(Debug, StructOpt)]
+#[derive(Debug, clap::Subcommand)]
 pub enum Subcommand {
 	/// Export the genesis state of the parachain.
-	#[structopt(name = "export-genesis-state")]
+	#[clap(name = "export-genesis-state")]
 	ExportGenesisState(ExportGenesisStateCommand),
 
 	/// Export the genesis wasm of the parachain.
-	#[structopt(name = "export-genesis-wasm")]
+	#[clap(name = "export-genesis-wasm")]
 	ExportGenesisWasm(ExportGenesisWasmCommand),
 
+	/// Export the runtime metadata of the parachain.
+	#[clap(name = "export-metadata")]
+	ExportMetadata(ExportMetadataCommand),
+
 	/// Build a chain specification.
 	BuildSpec(sc_cli::BuildSpecCmd),
 
+	/// Generate a chain specification from a network config file.
+	#[clap(name = "generate-spec")]
+	GenerateSpec(GenerateSpecCommand),
+
 	/// Validate blocks.
 	CheckBlock(sc_cli::CheckBlockCmd),
 
@@ -35,64 +44,222 @@ pub enum Subcommand {
 	/// Revert the chain to a previous state.
 	Revert(sc_cli::RevertCmd),
 
-	/// The custom benchmark subcommmand benchmarking runtime pallets.
-	#[structopt(name = "benchmark", about = "Benchmark runtime pallets.")]
+	/// Key utilities, and conversion of addresses between formats.
+	#[clap(subcommand)]
+	Key(KeyCmd),
+
+	/// Sub-commands concerned with benchmarking.
+	#[clap(subcommand)]
 	Benchmark(frame_benchmarking_cli::BenchmarkCmd),
+
+	/// Try running the runtime's migrations and blocks against live or snapshotted state.
+	#[cfg(feature = "try-runtime")]
+	TryRuntime(try_runtime_cli::TryRuntimeCmd),
+
+	/// Errors since the binary was not built with `--features try-runtime`.
+	#[cfg(not(feature = "try-runtime"))]
+	TryRuntime,
 }
 
 /// Command for exporting the genesis state of the parachain
-#[derive(Debug, StructOpt)]
+#[derive(Debug, Parser)]
 pub struct ExportGenesisStateCommand {
 	/// Output file name or stdout if unspecified.
-	#[structopt(parse(from_os_str))]
+	#[clap(parse(from_os_str))]
 	pub output: Option<PathBuf>,
 
 	/// Id of the parachain this state is for.
 	///
 	/// Default: 100
-	#[structopt(long)]
+	#[clap(long)]
 	pub parachain_id: Option<u32>,
 
 	/// Write output in binary. Default is to write in hex.
-	#[structopt(short, long)]
+	#[clap(short, long)]
 	pub raw: bool,
 
 	/// The name of the chain for that the genesis state should be exported.
-	#[structopt(long)]
+	#[clap(long)]
 	pub chain: Option<String>,
 }
 
 /// Command for exporting the genesis wasm file.
-#[derive(Debug, StructOpt)]
+#[derive(Debug, Parser)]
 pub struct ExportGenesisWasmCommand {
 	/// Output file name or stdout if unspecified.
-	#[structopt(parse(from_os_str))]
+	#[clap(parse(from_os_str))]
 	pub output: Option<PathBuf>,
 
 	/// Write output in binary. Default is to write in hex.
-	#[structopt(short, long)]
+	#[clap(short, long)]
 	pub raw: bool,
 
 	/// The name of the chain for that the genesis wasm file should be exported.
-	#[structopt(long)]
+	#[clap(long)]
+	pub chain: Option<String>,
+}
+
+/// Format of the exported runtime metadata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataFormat {
+	/// SCALE encoded, as a `0x` prefixed hex string.
+	Hex,
+	/// SCALE encoded bytes.
+	Binary,
+	/// Decoded and serialized as JSON.
+	Json,
+}
+
+impl std::str::FromStr for MetadataFormat {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"hex" => Ok(Self::Hex),
+			"binary" | "raw" => Ok(Self::Binary),
+			"json" => Ok(Self::Json),
+			_ => Err(format!("Unknown metadata format `{}`, expected hex, binary or json", s)),
+		}
+	}
+}
+
+/// Command for exporting the runtime metadata.
+#[derive(Debug, Parser)]
+pub struct ExportMetadataCommand {
+	/// Output file name or stdout if unspecified.
+	#[clap(parse(from_os_str))]
+	pub output: Option<PathBuf>,
+
+	/// Output format: `hex`, `binary` or `json`.
+	#[clap(long, default_value = "hex")]
+	pub format: MetadataFormat,
+
+	/// Runtime wasm file to read the metadata from, instead of the chain's genesis runtime.
+	#[clap(long, parse(from_os_str), conflicts_with = "chain")]
+	pub wasm: Option<PathBuf>,
+
+	/// The name of the chain whose genesis runtime the metadata should be exported from.
+	#[clap(long)]
 	pub chain: Option<String>,
 }
 
-#[derive(Debug, StructOpt)]
-#[structopt(settings = &[
-	structopt::clap::AppSettings::GlobalVersion,
-	structopt::clap::AppSettings::ArgsNegateSubcommands,
-	structopt::clap::AppSettings::SubcommandsNegateReqs,
-])]
+/// Key utilities of `sc_cli`, and address conversion.
+#[derive(Debug, clap::Subcommand)]
+pub enum KeyCmd {
+	/// Convert an address between the kpron, Kusama and generic Substrate formats.
+	Convert(ConvertAddressCommand),
+
+	#[clap(flatten)]
+	Base(sc_cli::KeySubcommand),
+}
+
+/// An SS58 address format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressFormat {
+	Kpron,
+	Kusama,
+	Substrate,
+}
+
+impl AddressFormat {
+	/// All formats, in the order `key convert` prints them.
+	pub const ALL: [AddressFormat; 3] =
+		[AddressFormat::Kpron, AddressFormat::Kusama, AddressFormat::Substrate];
+
+	pub fn prefix(self) -> u16 {
+		match self {
+			AddressFormat::Kpron => SS58_PREFIX,
+			AddressFormat::Kusama => KUSAMA_SS58_PREFIX,
+			AddressFormat::Substrate => SUBSTRATE_SS58_PREFIX,
+		}
+	}
+
+	pub fn ss58_format(self) -> Ss58AddressFormat {
+		Ss58AddressFormat::custom(self.prefix())
+	}
+
+	pub fn name(self) -> &'static str {
+		match self {
+			AddressFormat::Kpron => "kpron",
+			AddressFormat::Kusama => "kusama",
+			AddressFormat::Substrate => "substrate",
+		}
+	}
+}
+
+impl std::str::FromStr for AddressFormat {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		AddressFormat::ALL
+			.iter()
+			.copied()
+			.find(|format| format.name() == s)
+			.ok_or_else(|| format!("Unknown address format `{}`, expected kpron, kusama or substrate", s))
+	}
+}
+
+/// Command for converting an address between formats.
+#[derive(Debug, Parser)]
+pub struct ConvertAddressCommand {
+	/// SS58 address in any format, or `0x` prefixed hex public key.
+	pub address: String,
+
+	/// Format to convert to: `kpron`, `kusama` or `substrate`. All of them if unspecified.
+	#[clap(long)]
+	pub to: Option<AddressFormat>,
+}
+
+/// Command for generating a chain spec from a network config file.
+#[derive(Debug, Parser)]
+pub struct GenerateSpecCommand {
+	/// Network config file, in TOML, or JSON if its extension is `.json`.
+	#[clap(long, parse(from_os_str))]
+	pub config: PathBuf,
+
+	/// Output file name or stdout if unspecified.
+	#[clap(parse(from_os_str))]
+	pub output: Option<PathBuf>,
+
+	/// Id of the parachain, unless set by the config.
+	///
+	/// Default: 2019
+	#[clap(long)]
+	pub parachain_id: Option<u32>,
+
+	/// Emit the raw chain spec, with the genesis storage instead of the genesis config.
+	#[clap(long)]
+	pub raw: bool,
+}
+
+#[derive(Debug, Parser)]
+#[clap(
+	propagate_version = true,
+	args_conflicts_with_subcommands = true,
+	subcommand_negates_reqs = true
+)]
 pub struct Cli {
-	#[structopt(subcommand)]
+	#[clap(subcommand)]
 	pub subcommand: Option<Subcommand>,
 
-	#[structopt(flatten)]
+	#[clap(flatten)]
 	pub run: cumulus_client_cli::RunCmd,
 
+	/// Id of the parachain, unless set by the chain spec.
+	#[clap(long)]
+	pub parachain_id: Option<u32>,
+
+	/// Run without a relay chain, sealing blocks locally. For development only.
+	#[clap(long)]
+	pub dev_standalone: bool,
+
+	/// How blocks are sealed with `--dev-standalone`: `instant` when transactions arrive, or
+	/// `manual` on `engine_createBlock` calls only.
+	#[clap(long, default_value = "instant")]
+	pub sealing: Sealing,
+
 	/// Relaychain arguments
-	#[structopt(raw = true)]
+	#[clap(raw = true)]
 	pub relaychain_args: Vec<String>,
 }
 
@@ -123,7 +290,7 @@ impl RelayChainCli {
 		Self {
 			base_path,
 			chain_id,
-			base: polkadot_cli::RunCmd::from_iter(relay_chain_args),
+			base: polkadot_cli::RunCmd::parse_from(relay_chain_args),
 		}
 	}
 }
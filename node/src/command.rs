@@ -1,23 +1,24 @@
 use crate::{
 	chain_spec,
-	cli::{Cli, RelayChainCli, Subcommand},
+	cli::{AddressFormat, Cli, KeyCmd, MetadataFormat, RelayChainCli, Subcommand},
 	service::{
 		KpronParachainRuntimeExecutor, new_partial, Block,
 	},
 };
-use codec::Encode;
+use codec::{Decode, Encode};
 use cumulus_client_service::genesis::generate_genesis_block;
 use cumulus_primitives_core::ParaId;
+use frame_benchmarking_cli::BenchmarkCmd;
 use log::info;
-use polkadot_parachain::primitives::AccountIdConversion;
 use sc_cli::{
 	ChainSpec, CliConfiguration, DefaultConfigurationValues, ImportParams, KeystoreParams,
 	NetworkParams, Result, RuntimeVersion, SharedParams, SubstrateCli,
 };
 use sc_service::config::{BasePath, PrometheusConfig};
-use sp_core::hexdisplay::HexDisplay;
-use sp_runtime::traits::Block as BlockT;
-use std::{io::Write, net::SocketAddr};
+use sp_core::{crypto::Ss58Codec, hexdisplay::HexDisplay};
+use sp_runtime::traits::{AccountIdConversion, Block as BlockT};
+use parachains_common::AccountId;
+use std::{io::Write, net::SocketAddr, str::FromStr};
 
 const DEFAULT_PARA_ID: u32 = 2019;
 
@@ -25,14 +26,11 @@ fn load_spec(
 	id: &str,
 	para_id: ParaId,
 ) -> std::result::Result<Box<dyn sc_service::ChainSpec>, String> {
-	Ok(match id {
-		"" | "kpron" => Box::new(chain_spec::kpron_config(para_id)),
-		"kpron-dev" => Box::new(chain_spec::kpron_dev_config(para_id)),
-		"kpron-local" => Box::new(chain_spec::kpron_local_config(para_id)),
-		"kpron-testnet" => Box::new(chain_spec::kpron_testnet_config(para_id)),
-		path => Box::new(chain_spec::ChainSpec::from_json_file(
-			std::path::PathBuf::from(path),
-		)?),
+	Ok(match chain_spec::builtin_network(id) {
+		Some(config) => {
+			Box::new(chain_spec::NetworkConfig::from_toml(config)?.into_chain_spec(para_id)?)
+		}
+		None => Box::new(chain_spec::from_json_file(std::path::Path::new(id))?),
 	})
 }
 
@@ -68,7 +66,7 @@ impl SubstrateCli for Cli {
 	}
 
 	fn load_spec(&self, id: &str) -> std::result::Result<Box<dyn sc_service::ChainSpec>, String> {
-		load_spec(id, self.run.parachain_id.unwrap_or(DEFAULT_PARA_ID).into())
+		load_spec(id, self.parachain_id.unwrap_or(DEFAULT_PARA_ID).into())
 	}
 
 	fn native_runtime_version(_: &Box<dyn ChainSpec>) -> &'static RuntimeVersion {
@@ -124,6 +122,26 @@ fn extract_genesis_wasm(chain_spec: &Box<dyn sc_service::ChainSpec>) -> Result<V
 		.ok_or_else(|| "Could not find wasm file in genesis state!".into())
 }
 
+/// Call `Metadata_metadata` on the given runtime and return the SCALE encoded metadata.
+fn runtime_metadata(wasm: &[u8]) -> Result<Vec<u8>> {
+	let executor = sc_executor::WasmExecutor::<sp_io::SubstrateHostFunctions>::new(
+		sc_executor::WasmExecutionMethod::Interpreted,
+		None,
+		1,
+		None,
+		8,
+	);
+	let blob = sc_executor_common::runtime_blob::RuntimeBlob::uncompress_if_needed(wasm)
+		.map_err(|e| format!("Invalid runtime wasm: {}", e))?;
+	let mut ext = sp_io::TestExternalities::default();
+	let encoded = executor
+		.uncached_call(blob, &mut ext.ext(), true, "Metadata_metadata", &[])
+		.map_err(|e| format!("Error calling `Metadata_metadata`: {}", e))?;
+	let metadata = sp_core::OpaqueMetadata::decode(&mut &encoded[..])
+		.map_err(|e| format!("Invalid metadata returned by the runtime: {}", e))?;
+	Ok(metadata.to_vec())
+}
+
 macro_rules! construct_async_run {
 	(|$components:ident, $cli:ident, $cmd:ident, $config:ident| $( $code:tt )* ) => {{
 		let runner = $cli.create_runner($cmd)?;
@@ -138,15 +156,41 @@ macro_rules! construct_async_run {
 	}}
 }
 
+/// The account of an SS58 address in any format, or of a `0x` prefixed hex public key.
+fn parse_any_address(address: &str) -> std::result::Result<AccountId, String> {
+	if address.starts_with("0x") {
+		AccountId::from_str(address).map_err(|e| format!("Invalid public key {}: {}", address, e))
+	} else {
+		AccountId::from_ss58check_with_version(address)
+			.map(|(account, _)| account)
+			.map_err(|e| format!("Invalid address {}: {:?}", address, e))
+	}
+}
+
 /// Parse command line arguments into service configuration.
 pub fn run() -> Result<()> {
 	let cli = Cli::from_args();
+	// Addresses are printed in the kpron format unless asked otherwise.
+	sp_core::crypto::set_default_ss58_version(AddressFormat::Kpron.ss58_format());
 
 	match &cli.subcommand {
 		Some(Subcommand::BuildSpec(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
 			runner.sync_run(|config| cmd.run(config.chain_spec, config.network))
 		}
+		Some(Subcommand::GenerateSpec(params)) => {
+			let spec = chain_spec::NetworkConfig::from_file(&params.config)?
+				.into_chain_spec(params.parachain_id.unwrap_or(DEFAULT_PARA_ID).into())?;
+			let json = sc_service::chain_ops::build_spec(&spec, params.raw)?;
+
+			if let Some(output) = &params.output {
+				std::fs::write(output, json)?;
+			} else {
+				std::io::stdout().write_all(json.as_bytes())?;
+			}
+
+			Ok(())
+		}
 		Some(Subcommand::CheckBlock(cmd)) => {
 			construct_async_run!(|components, cli, cmd, config| {
 				Ok(cmd.run(components.client, components.import_queue))
@@ -181,7 +225,7 @@ pub fn run() -> Result<()> {
 				let polkadot_config = SubstrateCli::create_configuration(
 					&polkadot_cli,
 					&polkadot_cli,
-					config.task_executor.clone(),
+					config.tokio_handle.clone(),
 				)
 				.map_err(|err| format!("Relay chain argument error: {}", err))?;
 
@@ -189,17 +233,33 @@ pub fn run() -> Result<()> {
 			})
 		}
 		Some(Subcommand::Revert(cmd)) => construct_async_run!(|components, cli, cmd, config| {
-			Ok(cmd.run(components.client, components.backend))
+			Ok(cmd.run(components.client, components.backend, None))
 		}),
+		Some(Subcommand::Key(KeyCmd::Base(cmd))) => cmd.run(&cli),
+		Some(Subcommand::Key(KeyCmd::Convert(params))) => {
+			let account = parse_any_address(&params.address)?;
+			match params.to {
+				Some(format) => println!("{}", account.to_ss58check_with_version(format.ss58_format())),
+				None => for format in AddressFormat::ALL.iter() {
+					let address = account.to_ss58check_with_version(format.ss58_format());
+					println!("{:<10} {}", format!("{}:", format.name()), address);
+				},
+			}
+
+			Ok(())
+		}
 		Some(Subcommand::ExportGenesisState(params)) => {
 			let mut builder = sc_cli::LoggerBuilder::new("");
 			builder.with_profiling(sc_tracing::TracingReceiver::Log, "");
 			let _ = builder.init();
 
-			let block: crate::service::Block = generate_genesis_block(&load_spec(
+			let spec = load_spec(
 				&params.chain.clone().unwrap_or_default(),
 				params.parachain_id.unwrap_or(DEFAULT_PARA_ID).into(),
-			)?)?;
+			)?;
+			chain_spec::checked_para_id(&*spec, params.parachain_id)?;
+			let state_version = Cli::native_runtime_version(&spec).state_version();
+			let block: crate::service::Block = generate_genesis_block(&*spec, state_version)?;
 			let raw_header = block.header().encode();
 			let output_buf = if params.raw {
 				raw_header
@@ -236,22 +296,75 @@ pub fn run() -> Result<()> {
 
 			Ok(())
 		}
-		Some(Subcommand::Benchmark(cmd)) => {
-			if cfg!(feature = "runtime-benchmarks") {
-				let runner = cli.create_runner(cmd)?;
-				runner.sync_run(|config| cmd.run::<Block, KpronParachainRuntimeExecutor>(config))
+		Some(Subcommand::ExportMetadata(params)) => {
+			let mut builder = sc_cli::LoggerBuilder::new("");
+			builder.with_profiling(sc_tracing::TracingReceiver::Log, "");
+			let _ = builder.init();
+
+			let wasm = match &params.wasm {
+				Some(path) => std::fs::read(path)?,
+				None => extract_genesis_wasm(&cli.load_spec(&params.chain.clone().unwrap_or_default())?)?,
+			};
+			let metadata = runtime_metadata(&wasm)?;
+			let output_buf = match params.format {
+				MetadataFormat::Hex => format!("0x{:?}", HexDisplay::from(&metadata)).into_bytes(),
+				MetadataFormat::Binary => metadata,
+				MetadataFormat::Json => {
+					let decoded = frame_metadata::RuntimeMetadataPrefixed::decode(&mut &metadata[..])
+						.map_err(|e| format!("Error decoding metadata: {}", e))?;
+					serde_json::to_vec_pretty(&decoded)
+						.map_err(|e| format!("Error serializing metadata: {}", e))?
+				}
+			};
+
+			if let Some(output) = &params.output {
+				std::fs::write(output, output_buf)?;
 			} else {
-				Err("Benchmarking wasn't enabled when building the node. \
-				You can enable it with `--features runtime-benchmarks`.".into())
+				std::io::stdout().write_all(&output_buf)?;
 			}
+
+			Ok(())
 		}
-		None => {
+		Some(Subcommand::Benchmark(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			match cmd {
+				BenchmarkCmd::Pallet(cmd) => if cfg!(feature = "runtime-benchmarks") {
+					runner.sync_run(|config| cmd.run::<Block, KpronParachainRuntimeExecutor>(config))
+				} else {
+					Err("Benchmarking wasn't enabled when building the node. \
+					You can enable it with `--features runtime-benchmarks`.".into())
+				},
+				_ => Err("Only pallet benchmarks are supported".into()),
+			}
+		}
+		#[cfg(feature = "try-runtime")]
+		Some(Subcommand::TryRuntime(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			let registry = &runner.config().prometheus_config.as_ref().map(|cfg| &cfg.registry);
+			let task_manager =
+				sc_service::TaskManager::new(runner.config().tokio_handle.clone(), *registry)
+					.map_err(|e| format!("Error: {:?}", e))?;
+
+			runner.async_run(|config| {
+				Ok((cmd.run::<Block, KpronParachainRuntimeExecutor>(config), task_manager))
+			})
+		}
+		#[cfg(not(feature = "try-runtime"))]
+		Some(Subcommand::TryRuntime) => Err("TryRuntime wasn't enabled when building the node. \
+			You can enable it with `--features try-runtime`.".into()),
+		None if cli.dev_standalone => {
 			let runner = cli.create_runner(&cli.run.normalize())?;
 
 			runner.run_node_until_exit(|config| async move {
-				let para_id =
-					chain_spec::Extensions::try_get(&*config.chain_spec).map(|e| e.para_id);
+				info!("Running standalone with {:?} sealing, without a relay chain", cli.sealing);
+				crate::service::start_dev_node(config, cli.sealing).map_err(Into::into)
+			})
+		}
+		None => {
+			let runner = cli.create_runner(&cli.run.normalize())?;
+			let collator_options = cli.run.collator_options();
 
+			runner.run_node_until_exit(|config| async move {
 				let polkadot_cli = RelayChainCli::new(
 					&config,
 					[RelayChainCli::executable_name().to_string()]
@@ -259,18 +372,21 @@ pub fn run() -> Result<()> {
 						.chain(cli.relaychain_args.iter()),
 				);
 
-				let id = ParaId::from(cli.run.parachain_id.or(para_id).unwrap_or(DEFAULT_PARA_ID));
+				let id = chain_spec::checked_para_id(&*config.chain_spec, cli.parachain_id)?;
 
 				let parachain_account =
-					AccountIdConversion::<polkadot_primitives::v0::AccountId>::into_account(&id);
+					AccountIdConversion::<polkadot_primitives::v2::AccountId>::into_account(&id);
 
+				let state_version =
+					Cli::native_runtime_version(&config.chain_spec).state_version();
 				let block: crate::service::Block =
-					generate_genesis_block(&config.chain_spec).map_err(|e| format!("{:?}", e))?;
+					generate_genesis_block(&*config.chain_spec, state_version)
+						.map_err(|e| format!("{:?}", e))?;
 				let genesis_state = format!("0x{:?}", HexDisplay::from(&block.header().encode()));
 
-				let task_executor = config.task_executor.clone();
+				let tokio_handle = config.tokio_handle.clone();
 				let polkadot_config =
-					SubstrateCli::create_configuration(&polkadot_cli, &polkadot_cli, task_executor)
+					SubstrateCli::create_configuration(&polkadot_cli, &polkadot_cli, tokio_handle)
 						.map_err(|err| format!("Relay chain argument error: {}", err))?;
 
 				info!("Parachain id: {:?}", id);
@@ -288,6 +404,7 @@ pub fn run() -> Result<()> {
 				crate::service::start_node::<kpron_runtime::RuntimeApi, KpronParachainRuntimeExecutor>(
 					config,
 					polkadot_config,
+					collator_options,
 					id,
 				)
 					.await
@@ -352,11 +469,24 @@ impl CliConfiguration<Self> for RelayChainCli {
 		self.base.base.rpc_ws(default_listen_port)
 	}
 
-	fn prometheus_config(&self, default_listen_port: u16) -> Result<Option<PrometheusConfig>> {
-		self.base.base.prometheus_config(default_listen_port)
+	fn prometheus_config(
+		&self,
+		default_listen_port: u16,
+		chain_spec: &Box<dyn ChainSpec>,
+	) -> Result<Option<PrometheusConfig>> {
+		self.base.base.prometheus_config(default_listen_port, chain_spec)
 	}
 
-	fn init<C: SubstrateCli>(&self) -> Result<()> {
+	fn init<F>(
+		&self,
+		_support_url: &String,
+		_impl_version: &String,
+		_logger_hook: F,
+		_config: &sc_service::Configuration,
+	) -> Result<()>
+	where
+		F: FnOnce(&mut sc_cli::LoggerBuilder, &sc_service::Configuration),
+	{
 		unreachable!("PolkadotCli is never initialized; qed");
 	}
 
@@ -394,10 +524,6 @@ impl CliConfiguration<Self> for RelayChainCli {
 		self.base.base.rpc_cors(is_dev)
 	}
 
-	fn telemetry_external_transport(&self) -> Result<Option<sc_service::config::ExtTransport>> {
-		self.base.base.telemetry_external_transport()
-	}
-
 	fn default_heap_pages(&self) -> Result<Option<u64>> {
 		self.base.base.default_heap_pages()
 	}
@@ -1,50 +1,64 @@
-
-use cumulus_client_consensus_aura::{
-	build_aura_consensus, BuildAuraConsensusParams, SlotProportion,
-};
+use cumulus_client_cli::CollatorOptions;
+use cumulus_client_consensus_aura::{AuraConsensus, BuildAuraConsensusParams, SlotProportion};
 use cumulus_client_consensus_common::{
-	ParachainConsensus, ParachainCandidate, ParachainBlockImport,
+	ParachainBlockImport, ParachainCandidate, ParachainConsensus,
 };
-use cumulus_client_network::build_block_announce_validator;
+use cumulus_client_network::BlockAnnounceValidator;
 use cumulus_client_service::{
 	prepare_node_config, start_collator, start_full_node, StartCollatorParams, StartFullNodeParams,
 };
 use cumulus_primitives_core::{
-	ParaId, relay_chain::v1::{Hash as PHash, PersistedValidationData},
+	relay_chain::v2::{Hash as PHash, PersistedValidationData},
+	ParaId,
 };
+use cumulus_relay_chain_inprocess_interface::build_inprocess_relay_chain;
+use cumulus_relay_chain_interface::{RelayChainError, RelayChainInterface, RelayChainResult};
+use cumulus_relay_chain_rpc_interface::RelayChainRPCInterface;
+use polkadot_service::CollatorPair;
 
+use jsonrpsee::RpcModule;
 use sc_client_api::ExecutorProvider;
-use sc_executor::native_executor_instance;
+use sc_consensus::{
+	import_queue::{BasicQueue, Verifier as VerifierT},
+	BlockImportParams,
+};
+use sc_executor::NativeElseWasmExecutor;
 use sc_network::NetworkService;
-use sc_service::{Configuration, PartialComponents, Role, TFullBackend, TFullClient, TaskManager};
+use sc_service::{Configuration, PartialComponents, TFullBackend, TFullClient, TaskManager};
 use sc_telemetry::{Telemetry, TelemetryHandle, TelemetryWorker, TelemetryWorkerHandle};
-use sp_api::{ConstructRuntimeApi, ApiExt};
-use sp_consensus::{
-	BlockImportParams, BlockOrigin, SlotData,
-	import_queue::{BasicQueue, CacheKeyId, Verifier as VerifierT},
-};
+use sp_api::{ApiExt, ConstructRuntimeApi};
+use sp_consensus::CacheKeyId;
 use sp_consensus_aura::{sr25519::AuthorityId as AuraId, AuraApi};
 use sp_keystore::SyncCryptoStorePtr;
 use sp_runtime::{traits::{BlakeTwo256, Header as HeaderT}, generic::BlockId};
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 use substrate_prometheus_endpoint::Registry;
 use futures::lock::Mutex;
 use cumulus_client_consensus_relay_chain::Verifier as RelayChainVerifier;
 
-pub use sc_executor::NativeExecutor;
-
 type BlockNumber = u32;
 type Header = sp_runtime::generic::Header<BlockNumber, sp_runtime::traits::BlakeTwo256>;
 pub type Block = sp_runtime::generic::Block<Header, sp_runtime::OpaqueExtrinsic>;
 type Hash = sp_core::H256;
 
+/// The client of a kpron node, running the runtime natively if its version matches.
+type ParachainClient<RuntimeApi, Executor> =
+	TFullClient<Block, RuntimeApi, NativeElseWasmExecutor<Executor>>;
+
 // Native executor instance.
-native_executor_instance!(
-	pub KpronParachainRuntimeExecutor,
-	kpron_runtime::api::dispatch,
-	kpron_runtime::native_version,
-	frame_benchmarking::benchmarking::HostFunctions,
-);
+pub struct KpronParachainRuntimeExecutor;
+
+impl sc_executor::NativeExecutionDispatch for KpronParachainRuntimeExecutor {
+	type ExtendHostFunctions = frame_benchmarking::benchmarking::HostFunctions;
+
+	fn dispatch(method: &str, data: &[u8]) -> Option<Vec<u8>> {
+		kpron_runtime::api::dispatch(method, data)
+	}
+
+	fn native_version() -> sc_executor::NativeVersion {
+		kpron_runtime::native_version()
+	}
+}
 
 /// Starts a `ServiceBuilder` for a full service.
 ///
@@ -55,17 +69,17 @@ pub fn new_partial<RuntimeApi, Executor, BIQ>(
 	build_import_queue: BIQ,
 ) -> Result<
 	PartialComponents<
-		TFullClient<Block, RuntimeApi, Executor>,
+		ParachainClient<RuntimeApi, Executor>,
 		TFullBackend<Block>,
 		(),
-		sp_consensus::DefaultImportQueue<Block, TFullClient<Block, RuntimeApi, Executor>>,
-		sc_transaction_pool::FullPool<Block, TFullClient<Block, RuntimeApi, Executor>>,
+		sc_consensus::DefaultImportQueue<Block, ParachainClient<RuntimeApi, Executor>>,
+		sc_transaction_pool::FullPool<Block, ParachainClient<RuntimeApi, Executor>>,
 		(Option<Telemetry>, Option<TelemetryWorkerHandle>),
 	>,
 	sc_service::Error,
 >
 where
-	RuntimeApi: ConstructRuntimeApi<Block, TFullClient<Block, RuntimeApi, Executor>>
+	RuntimeApi: ConstructRuntimeApi<Block, ParachainClient<RuntimeApi, Executor>>
 		+ Send
 		+ Sync
 		+ 'static,
@@ -80,12 +94,12 @@ where
 	sc_client_api::StateBackendFor<TFullBackend<Block>, Block>: sp_api::StateBackend<BlakeTwo256>,
 	Executor: sc_executor::NativeExecutionDispatch + 'static,
 	BIQ: FnOnce(
-		Arc<TFullClient<Block, RuntimeApi, Executor>>,
+		Arc<ParachainClient<RuntimeApi, Executor>>,
 		&Configuration,
 		Option<TelemetryHandle>,
 		&TaskManager,
 	) -> Result<
-		sp_consensus::DefaultImportQueue<Block, TFullClient<Block, RuntimeApi, Executor>>,
+		sc_consensus::DefaultImportQueue<Block, ParachainClient<RuntimeApi, Executor>>,
 		sc_service::Error,
 	>,
 {
@@ -100,17 +114,25 @@ where
 		})
 		.transpose()?;
 
+	let executor = NativeElseWasmExecutor::<Executor>::new(
+		config.wasm_method,
+		config.default_heap_pages,
+		config.max_runtime_instances,
+		config.runtime_cache_size,
+	);
+
 	let (client, backend, keystore_container, task_manager) =
-		sc_service::new_full_parts::<Block, RuntimeApi, Executor>(
-			&config,
+		sc_service::new_full_parts::<Block, RuntimeApi, _>(
+			config,
 			telemetry.as_ref().map(|(_, telemetry)| telemetry.handle()),
+			executor,
 		)?;
 	let client = Arc::new(client);
 
 	let telemetry_worker_handle = telemetry.as_ref().map(|(worker, _)| worker.handle());
 
 	let telemetry = telemetry.map(|(worker, telemetry)| {
-		task_manager.spawn_handle().spawn("telemetry", worker.run());
+		task_manager.spawn_handle().spawn("telemetry", None, worker.run());
 		telemetry
 	});
 
@@ -118,7 +140,7 @@ where
 		config.transaction_pool.clone(),
 		config.role.is_authority().into(),
 		config.prometheus_registry(),
-		task_manager.spawn_handle(),
+		task_manager.spawn_essential_handle(),
 		client.clone(),
 	);
 
@@ -143,6 +165,28 @@ where
 	Ok(params)
 }
 
+/// The relay chain the node follows: a full node run in-process, or the one at
+/// `--relay-chain-rpc-url` if given.
+async fn build_relay_chain_interface(
+	polkadot_config: Configuration,
+	parachain_config: &Configuration,
+	telemetry_worker_handle: Option<TelemetryWorkerHandle>,
+	task_manager: &mut TaskManager,
+	collator_options: CollatorOptions,
+) -> RelayChainResult<(Arc<(dyn RelayChainInterface + 'static)>, Option<CollatorPair>)> {
+	match collator_options.relay_chain_rpc_url {
+		Some(relay_chain_url) =>
+			Ok((Arc::new(RelayChainRPCInterface::new(relay_chain_url).await?) as Arc<_>, None)),
+		None => build_inprocess_relay_chain(
+			polkadot_config,
+			parachain_config,
+			telemetry_worker_handle,
+			task_manager,
+			None,
+		),
+	}
+}
+
 /// Start a node with the given parachain `Configuration` and relay chain `Configuration`.
 ///
 /// This is the actual implementation that is abstract over the executor and the runtime api.
@@ -150,13 +194,14 @@ where
 async fn start_node_impl<RuntimeApi, Executor, RB, BIQ, BIC>(
 	parachain_config: Configuration,
 	polkadot_config: Configuration,
+	collator_options: CollatorOptions,
 	id: ParaId,
 	rpc_ext_builder: RB,
 	build_import_queue: BIQ,
 	build_consensus: BIC,
-) -> sc_service::error::Result<(TaskManager, Arc<TFullClient<Block, RuntimeApi, Executor>>)>
+) -> sc_service::error::Result<(TaskManager, Arc<ParachainClient<RuntimeApi, Executor>>)>
 	where
-		RuntimeApi: ConstructRuntimeApi<Block, TFullClient<Block, RuntimeApi, Executor>>
+		RuntimeApi: ConstructRuntimeApi<Block, ParachainClient<RuntimeApi, Executor>>
 		+ Send
 		+ Sync
 		+ 'static,
@@ -172,63 +217,59 @@ async fn start_node_impl<RuntimeApi, Executor, RB, BIQ, BIC>(
 		sc_client_api::StateBackendFor<TFullBackend<Block>, Block>: sp_api::StateBackend<BlakeTwo256>,
 		Executor: sc_executor::NativeExecutionDispatch + 'static,
 		RB: Fn(
-			Arc<TFullClient<Block, RuntimeApi, Executor>>,
-		) -> jsonrpc_core::IoHandler<sc_rpc::Metadata>
+			Arc<ParachainClient<RuntimeApi, Executor>>,
+		) -> Result<RpcModule<()>, sc_service::Error>
 		+ Send
 		+ 'static,
 		BIQ: FnOnce(
-			Arc<TFullClient<Block, RuntimeApi, Executor>>,
+			Arc<ParachainClient<RuntimeApi, Executor>>,
 			&Configuration,
 			Option<TelemetryHandle>,
 			&TaskManager,
 		) -> Result<
-			sp_consensus::DefaultImportQueue<Block, TFullClient<Block, RuntimeApi, Executor>>,
+			sc_consensus::DefaultImportQueue<Block, ParachainClient<RuntimeApi, Executor>>,
 			sc_service::Error,
 		>,
 		BIC: FnOnce(
-			Arc<TFullClient<Block, RuntimeApi, Executor>>,
+			Arc<ParachainClient<RuntimeApi, Executor>>,
 			Option<&Registry>,
 			Option<TelemetryHandle>,
 			&TaskManager,
-			&polkadot_service::NewFull<polkadot_service::Client>,
-			Arc<sc_transaction_pool::FullPool<Block, TFullClient<Block, RuntimeApi, Executor>>>,
+			Arc<dyn RelayChainInterface>,
+			Arc<sc_transaction_pool::FullPool<Block, ParachainClient<RuntimeApi, Executor>>>,
 			Arc<NetworkService<Block, Hash>>,
 			SyncCryptoStorePtr,
 			bool,
 		) -> Result<Box<dyn ParachainConsensus<Block>>, sc_service::Error>,
 {
-	if matches!(parachain_config.role, Role::Light) {
-		return Err("Light client not supported!".into());
-	}
-
 	let parachain_config = prepare_node_config(parachain_config);
 
 	let params = new_partial::<RuntimeApi, Executor, BIQ>(&parachain_config, build_import_queue)?;
 	let (mut telemetry, telemetry_worker_handle) = params.other;
 
-	let relay_chain_full_node = cumulus_client_service::build_polkadot_full_node(
+	let client = params.client.clone();
+	let backend = params.backend.clone();
+	let mut task_manager = params.task_manager;
+
+	let (relay_chain_interface, collator_key) = build_relay_chain_interface(
 		polkadot_config,
+		&parachain_config,
 		telemetry_worker_handle,
+		&mut task_manager,
+		collator_options.clone(),
 	)
-		.map_err(|e| match e {
-			polkadot_service::Error::Sub(x) => x,
-			s => format!("{}", s).into(),
-		})?;
+	.await
+	.map_err(|e| match e {
+		RelayChainError::ServiceError(polkadot_service::Error::Sub(x)) => x,
+		s => s.to_string().into(),
+	})?;
 
-	let client = params.client.clone();
-	let backend = params.backend.clone();
-	let block_announce_validator = build_block_announce_validator(
-		relay_chain_full_node.client.clone(),
-		id,
-		Box::new(relay_chain_full_node.network.clone()),
-		relay_chain_full_node.backend.clone(),
-	);
+	let block_announce_validator = BlockAnnounceValidator::new(relay_chain_interface.clone(), id);
 
 	let force_authoring = parachain_config.force_authoring;
 	let validator = parachain_config.role.is_authority();
 	let prometheus_registry = parachain_config.prometheus_registry().cloned();
 	let transaction_pool = params.transaction_pool.clone();
-	let mut task_manager = params.task_manager;
 	let import_queue = cumulus_client_service::SharedImportQueue::new(params.import_queue);
 	let (network, system_rpc_tx, start_network) =
 		sc_service::build_network(sc_service::BuildNetworkParams {
@@ -237,17 +278,17 @@ async fn start_node_impl<RuntimeApi, Executor, RB, BIQ, BIC>(
 			transaction_pool: transaction_pool.clone(),
 			spawn_handle: task_manager.spawn_handle(),
 			import_queue: import_queue.clone(),
-			on_demand: None,
-			block_announce_validator_builder: Some(Box::new(|_| block_announce_validator)),
+			block_announce_validator_builder: Some(Box::new(|_| {
+				Box::new(block_announce_validator)
+			})),
+			warp_sync: None,
 		})?;
 
 	let rpc_client = client.clone();
-	let rpc_extensions_builder = Box::new(move |_, _| rpc_ext_builder(rpc_client.clone()));
+	let rpc_builder = Box::new(move |_, _| rpc_ext_builder(rpc_client.clone()));
 
 	sc_service::spawn_tasks(sc_service::SpawnTasksParams {
-		on_demand: None,
-		remote_blockchain: None,
-		rpc_extensions_builder,
+		rpc_builder,
 		client: client.clone(),
 		transaction_pool: transaction_pool.clone(),
 		task_manager: &mut task_manager,
@@ -264,13 +305,15 @@ async fn start_node_impl<RuntimeApi, Executor, RB, BIQ, BIC>(
 		Arc::new(move |hash, data| network.announce_block(hash, data))
 	};
 
+	let relay_chain_slot_duration = Duration::from_secs(6);
+
 	if validator {
 		let parachain_consensus = build_consensus(
 			client.clone(),
 			prometheus_registry.as_ref(),
 			telemetry.as_ref().map(|t| t.handle()),
 			&task_manager,
-			&relay_chain_full_node,
+			relay_chain_interface.clone(),
 			transaction_pool,
 			network,
 			params.keystore_container.sync_keystore(),
@@ -285,10 +328,13 @@ async fn start_node_impl<RuntimeApi, Executor, RB, BIQ, BIC>(
 			announce_block,
 			client: client.clone(),
 			task_manager: &mut task_manager,
-			relay_chain_full_node,
+			relay_chain_interface,
 			spawner,
 			parachain_consensus,
 			import_queue,
+			collator_key: collator_key
+				.ok_or_else(|| "Collating needs an in-process relay chain node".to_string())?,
+			relay_chain_slot_duration,
 		};
 
 		start_collator(params).await?;
@@ -298,7 +344,9 @@ async fn start_node_impl<RuntimeApi, Executor, RB, BIQ, BIC>(
 			announce_block,
 			task_manager: &mut task_manager,
 			para_id: id,
-			relay_chain_full_node,
+			relay_chain_interface,
+			relay_chain_slot_duration,
+			import_queue,
 		};
 
 		start_full_node(params)?;
@@ -394,10 +442,7 @@ where
 {
 	async fn verify(
 		&mut self,
-		origin: BlockOrigin,
-		header: Header,
-		justifications: Option<sp_runtime::Justifications>,
-		body: Option<Vec<<Block as sp_runtime::traits::Block>::Extrinsic>>,
+		block_import: BlockImportParams<Block, ()>,
 	) -> Result<
 		(
 			BlockImportParams<Block, ()>,
@@ -405,7 +450,7 @@ where
 		),
 		String,
 	> {
-		let block_id = BlockId::hash(*header.parent_hash());
+		let block_id = BlockId::hash(*block_import.header.parent_hash());
 
 		if self
 			.client
@@ -413,29 +458,24 @@ where
 			.has_api::<dyn AuraApi<Block, AuraId>>(&block_id)
 			.unwrap_or(false)
 		{
-			self.aura_verifier
-				.get_mut()
-				.verify(origin, header, justifications, body)
-				.await
+			self.aura_verifier.get_mut().verify(block_import).await
 		} else {
-			self.relay_chain_verifier
-				.verify(origin, header, justifications, body)
-				.await
+			self.relay_chain_verifier.verify(block_import).await
 		}
 	}
 }
 
 pub fn kpron_build_import_queue<RuntimeApi, Executor>(
-	client: Arc<TFullClient<Block, RuntimeApi, Executor>>,
+	client: Arc<ParachainClient<RuntimeApi, Executor>>,
 	config: &Configuration,
 	telemetry_handle: Option<TelemetryHandle>,
 	task_manager: &TaskManager,
 ) -> Result<
-	sp_consensus::DefaultImportQueue<Block, TFullClient<Block, RuntimeApi, Executor>>,
+	sc_consensus::DefaultImportQueue<Block, ParachainClient<RuntimeApi, Executor>>,
 	sc_service::Error,
 >
 where
-	RuntimeApi: ConstructRuntimeApi<Block, TFullClient<Block, RuntimeApi, Executor>>
+	RuntimeApi: ConstructRuntimeApi<Block, ParachainClient<RuntimeApi, Executor>>
 		+ Send
 		+ Sync
 		+ 'static,
@@ -467,9 +507,9 @@ where
 				let time = sp_timestamp::InherentDataProvider::from_system_time();
 
 				let slot =
-					sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_duration(
+					sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
 						*time,
-						slot_duration.slot_duration(),
+						slot_duration,
 					);
 
 				Ok((time, slot))
@@ -507,13 +547,14 @@ where
 pub async fn start_node<RuntimeApi, Executor>(
 	parachain_config: Configuration,
 	polkadot_config: Configuration,
+	collator_options: CollatorOptions,
 	id: ParaId,
 ) -> sc_service::error::Result<(
 	TaskManager,
-	Arc<TFullClient<Block, RuntimeApi, Executor>>,
+	Arc<ParachainClient<RuntimeApi, Executor>>,
 )>
 where
-	RuntimeApi: ConstructRuntimeApi<Block, TFullClient<Block, RuntimeApi, Executor>>
+	RuntimeApi: ConstructRuntimeApi<Block, ParachainClient<RuntimeApi, Executor>>
 		+ Send
 		+ Sync
 		+ 'static,
@@ -533,25 +574,25 @@ where
 	start_node_impl::<RuntimeApi, Executor, _, _, _>(
 		parachain_config,
 		polkadot_config,
+		collator_options,
 		id,
-		|_| Default::default(),
+		|_| Ok(RpcModule::new(())),
 		kpron_build_import_queue,
 		|client,
 		 prometheus_registry,
 		 telemetry,
 		 task_manager,
-		 relay_chain_node,
+		 relay_chain_interface,
 		 transaction_pool,
 		 sync_oracle,
 		 keystore,
 		 force_authoring| {
 			let client2 = client.clone();
-			let relay_chain_backend = relay_chain_node.backend.clone();
-			let relay_chain_client = relay_chain_node.client.clone();
 			let spawn_handle = task_manager.spawn_handle();
 			let transaction_pool2 = transaction_pool.clone();
 			let telemetry2 = telemetry.clone();
 			let prometheus_registry2 = prometheus_registry.map(|r| (*r).clone());
+			let relay_chain_for_aura = relay_chain_interface.clone();
 
 			let aura_consensus = BuildOnAccess::Uninitialized(Some(
 				Box::new(move || {
@@ -567,10 +608,7 @@ where
 							telemetry2.clone(),
 						);
 
-					let relay_chain_backend2 = relay_chain_backend.clone();
-					let relay_chain_client2 = relay_chain_client.clone();
-
-					build_aura_consensus::<
+					AuraConsensus::build::<
 						sp_consensus_aura::sr25519::AuthorityPair,
 						_,
 						_,
@@ -578,29 +616,28 @@ where
 						_,
 						_,
 						_,
-						_,
-						_,
-						_,
 					>(BuildAuraConsensusParams {
 						proposer_factory,
 						create_inherent_data_providers:
 							move |_, (relay_parent, validation_data)| {
-								let parachain_inherent =
-								cumulus_primitives_parachain_inherent::ParachainInherentData::create_at_with_client(
-									relay_parent,
-									&relay_chain_client,
-									&*relay_chain_backend,
-									&validation_data,
-									id,
-								);
+								let relay_chain_for_aura = relay_chain_for_aura.clone();
 								async move {
+									let parachain_inherent =
+										cumulus_primitives_parachain_inherent::ParachainInherentData::create_at(
+											relay_parent,
+											&relay_chain_for_aura,
+											&validation_data,
+											id,
+										)
+										.await;
+
 									let time =
 										sp_timestamp::InherentDataProvider::from_system_time();
 
 									let slot =
-									sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_duration(
+									sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
 										*time,
-										slot_duration.slot_duration(),
+										slot_duration,
 									);
 
 									let parachain_inherent =
@@ -609,12 +646,10 @@ where
 												"Failed to create parachain inherent",
 											)
 										})?;
-									Ok((time, slot, parachain_inherent))
+									Ok((slot, time, parachain_inherent))
 								}
 							},
 						block_import: client2.clone(),
-						relay_chain_client: relay_chain_client2,
-						relay_chain_backend: relay_chain_backend2,
 						para_client: client2.clone(),
 						backoff_authoring_blocks: Option::<()>::None,
 						sync_oracle,
@@ -623,6 +658,8 @@ where
 						slot_duration,
 						// We got around 500ms for proposing
 						block_proposal_slot_portion: SlotProportion::new(1f32 / 24f32),
+						// And a maximum of 750ms if slots are skipped
+						max_block_proposal_slot_portion: Some(SlotProportion::new(1f32 / 16f32)),
 						telemetry: telemetry2,
 					})
 				}),
@@ -632,32 +669,29 @@ where
 				task_manager.spawn_handle(),
 				client.clone(),
 				transaction_pool,
-				prometheus_registry.clone(),
+				prometheus_registry,
 				telemetry.clone(),
 			);
 
-			let relay_chain_backend = relay_chain_node.backend.clone();
-			let relay_chain_client = relay_chain_node.client.clone();
-
 			let relay_chain_consensus =
 				cumulus_client_consensus_relay_chain::build_relay_chain_consensus(
 					cumulus_client_consensus_relay_chain::BuildRelayChainConsensusParams {
 						para_id: id,
 						proposer_factory,
 						block_import: client.clone(),
-						relay_chain_client: relay_chain_node.client.clone(),
-						relay_chain_backend: relay_chain_node.backend.clone(),
+						relay_chain_interface: relay_chain_interface.clone(),
 						create_inherent_data_providers:
 							move |_, (relay_parent, validation_data)| {
-								let parachain_inherent =
-									cumulus_primitives_parachain_inherent::ParachainInherentData::create_at_with_client(
-										relay_parent,
-										&relay_chain_client,
-										&*relay_chain_backend,
-										&validation_data,
-										id,
-									);
+								let relay_chain_interface = relay_chain_interface.clone();
 								async move {
+									let parachain_inherent =
+										cumulus_primitives_parachain_inherent::ParachainInherentData::create_at(
+											relay_parent,
+											&relay_chain_interface,
+											&validation_data,
+											id,
+										)
+										.await;
 									let parachain_inherent =
 										parachain_inherent.ok_or_else(|| {
 											Box::<dyn std::error::Error + Send + Sync>::from(
@@ -681,3 +715,215 @@ where
 	)
 	.await
 }
+
+/// How blocks are sealed by a standalone dev node.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sealing {
+	/// Seal a block as soon as a transaction enters the pool, or on `engine_createBlock`.
+	Instant,
+	/// Seal blocks only on `engine_createBlock`.
+	Manual,
+}
+
+impl std::str::FromStr for Sealing {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"instant" => Ok(Sealing::Instant),
+			"manual" => Ok(Sealing::Manual),
+			_ => Err(format!("Unknown sealing {}, expected instant or manual", s)),
+		}
+	}
+}
+
+/// Puts an Aura pre-runtime digest for the slot of the block's timestamp in each sealed block, as
+/// the runtime's Aura pallet expects one.
+struct AuraDigestProvider {
+	slot_duration: u64,
+}
+
+impl sc_consensus_manual_seal::consensus::ConsensusDataProvider<Block> for AuraDigestProvider {
+	type Transaction = sp_api::TransactionFor<
+		ParachainClient<kpron_runtime::RuntimeApi, KpronParachainRuntimeExecutor>,
+		Block,
+	>;
+
+	fn create_digest(
+		&self,
+		_parent: &Header,
+		inherents: &sp_inherents::InherentData,
+	) -> Result<sp_runtime::Digest, sc_consensus_manual_seal::Error> {
+		use sp_consensus_aura::digests::CompatibleDigestItem;
+		use sp_timestamp::TimestampInherentData;
+
+		let timestamp = inherents
+			.timestamp_inherent_data()?
+			.ok_or_else(|| sc_consensus_manual_seal::Error::StringError("No timestamp inherent".into()))?;
+		let slot = sp_consensus_aura::Slot::from(*timestamp / self.slot_duration);
+		Ok(sp_runtime::Digest {
+			logs: vec![<sp_runtime::DigestItem as CompatibleDigestItem<
+				sp_consensus_aura::sr25519::AuthoritySignature,
+			>>::aura_pre_digest(slot)],
+		})
+	}
+
+	fn append_block_import(
+		&self,
+		_parent: &Header,
+		_params: &mut BlockImportParams<Block, Self::Transaction>,
+		_inherents: &sp_inherents::InherentData,
+	) -> Result<(), sc_consensus_manual_seal::Error> {
+		Ok(())
+	}
+}
+
+fn dev_build_import_queue(
+	client: Arc<ParachainClient<kpron_runtime::RuntimeApi, KpronParachainRuntimeExecutor>>,
+	config: &Configuration,
+	_: Option<TelemetryHandle>,
+	task_manager: &TaskManager,
+) -> Result<
+	sc_consensus::DefaultImportQueue<
+		Block,
+		ParachainClient<kpron_runtime::RuntimeApi, KpronParachainRuntimeExecutor>,
+	>,
+	sc_service::Error,
+> {
+	Ok(sc_consensus_manual_seal::import_queue(
+		Box::new(client),
+		&task_manager.spawn_essential_handle(),
+		config.prometheus_registry(),
+	))
+}
+
+/// Start a standalone node for development, which seals its own blocks without a relay chain.
+///
+/// The parachain inherent is mocked, and every block gets its own Aura slot regardless of how fast
+/// blocks are sealed. Blocks can be requested with the `engine_createBlock` RPC.
+pub fn start_dev_node(
+	config: Configuration,
+	sealing: Sealing,
+) -> sc_service::error::Result<TaskManager> {
+	use futures::{stream::select, StreamExt};
+	use sc_consensus_manual_seal::{
+		rpc::{EngineCommand, ManualSeal, ManualSealApiServer},
+		run_manual_seal, ManualSealParams,
+	};
+	use sp_blockchain::HeaderBackend;
+
+	let params = new_partial::<kpron_runtime::RuntimeApi, KpronParachainRuntimeExecutor, _>(
+		&config,
+		dev_build_import_queue,
+	)?;
+	let (mut telemetry, _) = params.other;
+	let client = params.client.clone();
+	let transaction_pool = params.transaction_pool.clone();
+	let mut task_manager = params.task_manager;
+
+	let (network, system_rpc_tx, start_network) =
+		sc_service::build_network(sc_service::BuildNetworkParams {
+			config: &config,
+			client: client.clone(),
+			transaction_pool: transaction_pool.clone(),
+			spawn_handle: task_manager.spawn_handle(),
+			import_queue: params.import_queue,
+			block_announce_validator_builder: None,
+			warp_sync: None,
+		})?;
+
+	let (command_sink, rpc_commands) = futures::channel::mpsc::channel(1024);
+	let rpc_builder = Box::new(move |_, _| {
+		let mut module = RpcModule::new(());
+		module
+			.merge(ManualSeal::new(command_sink.clone()).into_rpc())
+			.map_err(|e| sc_service::Error::Other(e.to_string()))?;
+		Ok(module)
+	});
+
+	let proposer_factory = sc_basic_authorship::ProposerFactory::new(
+		task_manager.spawn_handle(),
+		client.clone(),
+		transaction_pool.clone(),
+		config.prometheus_registry(),
+		telemetry.as_ref().map(|t| t.handle()),
+	);
+	let select_chain = sc_consensus::LongestChain::new(params.backend.clone());
+
+	sc_service::spawn_tasks(sc_service::SpawnTasksParams {
+		rpc_builder,
+		client: client.clone(),
+		transaction_pool: transaction_pool.clone(),
+		task_manager: &mut task_manager,
+		config,
+		keystore: params.keystore_container.sync_keystore(),
+		backend: params.backend,
+		network,
+		system_rpc_tx,
+		telemetry: telemetry.as_mut(),
+	})?;
+
+	let commands_stream: Box<dyn futures::Stream<Item = EngineCommand<Hash>> + Send + Sync + Unpin> =
+		match sealing {
+			Sealing::Manual => Box::new(rpc_commands),
+			Sealing::Instant => Box::new(select(
+				rpc_commands,
+				transaction_pool.pool().validated_pool().import_notification_stream().map(|_| {
+					EngineCommand::SealNewBlock {
+						create_empty: false,
+						finalize: false,
+						parent_hash: None,
+						sender: None,
+					}
+				}),
+			)),
+		};
+
+	let slot_duration = cumulus_client_consensus_aura::slot_duration(&*client)?.as_millis();
+	// Timestamps advance by a slot per block, so that Aura accepts blocks sealed in quick
+	// succession.
+	let next_timestamp = Arc::new(std::sync::atomic::AtomicU64::new(
+		*sp_timestamp::InherentDataProvider::from_system_time().timestamp(),
+	));
+	let inherent_client = client.clone();
+
+	let authorship_future = run_manual_seal(ManualSealParams {
+		block_import: client.clone(),
+		env: proposer_factory,
+		client: client.clone(),
+		pool: transaction_pool.pool().clone(),
+		commands_stream,
+		select_chain,
+		consensus_data_provider: Some(Box::new(AuraDigestProvider { slot_duration })),
+		create_inherent_data_providers: move |parent, ()| {
+			let current_para_block = inherent_client
+				.header(&BlockId::hash(parent))
+				.ok()
+				.flatten()
+				.map_or(0, |header| *header.number() + 1);
+			let timestamp = next_timestamp
+				.fetch_add(slot_duration, std::sync::atomic::Ordering::SeqCst);
+			async move {
+				let time = sp_timestamp::InherentDataProvider::new(timestamp.into());
+				let parachain_inherent =
+					cumulus_primitives_parachain_inherent::MockValidationDataInherentDataProvider {
+						current_para_block,
+						relay_offset: 1000,
+						relay_blocks_per_para_block: 2,
+						xcm_config: Default::default(),
+						raw_downward_messages: vec![],
+						raw_horizontal_messages: vec![],
+					};
+				Ok((time, parachain_inherent))
+			}
+		},
+	});
+
+	task_manager
+		.spawn_essential_handle()
+		.spawn_blocking("manual-seal", None, authorship_future);
+
+	start_network.start_network();
+
+	Ok(task_manager)
+}
@@ -0,0 +1,29 @@
+//! Benchmarks for the asset transaction payment pallet.
+
+use super::*;
+use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite};
+use frame_support::{assert_ok, traits::EnsureOrigin};
+use sp_runtime::traits::StaticLookup;
+
+benchmarks! {
+	set_fee_rate {
+		let asset = AssetIdOf::<T>::default();
+		let owner: T::AccountId = account("owner", 0, 0);
+		assert_ok!(pallet_assets::Pallet::<T>::force_create(
+			<T as pallet_assets::Config>::ForceOrigin::successful_origin(),
+			asset,
+			T::Lookup::unlookup(owner),
+			true,
+			1u32.into(),
+		));
+		let rate = FixedU128::saturating_from_rational(1, 2);
+		let origin = T::UpdateOrigin::successful_origin();
+	}: {
+		assert_ok!(Pallet::<T>::set_fee_rate(origin, asset, Some(rate)));
+	}
+	verify {
+		assert_eq!(Pallet::<T>::fee_rate(asset), Some(rate));
+	}
+}
+
+impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
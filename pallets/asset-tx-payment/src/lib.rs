@@ -0,0 +1,360 @@
+//! Transaction fees paid in `pallet_assets` assets.
+//!
+//! [`ChargeAssetTxPayment`] replaces `pallet_transaction_payment::ChargeTransactionPayment` in a
+//! runtime's signed extensions. A transaction that names no asset pays its fee in the native
+//! currency exactly as before. A transaction that names an asset pays the same fee converted at
+//! the rate governance set for that asset with [`Pallet::set_fee_rate`], as units of the asset per
+//! unit of the native currency, rounded up. Assets without a rate cannot pay fees.
+//!
+//! The fee, including the tip, is transferred to `Config::FeeReceiver` before the call is
+//! dispatched, and the part of it not used by the call is refunded afterwards. If the refund
+//! fails, the receiver keeps the whole fee and [`Event::RefundFailed`] reports it.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod weights;
+
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use frame_support::{
+	traits::{
+		fungibles::{Inspect, Transfer},
+		Get,
+	},
+	weights::{DispatchInfo, PostDispatchInfo},
+};
+use pallet_transaction_payment::{ChargeTransactionPayment, OnChargeTransaction};
+use sp_runtime::{
+	traits::{
+		DispatchInfoOf, Dispatchable, PostDispatchInfoOf, SaturatedConversion, SignedExtension, Zero,
+	},
+	transaction_validity::{
+		InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
+	FixedPointNumber, FixedPointOperand, FixedU128,
+};
+use sp_std::{fmt, prelude::*};
+
+pub use pallet::*;
+pub use weights::WeightInfo;
+
+/// The balance type of the native currency fees are computed in.
+pub type NativeBalanceOf<T> = <<T as pallet_transaction_payment::Config>::OnChargeTransaction as
+	OnChargeTransaction<T>>::Balance;
+/// The identifier of an asset.
+pub type AssetIdOf<T> = <T as pallet_assets::Config>::AssetId;
+/// The balance type of assets.
+pub type AssetBalanceOf<T> = <T as pallet_assets::Config>::Balance;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config:
+		frame_system::Config + pallet_transaction_payment::Config + pallet_assets::Config
+	{
+		/// The overarching event type.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// Origin allowed to set the fee rate of an asset.
+		type UpdateOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Account fees paid in assets are transferred to.
+		type FeeReceiver: Get<Self::AccountId>;
+
+		/// Weight information for the calls of this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The fee rate of an asset was set, or removed if `None`. \[ asset, rate \]
+		FeeRateSet(AssetIdOf<T>, Option<FixedU128>),
+		/// A transaction fee, including the tip, was paid in an asset. \[ who, asset, amount \]
+		AssetFeePaid(T::AccountId, AssetIdOf<T>, AssetBalanceOf<T>),
+		/// The unused part of a fee paid in an asset could not be refunded, so the fee receiver
+		/// kept it. \[ who, asset, amount \]
+		RefundFailed(T::AccountId, AssetIdOf<T>, AssetBalanceOf<T>),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The asset does not exist.
+		UnknownAsset,
+		/// A rate of zero would make fees paid in the asset free.
+		ZeroRate,
+	}
+
+	/// Units of an asset paid per unit of the native currency, for the assets fees can be paid in.
+	#[pallet::storage]
+	#[pallet::getter(fn fee_rate)]
+	pub(super) type FeeRates<T: Config> =
+		StorageMap<_, Blake2_128Concat, AssetIdOf<T>, FixedU128, OptionQuery>;
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Set the units of `asset` paid per unit of the native currency, or stop accepting `asset`
+		/// for fees if `rate` is `None`.
+		///
+		/// The dispatch origin must be `Config::UpdateOrigin`.
+		#[pallet::weight(<T as Config>::WeightInfo::set_fee_rate())]
+		pub fn set_fee_rate(
+			origin: OriginFor<T>,
+			#[pallet::compact] asset: AssetIdOf<T>,
+			rate: Option<FixedU128>,
+		) -> DispatchResult {
+			T::UpdateOrigin::ensure_origin(origin)?;
+			match rate {
+				Some(rate) => {
+					ensure!(!rate.into_inner().is_zero(), Error::<T>::ZeroRate);
+					ensure!(
+						!pallet_assets::Pallet::<T>::minimum_balance(asset).is_zero(),
+						Error::<T>::UnknownAsset
+					);
+					FeeRates::<T>::insert(asset, rate);
+				},
+				None => FeeRates::<T>::remove(asset),
+			}
+			Self::deposit_event(Event::FeeRateSet(asset, rate));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// The amount of `asset` paid for `fee` in the native currency, if fees can be paid in `asset`.
+	///
+	/// Rounded up, so that a fee is never paid in less than it is worth.
+	pub fn to_asset_balance(
+		asset: AssetIdOf<T>,
+		fee: NativeBalanceOf<T>,
+	) -> Option<AssetBalanceOf<T>> {
+		let rate = FeeRates::<T>::get(asset)?.into_inner();
+		let fee: u128 = fee.saturated_into();
+		// `fee * rate`, in parts that cannot overflow before saturating.
+		let (int, frac) = (rate / FixedU128::DIV, rate % FixedU128::DIV);
+		let (whole, part) = (fee / FixedU128::DIV, fee % FixedU128::DIV);
+		let amount = fee
+			.saturating_mul(int)
+			.saturating_add(whole.saturating_mul(frac))
+			.saturating_add((part * frac + FixedU128::DIV - 1) / FixedU128::DIV);
+		Some(amount.saturated_into())
+	}
+
+	/// Transfer `amount` of `asset` from `who` to the fee receiver.
+	fn charge(
+		who: &T::AccountId,
+		asset: AssetIdOf<T>,
+		amount: AssetBalanceOf<T>,
+	) -> Result<(), TransactionValidityError> {
+		if amount.is_zero() {
+			return Ok(())
+		}
+		<pallet_assets::Pallet<T> as Transfer<T::AccountId>>::transfer(
+			asset,
+			who,
+			&T::FeeReceiver::get(),
+			amount,
+			false,
+		)
+		.map(|_| ())
+		.map_err(|_| InvalidTransaction::Payment.into())
+	}
+}
+
+/// The fee charged by [`ChargeAssetTxPayment`] before dispatch, settled after it.
+pub enum InitialPayment<T: Config> {
+	/// Paid in the native currency through `pallet_transaction_payment`, as its tip, payer and
+	/// withdrawn imbalance.
+	Native(
+		(
+			NativeBalanceOf<T>,
+			T::AccountId,
+			<<T as pallet_transaction_payment::Config>::OnChargeTransaction as OnChargeTransaction<
+				T,
+			>>::LiquidityInfo,
+		),
+	),
+	/// Paid in an asset; `paid` includes the tip.
+	Asset {
+		who: T::AccountId,
+		asset: AssetIdOf<T>,
+		paid: AssetBalanceOf<T>,
+		tip: NativeBalanceOf<T>,
+	},
+}
+
+/// Charge the fee of a transaction, with a tip, in the native currency or in `asset_id`.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct ChargeAssetTxPayment<T: Config> {
+	#[codec(compact)]
+	tip: NativeBalanceOf<T>,
+	asset_id: Option<AssetIdOf<T>>,
+}
+
+impl<T: Config> ChargeAssetTxPayment<T>
+where
+	T::Call: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+	NativeBalanceOf<T>: Send + Sync + FixedPointOperand,
+	AssetIdOf<T>: Send + Sync,
+{
+	/// Tip `tip`, paying the fee in `asset_id`, or in the native currency if `None`.
+	pub fn from(tip: NativeBalanceOf<T>, asset_id: Option<AssetIdOf<T>>) -> Self {
+		Self { tip, asset_id }
+	}
+
+	/// The asset the fee is paid in, if not the native currency.
+	pub fn asset_id(&self) -> Option<AssetIdOf<T>> {
+		self.asset_id
+	}
+
+	fn native(&self) -> ChargeTransactionPayment<T> {
+		ChargeTransactionPayment::<T>::from(self.tip)
+	}
+
+	/// The fee of the transaction in `asset`, including the tip, and the native fee it is
+	/// converted from.
+	fn asset_fee(
+		&self,
+		asset: AssetIdOf<T>,
+		info: &DispatchInfoOf<T::Call>,
+		len: usize,
+	) -> Result<(AssetBalanceOf<T>, NativeBalanceOf<T>), TransactionValidityError> {
+		let fee = pallet_transaction_payment::Pallet::<T>::compute_fee(len as u32, info, self.tip);
+		let asset_fee =
+			Pallet::<T>::to_asset_balance(asset, fee).ok_or(InvalidTransaction::Payment)?;
+		Ok((asset_fee, fee))
+	}
+}
+
+impl<T: Config> fmt::Debug for ChargeAssetTxPayment<T> {
+	#[cfg(feature = "std")]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "ChargeAssetTxPayment<{:?}, {:?}>", self.tip, self.asset_id)
+	}
+	#[cfg(not(feature = "std"))]
+	fn fmt(&self, _: &mut fmt::Formatter) -> fmt::Result {
+		Ok(())
+	}
+}
+
+impl<T: Config> SignedExtension for ChargeAssetTxPayment<T>
+where
+	T::Call: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+	NativeBalanceOf<T>: Send + Sync + FixedPointOperand,
+	AssetIdOf<T>: Send + Sync,
+{
+	const IDENTIFIER: &'static str = "ChargeAssetTxPayment";
+	type AccountId = T::AccountId;
+	type Call = T::Call;
+	type AdditionalSigned = ();
+	type Pre = InitialPayment<T>;
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> TransactionValidity {
+		let asset = match self.asset_id {
+			Some(asset) => asset,
+			None => return self.native().validate(who, call, info, len),
+		};
+		let (asset_fee, fee) = self.asset_fee(asset, info, len)?;
+		if !asset_fee.is_zero() {
+			pallet_assets::Pallet::<T>::can_withdraw(asset, who, asset_fee)
+				.into_result()
+				.map_err(|_| InvalidTransaction::Payment)?;
+			// A receiver not holding the asset yet cannot take less than its minimum balance.
+			pallet_assets::Pallet::<T>::can_deposit(asset, &T::FeeReceiver::get(), asset_fee)
+				.into_result()
+				.map_err(|_| InvalidTransaction::Payment)?;
+		}
+		// Prioritised like the same fee and tip paid natively.
+		Ok(ValidTransaction {
+			priority: ChargeTransactionPayment::<T>::get_priority(info, len, self.tip, fee),
+			..Default::default()
+		})
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		let asset = match self.asset_id {
+			Some(asset) => asset,
+			None =>
+				return self.native().pre_dispatch(who, call, info, len).map(InitialPayment::Native),
+		};
+		let (paid, _) = self.asset_fee(asset, info, len)?;
+		Pallet::<T>::charge(who, asset, paid)?;
+		Ok(InitialPayment::Asset { who: who.clone(), asset, paid, tip: self.tip })
+	}
+
+	fn post_dispatch(
+		pre: Option<Self::Pre>,
+		info: &DispatchInfoOf<Self::Call>,
+		post_info: &PostDispatchInfoOf<Self::Call>,
+		len: usize,
+		result: &sp_runtime::DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		match pre {
+			Some(InitialPayment::Native(pre)) =>
+				ChargeTransactionPayment::<T>::post_dispatch(Some(pre), info, post_info, len, result),
+			Some(InitialPayment::Asset { who, asset, paid, tip }) => {
+				let fee = pallet_transaction_payment::Pallet::<T>::compute_actual_fee(
+					len as u32, info, post_info, tip,
+				);
+				let actual = Pallet::<T>::to_asset_balance(asset, fee)
+					.map_or(paid, |actual| actual.min(paid));
+				let refund = paid - actual;
+				let mut kept = actual;
+				if !refund.is_zero() {
+					// The receiver was just paid at least `refund`, but may have spent it since, and
+					// `who` may no longer hold the asset and be refunded less than its minimum
+					// balance.
+					let refunded = <pallet_assets::Pallet<T> as Transfer<T::AccountId>>::transfer(
+						asset,
+						&T::FeeReceiver::get(),
+						&who,
+						refund,
+						false,
+					);
+					if refunded.is_err() {
+						kept = paid;
+						Pallet::<T>::deposit_event(Event::RefundFailed(who.clone(), asset, refund));
+					}
+				}
+				Pallet::<T>::deposit_event(Event::AssetFeePaid(who, asset, kept));
+				Ok(())
+			},
+			None => Ok(()),
+		}
+	}
+}
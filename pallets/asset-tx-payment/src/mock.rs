@@ -0,0 +1,162 @@
+use crate as pallet_asset_tx_payment;
+use frame_support::{
+	parameter_types,
+	traits::Everything,
+	weights::{ConstantMultiplier, DispatchClass, IdentityFee},
+};
+use frame_system::{limits::BlockWeights, EnsureRoot};
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+	FixedPointNumber, FixedU128,
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		TransactionPayment: pallet_transaction_payment::{Pallet, Storage},
+		Assets: pallet_assets::{Pallet, Call, Storage, Event<T>},
+		AssetTxPayment: pallet_asset_tx_payment::{Pallet, Call, Storage, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+	// No base fee per extrinsic, so that a fee is its length plus its weight.
+	pub TestBlockWeights: BlockWeights = BlockWeights::builder()
+		.base_block(0)
+		.for_class(DispatchClass::all(), |weights| weights.base_extrinsic = 0)
+		.build_or_panic();
+}
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = Everything;
+	type BlockWeights = TestBlockWeights;
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type OnSetCode = ();
+	type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+	pub const MaxReserves: u32 = 50;
+}
+
+impl pallet_balances::Config for Test {
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+	type MaxLocks = ();
+	type MaxReserves = MaxReserves;
+	type ReserveIdentifier = [u8; 8];
+}
+
+parameter_types! {
+	pub const TransactionByteFee: u64 = 1;
+	pub const OperationalFeeMultiplier: u8 = 5;
+}
+
+impl pallet_transaction_payment::Config for Test {
+	type OnChargeTransaction = pallet_transaction_payment::CurrencyAdapter<Balances, ()>;
+	type OperationalFeeMultiplier = OperationalFeeMultiplier;
+	type WeightToFee = IdentityFee<u64>;
+	type LengthToFee = ConstantMultiplier<u64, TransactionByteFee>;
+	type FeeMultiplierUpdate = ();
+}
+
+parameter_types! {
+	pub const AssetDeposit: u64 = 0;
+	pub const ApprovalDeposit: u64 = 0;
+	pub const StringLimit: u32 = 50;
+	pub const MetadataDepositBase: u64 = 0;
+	pub const MetadataDepositPerByte: u64 = 0;
+}
+
+impl pallet_assets::Config for Test {
+	type Event = Event;
+	type Balance = u64;
+	type AssetId = u32;
+	type Currency = Balances;
+	type ForceOrigin = EnsureRoot<u64>;
+	type AssetDeposit = AssetDeposit;
+	type MetadataDepositBase = MetadataDepositBase;
+	type MetadataDepositPerByte = MetadataDepositPerByte;
+	type ApprovalDeposit = ApprovalDeposit;
+	type StringLimit = StringLimit;
+	type Freezer = ();
+	type Extra = ();
+	type WeightInfo = ();
+}
+
+/// Account fees paid in assets go to.
+pub const RECEIVER: u64 = 99;
+/// The asset created at genesis, which fees can be paid in.
+pub const ASSET: u32 = 1;
+/// Owner of `ASSET`.
+pub const OWNER: u64 = 42;
+
+parameter_types! {
+	pub const FeeReceiver: u64 = RECEIVER;
+}
+
+impl pallet_asset_tx_payment::Config for Test {
+	type Event = Event;
+	type UpdateOrigin = EnsureRoot<u64>;
+	type FeeReceiver = FeeReceiver;
+	type WeightInfo = ();
+}
+
+/// The fee rate of `ASSET` at genesis.
+pub fn rate() -> FixedU128 {
+	FixedU128::saturating_from_rational(5, 2)
+}
+
+/// Accounts 1 and 2 hold 100 of the native currency and 100 of `ASSET`, whose fee rate is 5/2.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> { balances: vec![(1, 100), (2, 100)] }
+		.assimilate_storage(&mut t)
+		.unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		Assets::force_create(Origin::root(), ASSET, OWNER, true, 1).unwrap();
+		for who in 1..=2 {
+			Assets::mint(Origin::signed(OWNER), ASSET, who, 100).unwrap();
+		}
+		AssetTxPayment::set_fee_rate(Origin::root(), ASSET, Some(rate())).unwrap();
+	});
+	ext
+}
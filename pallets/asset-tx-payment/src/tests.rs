@@ -0,0 +1,249 @@
+use crate::{mock::*, ChargeAssetTxPayment, Error, Event as AssetTxPaymentEvent};
+use pallet_transaction_payment::ChargeTransactionPayment;
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::fungibles::Inspect,
+	weights::{DispatchInfo, PostDispatchInfo},
+};
+use sp_runtime::{
+	traits::{BadOrigin, SignedExtension},
+	transaction_validity::InvalidTransaction,
+	FixedPointNumber, FixedU128,
+};
+
+const CALL: &Call = &Call::Balances(pallet_balances::Call::transfer { dest: 2, value: 69 });
+const LEN: usize = 10;
+
+fn info(weight: u64) -> DispatchInfo {
+	DispatchInfo { weight, ..Default::default() }
+}
+
+fn post_info(weight: u64) -> PostDispatchInfo {
+	PostDispatchInfo { actual_weight: Some(weight), ..Default::default() }
+}
+
+fn asset_balance(who: u64) -> u64 {
+	<Assets as Inspect<u64>>::balance(ASSET, &who)
+}
+
+fn events() -> Vec<Event> {
+	System::events().into_iter().map(|r| r.event).collect()
+}
+
+#[test]
+fn native_fees_convert_at_the_asset_rate() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(AssetTxPayment::to_asset_balance(ASSET, 10), Some(25));
+		// Rounded up.
+		assert_eq!(AssetTxPayment::to_asset_balance(ASSET, 15), Some(38));
+		assert_eq!(AssetTxPayment::to_asset_balance(ASSET, 0), Some(0));
+		assert_eq!(AssetTxPayment::to_asset_balance(ASSET + 1, 10), None);
+
+		assert_ok!(AssetTxPayment::set_fee_rate(
+			Origin::root(),
+			ASSET,
+			Some(FixedU128::saturating_from_rational(1, 1_000)),
+		));
+		assert_eq!(AssetTxPayment::to_asset_balance(ASSET, 1), Some(1));
+		assert_eq!(AssetTxPayment::to_asset_balance(ASSET, 1_000), Some(1));
+		assert_eq!(AssetTxPayment::to_asset_balance(ASSET, 1_001), Some(2));
+		assert_eq!(AssetTxPayment::to_asset_balance(ASSET, u64::MAX), Some(u64::MAX / 1_000 + 1));
+
+		assert_ok!(AssetTxPayment::set_fee_rate(
+			Origin::root(),
+			ASSET,
+			Some(FixedU128::saturating_from_integer(2)),
+		));
+		// Saturates instead of overflowing.
+		assert_eq!(AssetTxPayment::to_asset_balance(ASSET, u64::MAX), Some(u64::MAX));
+	});
+}
+
+#[test]
+fn set_fee_rate_works() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(AssetTxPayment::set_fee_rate(Origin::signed(OWNER), ASSET, None), BadOrigin);
+		assert_noop!(
+			AssetTxPayment::set_fee_rate(Origin::root(), ASSET + 1, Some(rate())),
+			Error::<Test>::UnknownAsset
+		);
+		assert_noop!(
+			AssetTxPayment::set_fee_rate(Origin::root(), ASSET, Some(FixedU128::from_inner(0))),
+			Error::<Test>::ZeroRate
+		);
+
+		assert_eq!(AssetTxPayment::fee_rate(ASSET), Some(rate()));
+		assert_ok!(AssetTxPayment::set_fee_rate(Origin::root(), ASSET, None));
+		assert_eq!(AssetTxPayment::fee_rate(ASSET), None);
+		assert_eq!(
+			events().last(),
+			Some(&Event::AssetTxPayment(AssetTxPaymentEvent::FeeRateSet(ASSET, None)))
+		);
+	});
+}
+
+#[test]
+fn fees_without_an_asset_are_paid_natively() {
+	new_test_ext().execute_with(|| {
+		let ext = ChargeAssetTxPayment::<Test>::from(0, None);
+		let pre = ext.pre_dispatch(&1, CALL, &info(5), LEN).unwrap();
+		assert_eq!(Balances::free_balance(1), 100 - 15);
+
+		assert_ok!(ChargeAssetTxPayment::<Test>::post_dispatch(
+			Some(pre),
+			&info(5),
+			&post_info(2),
+			LEN,
+			&Ok(())
+		));
+		assert_eq!(Balances::free_balance(1), 100 - 12);
+		assert_eq!(asset_balance(1), 100);
+	});
+}
+
+#[test]
+fn fees_in_an_asset_are_charged_and_refunded() {
+	new_test_ext().execute_with(|| {
+		let ext = ChargeAssetTxPayment::<Test>::from(0, Some(ASSET));
+		assert_eq!(ext.asset_id(), Some(ASSET));
+		assert_ok!(ext.validate(&1, CALL, &info(5), LEN));
+
+		// 15 native, at a rate of 5/2.
+		let pre = ext.pre_dispatch(&1, CALL, &info(5), LEN).unwrap();
+		assert_eq!(asset_balance(1), 100 - 38);
+		assert_eq!(asset_balance(RECEIVER), 38);
+
+		// 12 native were used.
+		assert_ok!(ChargeAssetTxPayment::<Test>::post_dispatch(
+			Some(pre),
+			&info(5),
+			&post_info(2),
+			LEN,
+			&Ok(())
+		));
+		assert_eq!(asset_balance(1), 100 - 30);
+		assert_eq!(asset_balance(RECEIVER), 30);
+		assert_eq!(Balances::free_balance(1), 100);
+		assert_eq!(
+			events().last(),
+			Some(&Event::AssetTxPayment(AssetTxPaymentEvent::AssetFeePaid(1, ASSET, 30)))
+		);
+	});
+}
+
+#[test]
+fn tips_are_paid_in_the_asset() {
+	new_test_ext().execute_with(|| {
+		let ext = ChargeAssetTxPayment::<Test>::from(4, Some(ASSET));
+		let pre = ext.pre_dispatch(&1, CALL, &info(5), LEN).unwrap();
+		assert_eq!(asset_balance(1), 100 - 48);
+
+		assert_ok!(ChargeAssetTxPayment::<Test>::post_dispatch(
+			Some(pre),
+			&info(5),
+			&post_info(5),
+			LEN,
+			&Ok(())
+		));
+		assert_eq!(asset_balance(1), 100 - 48);
+		assert_eq!(asset_balance(RECEIVER), 48);
+	});
+}
+
+#[test]
+fn assets_without_a_rate_cannot_pay() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AssetTxPayment::set_fee_rate(Origin::root(), ASSET, None));
+		let ext = ChargeAssetTxPayment::<Test>::from(0, Some(ASSET));
+		assert_eq!(ext.validate(&1, CALL, &info(5), LEN), Err(InvalidTransaction::Payment.into()));
+		assert_eq!(
+			ext.pre_dispatch(&1, CALL, &info(5), LEN).map(|_| ()),
+			Err(InvalidTransaction::Payment.into())
+		);
+		assert_eq!(asset_balance(1), 100);
+	});
+}
+
+#[test]
+fn insufficient_asset_balance_cannot_pay() {
+	new_test_ext().execute_with(|| {
+		// 50 native would cost 125 of the asset.
+		let ext = ChargeAssetTxPayment::<Test>::from(0, Some(ASSET));
+		assert_eq!(
+			ext.validate(&1, CALL, &info(40), LEN),
+			Err(InvalidTransaction::Payment.into())
+		);
+		assert_eq!(
+			ext.pre_dispatch(&1, CALL, &info(40), LEN).map(|_| ()),
+			Err(InvalidTransaction::Payment.into())
+		);
+		assert_eq!(asset_balance(1), 100);
+		assert_eq!(Balances::free_balance(1), 100);
+	});
+}
+
+#[test]
+fn tips_in_an_asset_are_prioritised_like_native_ones() {
+	new_test_ext().execute_with(|| {
+		let priority = |tip, asset| match asset {
+			Some(_) => ChargeAssetTxPayment::<Test>::from(tip, asset)
+				.validate(&1, CALL, &info(5), LEN)
+				.unwrap()
+				.priority,
+			None => ChargeTransactionPayment::<Test>::from(tip)
+				.validate(&1, CALL, &info(5), LEN)
+				.unwrap()
+				.priority,
+		};
+		assert_eq!(priority(4, Some(ASSET)), priority(4, None));
+		assert!(priority(4, Some(ASSET)) > priority(0, Some(ASSET)));
+	});
+}
+
+#[test]
+fn fees_below_the_minimum_balance_need_a_receiver_holding_the_asset() {
+	new_test_ext().execute_with(|| {
+		let asset = ASSET + 1;
+		assert_ok!(Assets::force_create(Origin::root(), asset, OWNER, true, 50));
+		assert_ok!(Assets::mint(Origin::signed(OWNER), asset, 1, 100));
+		assert_ok!(AssetTxPayment::set_fee_rate(
+			Origin::root(),
+			asset,
+			Some(FixedU128::saturating_from_integer(1)),
+		));
+
+		// 15 native is less than the receiver may hold of the asset.
+		let ext = ChargeAssetTxPayment::<Test>::from(0, Some(asset));
+		assert_eq!(ext.validate(&1, CALL, &info(5), LEN), Err(InvalidTransaction::Payment.into()));
+
+		assert_ok!(Assets::mint(Origin::signed(OWNER), asset, RECEIVER, 50));
+		assert_ok!(ext.validate(&1, CALL, &info(5), LEN));
+	});
+}
+
+#[test]
+fn failed_refunds_are_kept_and_reported() {
+	new_test_ext().execute_with(|| {
+		let ext = ChargeAssetTxPayment::<Test>::from(0, Some(ASSET));
+		let pre = ext.pre_dispatch(&1, CALL, &info(5), LEN).unwrap();
+		// The receiver spends the fee before the refund.
+		assert_ok!(Assets::transfer(Origin::signed(RECEIVER), ASSET, 3, 38));
+
+		assert_ok!(ChargeAssetTxPayment::<Test>::post_dispatch(
+			Some(pre),
+			&info(5),
+			&post_info(2),
+			LEN,
+			&Ok(())
+		));
+		assert_eq!(asset_balance(1), 100 - 38);
+		let events = events();
+		assert_eq!(
+			events[events.len() - 2..],
+			[
+				Event::AssetTxPayment(AssetTxPaymentEvent::RefundFailed(1, ASSET, 8)),
+				Event::AssetTxPayment(AssetTxPaymentEvent::AssetFeePaid(1, ASSET, 38)),
+			]
+		);
+	});
+}
@@ -0,0 +1,22 @@
+//! Weights for pallet_asset_tx_payment.
+//!
+//! These are estimates from the storage each call touches, until the pallet is benchmarked with
+//! `scripts/benchmark.sh`.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::weights::{constants::RocksDbWeight, Weight};
+
+/// Weight functions needed for pallet_asset_tx_payment.
+pub trait WeightInfo {
+	fn set_fee_rate() -> Weight;
+}
+
+impl WeightInfo for () {
+	fn set_fee_rate() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+}
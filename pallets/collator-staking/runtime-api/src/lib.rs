@@ -0,0 +1,19 @@
+//! Runtime API for querying how collators performed in recent sessions.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_staking::SessionIndex;
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+	pub trait CollatorStakingApi<AccountId, Performance> where
+		AccountId: Codec,
+		Performance: Codec,
+	{
+		/// The performance of `who` in each session of the history window, oldest first.
+		fn collator_performance(who: AccountId) -> Vec<(SessionIndex, Performance)>;
+
+		/// The performance of every collator in `session`, if it is in the history window.
+		fn session_performance(session: SessionIndex) -> Vec<(AccountId, Performance)>;
+	}
+}
@@ -0,0 +1,257 @@
+//! Benchmarks for the collator staking pallet.
+
+use super::*;
+use codec::{Decode, Encode};
+use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_support::{assert_ok, traits::{EnsureOrigin, Get}};
+use frame_system::RawOrigin;
+use sp_runtime::traits::{Bounded, TrailingZeroInput};
+
+const SEED: u32 = 0;
+
+fn funded<T: Config>(who: &T::AccountId) {
+	T::Currency::make_free_balance_be(who, BalanceOf::<T>::max_value() / 1_000_000_000u32.into());
+}
+
+/// Fund `who` and register session keys for it, derived from its account so that they are unique.
+fn register<T: Config + pallet_session::Config>(who: &T::AccountId) {
+	funded::<T>(who);
+	let encoded = who.encode();
+	let keys = <T as pallet_session::Config>::Keys::decode(&mut TrailingZeroInput::new(&encoded))
+		.expect("keys decode from any input");
+	assert_ok!(pallet_session::Pallet::<T>::set_keys(
+		RawOrigin::Signed(who.clone()).into(),
+		keys,
+		Vec::new(),
+	));
+}
+
+/// Make `who` a candidate with `delegators` delegators.
+fn make_candidate<T: Config + pallet_session::Config>(who: &T::AccountId, delegators: u32) {
+	register::<T>(who);
+	assert_ok!(Pallet::<T>::join_candidates(
+		RawOrigin::Signed(who.clone()).into(),
+		Pallet::<T>::min_candidate_bond(),
+		Perbill::from_percent(10),
+	));
+	let index = <CandidateCount<T>>::get();
+	for d in 0..delegators {
+		let delegator: T::AccountId = account("delegator", d, index);
+		funded::<T>(&delegator);
+		assert_ok!(Pallet::<T>::delegate(
+			RawOrigin::Signed(delegator).into(),
+			who.clone(),
+			T::MinDelegation::get(),
+		));
+	}
+}
+
+/// The most collators a session can have.
+fn max_collators<T: Config>() -> u32 {
+	T::MaxInvulnerables::get() + T::MaxCandidates::get()
+}
+
+benchmarks! {
+	where_clause { where T: pallet_session::Config }
+
+	set_invulnerables {
+		let b in 1 .. T::MaxInvulnerables::get();
+		let new = (0..b).map(|i| account("invulnerable", i, SEED)).collect::<Vec<T::AccountId>>();
+		let origin = T::UpdateOrigin::successful_origin();
+	}: {
+		assert_ok!(Pallet::<T>::set_invulnerables(origin, new.clone()));
+	}
+	verify {
+		assert_eq!(Pallet::<T>::invulnerables(), new);
+	}
+
+	set_desired_candidates {
+		let max = T::MaxCandidates::get();
+		let origin = T::UpdateOrigin::successful_origin();
+	}: {
+		assert_ok!(Pallet::<T>::set_desired_candidates(origin, max));
+	}
+	verify {
+		assert_eq!(Pallet::<T>::desired_candidates(), max);
+	}
+
+	set_min_candidate_bond {
+		let bond = T::Currency::minimum_balance() * 10u32.into();
+		let origin = T::UpdateOrigin::successful_origin();
+	}: {
+		assert_ok!(Pallet::<T>::set_min_candidate_bond(origin, bond));
+	}
+	verify {
+		assert_eq!(Pallet::<T>::min_candidate_bond(), bond);
+	}
+
+	set_inflation_rate {
+		let rate = Perbill::from_percent(5);
+		let origin = T::UpdateOrigin::successful_origin();
+	}: {
+		assert_ok!(Pallet::<T>::set_inflation_rate(origin, rate));
+	}
+	verify {
+		assert_eq!(Pallet::<T>::inflation_rate(), rate);
+	}
+
+	join_candidates {
+		let caller: T::AccountId = whitelisted_caller();
+		register::<T>(&caller);
+		let bond = Pallet::<T>::min_candidate_bond();
+	}: _(RawOrigin::Signed(caller.clone()), bond, Perbill::from_percent(10))
+	verify {
+		assert!(<Candidates<T>>::contains_key(&caller));
+	}
+
+	candidate_bond_more {
+		let caller: T::AccountId = whitelisted_caller();
+		make_candidate::<T>(&caller, 0);
+		let bond = Pallet::<T>::min_candidate_bond();
+		let more = T::Currency::minimum_balance();
+	}: _(RawOrigin::Signed(caller.clone()), more)
+	verify {
+		assert_eq!(Pallet::<T>::candidates(&caller).unwrap().bond, bond + more);
+	}
+
+	set_commission {
+		let caller: T::AccountId = whitelisted_caller();
+		make_candidate::<T>(&caller, 0);
+		let commission = Perbill::from_percent(20);
+	}: _(RawOrigin::Signed(caller.clone()), commission)
+	verify {
+		assert_eq!(Pallet::<T>::candidates(&caller).unwrap().commission, commission);
+	}
+
+	leave_candidates {
+		let d in 0 .. T::MaxDelegatorsPerCandidate::get();
+		let caller: T::AccountId = whitelisted_caller();
+		make_candidate::<T>(&caller, d);
+	}: _(RawOrigin::Signed(caller.clone()))
+	verify {
+		assert!(!<Candidates<T>>::contains_key(&caller));
+	}
+
+	delegate {
+		let candidate: T::AccountId = account("candidate", 0, SEED);
+		make_candidate::<T>(&candidate, 0);
+		let caller: T::AccountId = whitelisted_caller();
+		funded::<T>(&caller);
+		let amount = T::MinDelegation::get();
+	}: _(RawOrigin::Signed(caller.clone()), candidate.clone(), amount)
+	verify {
+		assert_eq!(Pallet::<T>::delegations(&candidate, &caller), amount);
+	}
+
+	undelegate {
+		let candidate: T::AccountId = account("candidate", 0, SEED);
+		make_candidate::<T>(&candidate, 0);
+		let caller: T::AccountId = whitelisted_caller();
+		funded::<T>(&caller);
+		let amount = T::MinDelegation::get();
+		assert_ok!(Pallet::<T>::delegate(
+			RawOrigin::Signed(caller.clone()).into(),
+			candidate.clone(),
+			amount,
+		));
+	}: _(RawOrigin::Signed(caller.clone()), candidate.clone(), amount)
+	verify {
+		assert!(!<Delegations<T>>::contains_key(&candidate, &caller));
+	}
+
+	withdraw_unbonded {
+		let candidate: T::AccountId = account("candidate", 0, SEED);
+		make_candidate::<T>(&candidate, 0);
+		let caller: T::AccountId = whitelisted_caller();
+		funded::<T>(&caller);
+		let amount = T::MinDelegation::get();
+		assert_ok!(Pallet::<T>::delegate(
+			RawOrigin::Signed(caller.clone()).into(),
+			candidate.clone(),
+			amount,
+		));
+		assert_ok!(Pallet::<T>::undelegate(
+			RawOrigin::Signed(caller.clone()).into(),
+			candidate,
+			amount,
+		));
+		<CurrentSession<T>>::put(T::UnbondingDelay::get());
+	}: _(RawOrigin::Signed(caller.clone()))
+	verify {
+		assert!(Pallet::<T>::unlocking(&caller).is_empty());
+	}
+
+	new_session {
+		let c in 1 .. T::MaxCandidates::get();
+		for i in 0..c {
+			make_candidate::<T>(&account("candidate", i, SEED), 0);
+		}
+		<DesiredCandidates<T>>::put(c);
+	}: {
+		<Pallet<T> as SessionManager<_>>::new_session(1);
+	}
+	verify {
+		assert_eq!(Pallet::<T>::selected_candidates().len() as u32, c);
+	}
+
+	record_performance {
+		let c in 1 .. max_collators::<T>();
+		let session = T::PerformanceHistoryDepth::get();
+		for i in 0..c {
+			let who: T::AccountId = account("collator", i, SEED);
+			<Points<T>>::insert(&who, 1);
+			<MissedSlots<T>>::insert(&who, 1);
+			<History<T>>::insert(0, &who, CollatorPerformance { authored: 1, missed: 1 });
+		}
+	}: {
+		Pallet::<T>::record_performance(session);
+	}
+	verify {
+		assert_eq!(Pallet::<T>::session_performance(session).len() as u32, c);
+		assert!(Pallet::<T>::session_performance(0).is_empty());
+	}
+
+	kick_candidate {
+		let d in 0 .. T::MaxDelegatorsPerCandidate::get();
+		let candidate: T::AccountId = account("candidate", 0, SEED);
+		make_candidate::<T>(&candidate, d);
+		<MissedSlots<T>>::insert(&candidate, 1);
+	}: {
+		Pallet::<T>::record_performance(0);
+	}
+	verify {
+		assert!(!<Candidates<T>>::contains_key(&candidate));
+	}
+
+	distribute_rewards {
+		let a in 1 .. max_collators::<T>();
+		for i in 0..a {
+			<Points<T>>::insert(&account::<T::AccountId>("collator", i, SEED), 1);
+		}
+		funded::<T>(&Pallet::<T>::account_id());
+		<InflationRate<T>>::put(Perbill::from_percent(5));
+	}: {
+		Pallet::<T>::distribute_rewards();
+	}
+	verify {
+		assert_eq!(<PendingPayouts<T>>::iter().count() as u32, a);
+	}
+
+	pay_author {
+		let d in 0 .. T::MaxDelegatorsPerCandidate::get();
+		let candidate: T::AccountId = account("candidate", 0, SEED);
+		make_candidate::<T>(&candidate, d);
+		let pot = Pallet::<T>::account_id();
+		funded::<T>(&pot);
+		let share = T::Currency::free_balance(&pot) / 2u32.into();
+		<PendingPayouts<T>>::insert(&candidate, share);
+		<PendingPayoutTotal<T>>::put(share);
+	}: {
+		Pallet::<T>::pay_pending();
+	}
+	verify {
+		assert!(Pallet::<T>::pending_payout_total().is_zero());
+	}
+}
+
+impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
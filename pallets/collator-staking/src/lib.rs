@@ -0,0 +1,962 @@
+//! Collator selection by stake.
+//!
+//! Accounts with registered session keys become candidates by bonding at least
+//! `MinCandidateBond`, and other accounts back them by delegating to them. At every new session the
+//! `DesiredCandidates` candidates with the most stake behind them are selected as collators, next
+//! to the invulnerables set by governance. Bonds and delegations are reserved; once withdrawn they
+//! stay reserved for `Config::UnbondingDelay` sessions before they can be unlocked with
+//! [`Pallet::withdraw_unbonded`].
+//!
+//! Block authors earn a point per block through `pallet_authorship`'s event handler. When a
+//! session ends, the balance of the pot account is shared among the authors of that session by
+//! points. The shares are paid out over the following blocks, `Config::MaxPayoutsPerBlock` authors
+//! at a time. A candidate keeps its commission out of its share, and the rest is split between the
+//! candidate and its delegators by stake at the time of payout.
+//!
+//! Before the pot is shared, a session's part of the annual `InflationRate` of the total issuance
+//! is minted into it, so that collators are paid even while fees are low.
+//!
+//! Slots skipped between two blocks are counted as missed by the collators expected to author
+//! them. Each collator's authored blocks and missed slots are kept for the last
+//! `Config::PerformanceHistoryDepth` sessions, and a candidate which missed its slots without
+//! authoring a block in a session is removed as if it had left. At most
+//! `Config::MaxKicksPerSession` candidates are removed per session, those which missed the most
+//! slots first.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod migration;
+pub mod weights;
+
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use frame_support::{
+	traits::{Currency, ExistenceRequirement, Imbalance, ReservableCurrency, ValidatorRegistration},
+	weights::{DispatchClass, Weight},
+	PalletId,
+};
+use pallet_session::SessionManager;
+use sp_runtime::{
+	traits::{AccountIdConversion, Convert, Saturating, Zero},
+	Perbill, RuntimeDebug,
+};
+use sp_staking::SessionIndex;
+use sp_std::{collections::btree_map::BTreeMap, prelude::*};
+
+pub use pallet::*;
+pub use weights::WeightInfo;
+
+pub type BalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// A candidate's stake and terms.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct CandidateInfo<Balance> {
+	/// The candidate's own bond.
+	pub bond: Balance,
+	/// The sum of all delegations to the candidate.
+	pub delegated: Balance,
+	/// The part of the candidate's rewards it keeps before the rest is shared by stake.
+	pub commission: Perbill,
+	/// The number of accounts delegating to the candidate.
+	pub delegators: u32,
+}
+
+impl<Balance: Saturating + Copy> CandidateInfo<Balance> {
+	/// The candidate's total stake, by which candidates are ranked.
+	pub fn total(&self) -> Balance {
+		self.bond.saturating_add(self.delegated)
+	}
+}
+
+/// Reserved funds which can be unlocked from a given session on.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct UnlockChunk<Balance> {
+	pub value: Balance,
+	pub session: SessionIndex,
+}
+
+/// The blocks a collator authored and the slots it missed in a session.
+#[derive(Clone, Copy, Default, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct CollatorPerformance {
+	pub authored: u32,
+	pub missed: u32,
+}
+
+/// The slots blocks are authored in, and the collator expected to author each of them.
+pub trait SlotSchedule<AccountId> {
+	/// The slot of the current block.
+	fn current_slot() -> u64;
+	/// The collator expected to author in `slot`.
+	fn expected_author(slot: u64) -> Option<AccountId>;
+}
+
+/// No slots; nothing is counted as missed.
+impl<AccountId> SlotSchedule<AccountId> for () {
+	fn current_slot() -> u64 {
+		0
+	}
+	fn expected_author(_slot: u64) -> Option<AccountId> {
+		None
+	}
+}
+
+/// Missed slots counted per block at most, bounding the work of the first block after a stall.
+const MAX_MISSED_SLOTS_PER_BLOCK: u64 = 100;
+
+/// A `Convert` implementation that finds the stash of a collator, which is the collator itself.
+pub struct IdentityCollator;
+impl<T> Convert<T, Option<T>> for IdentityCollator {
+	fn convert(t: T) -> Option<T> {
+		Some(t)
+	}
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	/// The storage version, bumped by each migration in [`crate::migration`].
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The currency bonds and delegations are reserved in.
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// Origin allowed to set the invulnerables and the selection parameters.
+		type UpdateOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Account identifier of the pot rewards are paid from.
+		#[pallet::constant]
+		type PotId: Get<PalletId>;
+
+		/// Maximum number of candidates.
+		#[pallet::constant]
+		type MaxCandidates: Get<u32>;
+
+		/// Maximum number of invulnerables.
+		#[pallet::constant]
+		type MaxInvulnerables: Get<u32>;
+
+		/// Maximum number of delegators backing a single candidate.
+		#[pallet::constant]
+		type MaxDelegatorsPerCandidate: Get<u32>;
+
+		/// Maximum number of candidates a single account may delegate to.
+		#[pallet::constant]
+		type MaxDelegationsPerDelegator: Get<u32>;
+
+		/// Minimum amount an account may have delegated to a candidate.
+		#[pallet::constant]
+		type MinDelegation: Get<BalanceOf<Self>>;
+
+		/// Number of sessions withdrawn bonds and delegations stay reserved for.
+		#[pallet::constant]
+		type UnbondingDelay: Get<SessionIndex>;
+
+		/// Maximum number of unlocking chunks an account may have.
+		#[pallet::constant]
+		type MaxUnlockingChunks: Get<u32>;
+
+		/// Number of sessions in a year, over which the annual inflation is minted.
+		#[pallet::constant]
+		type SessionsPerYear: Get<u32>;
+
+		/// Number of ended sessions whose collator performance is kept.
+		#[pallet::constant]
+		type PerformanceHistoryDepth: Get<SessionIndex>;
+
+		/// Maximum number of candidates removed for missing their slots when a session ends.
+		#[pallet::constant]
+		type MaxKicksPerSession: Get<u32>;
+
+		/// Maximum number of authors whose rewards are paid out in a single block.
+		#[pallet::constant]
+		type MaxPayoutsPerBlock: Get<u32>;
+
+		/// The slots blocks are authored in, to count missed ones.
+		type SlotSchedule: SlotSchedule<Self::AccountId>;
+
+		/// A stable ID for a collator.
+		type ValidatorId: Member + Parameter;
+
+		/// A conversion from account ID to validator ID.
+		type ValidatorIdOf: Convert<Self::AccountId, Option<Self::ValidatorId>>;
+
+		/// Validate a collator is registered with session keys.
+		type ValidatorRegistration: ValidatorRegistration<Self::ValidatorId>;
+
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The invulnerables were set. \[ invulnerables \]
+		NewInvulnerables(Vec<T::AccountId>),
+		/// The number of candidates to select was set. \[ desired_candidates \]
+		NewDesiredCandidates(u32),
+		/// The minimum candidate bond was set. \[ min_candidate_bond \]
+		NewMinCandidateBond(BalanceOf<T>),
+		/// An account became a candidate. \[ candidate, bond, commission \]
+		CandidateJoined(T::AccountId, BalanceOf<T>, Perbill),
+		/// A candidate increased its bond. \[ candidate, new_bond \]
+		CandidateBondedMore(T::AccountId, BalanceOf<T>),
+		/// A candidate changed its commission. \[ candidate, commission \]
+		CommissionSet(T::AccountId, Perbill),
+		/// A candidate left; its bond and all delegations to it are unbonding.
+		/// \[ candidate \]
+		CandidateLeft(T::AccountId),
+		/// An account delegated to a candidate. \[ delegator, candidate, amount \]
+		Delegated(T::AccountId, T::AccountId, BalanceOf<T>),
+		/// An account withdrew (part of) its delegation to a candidate.
+		/// \[ delegator, candidate, amount \]
+		Undelegated(T::AccountId, T::AccountId, BalanceOf<T>),
+		/// Unbonded funds were unlocked. \[ who, amount \]
+		Withdrawn(T::AccountId, BalanceOf<T>),
+		/// Candidates were selected to collate in a session, next to the invulnerables.
+		/// \[ session, selected \]
+		CandidatesSelected(SessionIndex, Vec<T::AccountId>),
+		/// An account was paid its share of a session's rewards. \[ who, amount \]
+		Rewarded(T::AccountId, BalanceOf<T>),
+		/// The annual inflation rate was set. \[ inflation_rate \]
+		NewInflationRate(Perbill),
+		/// A session's inflation was minted into the pot. \[ amount \]
+		InflationMinted(BalanceOf<T>),
+		/// A candidate was removed for missing its slots without authoring a block in a session;
+		/// its bond and all delegations to it are unbonding. \[ candidate, performance \]
+		CandidateKicked(T::AccountId, CollatorPerformance),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Too many invulnerables.
+		TooManyInvulnerables,
+		/// Too many candidates.
+		TooManyCandidates,
+		/// The account is already a candidate.
+		AlreadyCandidate,
+		/// The account is not a candidate.
+		NotCandidate,
+		/// The bond is below `MinCandidateBond`.
+		BondTooLow,
+		/// The account has no session keys registered.
+		ValidatorNotRegistered,
+		/// The candidate has too many delegators.
+		TooManyDelegators,
+		/// The account delegates to too many candidates.
+		TooManyDelegations,
+		/// The remaining delegation would be below `MinDelegation`.
+		DelegationTooLow,
+		/// The account does not delegate this much to the candidate.
+		NotEnoughDelegated,
+		/// The account has too many chunks unlocking.
+		TooManyUnlockingChunks,
+		/// Candidates cannot delegate to themselves.
+		SelfDelegation,
+	}
+
+	/// Collators which are always selected, independent of stake.
+	#[pallet::storage]
+	#[pallet::getter(fn invulnerables)]
+	pub type Invulnerables<T: Config> = StorageValue<_, Vec<T::AccountId>, ValueQuery>;
+
+	/// The number of candidates selected next to the invulnerables.
+	#[pallet::storage]
+	#[pallet::getter(fn desired_candidates)]
+	pub type DesiredCandidates<T> = StorageValue<_, u32, ValueQuery>;
+
+	/// The minimum bond for becoming a candidate.
+	#[pallet::storage]
+	#[pallet::getter(fn min_candidate_bond)]
+	pub type MinCandidateBond<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
+
+	/// The current candidates.
+	#[pallet::storage]
+	#[pallet::getter(fn candidates)]
+	pub type Candidates<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, CandidateInfo<BalanceOf<T>>, OptionQuery>;
+
+	/// The number of current candidates.
+	#[pallet::storage]
+	pub type CandidateCount<T> = StorageValue<_, u32, ValueQuery>;
+
+	/// Delegations by candidate and delegator.
+	#[pallet::storage]
+	#[pallet::getter(fn delegations)]
+	pub type Delegations<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		T::AccountId,
+		Twox64Concat,
+		T::AccountId,
+		BalanceOf<T>,
+		ValueQuery,
+	>;
+
+	/// The number of candidates an account delegates to.
+	#[pallet::storage]
+	pub type DelegationCount<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, u32, ValueQuery>;
+
+	/// Withdrawn bonds and delegations which are still reserved.
+	#[pallet::storage]
+	#[pallet::getter(fn unlocking)]
+	pub type Unlocking<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, Vec<UnlockChunk<BalanceOf<T>>>, ValueQuery>;
+
+	/// The candidates selected for the latest planned session.
+	#[pallet::storage]
+	#[pallet::getter(fn selected_candidates)]
+	pub type SelectedCandidates<T: Config> = StorageValue<_, Vec<T::AccountId>, ValueQuery>;
+
+	/// The index of the current session.
+	#[pallet::storage]
+	#[pallet::getter(fn current_session)]
+	pub type CurrentSession<T> = StorageValue<_, SessionIndex, ValueQuery>;
+
+	/// The part of the total issuance minted as rewards over a year.
+	#[pallet::storage]
+	#[pallet::getter(fn inflation_rate)]
+	pub type InflationRate<T> = StorageValue<_, Perbill, ValueQuery>;
+
+	/// Blocks authored in the current session, by author.
+	#[pallet::storage]
+	#[pallet::getter(fn points)]
+	pub type Points<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, u32, ValueQuery>;
+
+	/// Slots missed in the current session, by the collator expected to author them.
+	#[pallet::storage]
+	#[pallet::getter(fn missed_slots)]
+	pub type MissedSlots<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, u32, ValueQuery>;
+
+	/// The slot of the latest block.
+	#[pallet::storage]
+	pub type LastSlot<T> = StorageValue<_, u64, ValueQuery>;
+
+	/// Collator performance in the ended sessions of the history window.
+	#[pallet::storage]
+	#[pallet::getter(fn performance)]
+	pub type History<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		SessionIndex,
+		Twox64Concat,
+		T::AccountId,
+		CollatorPerformance,
+		OptionQuery,
+	>;
+
+	/// Rewards of ended sessions which are yet to be paid out, by author.
+	#[pallet::storage]
+	#[pallet::getter(fn pending_payout)]
+	pub type PendingPayouts<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, BalanceOf<T>, ValueQuery>;
+
+	/// The sum of `PendingPayouts`, which stays in the pot until it is paid out.
+	#[pallet::storage]
+	#[pallet::getter(fn pending_payout_total)]
+	pub type PendingPayoutTotal<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		pub invulnerables: Vec<T::AccountId>,
+		pub desired_candidates: u32,
+		pub min_candidate_bond: BalanceOf<T>,
+		pub inflation_rate: Perbill,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self {
+				invulnerables: Default::default(),
+				desired_candidates: Default::default(),
+				min_candidate_bond: Default::default(),
+				inflation_rate: Default::default(),
+			}
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			let duplicate_invulnerables = self.invulnerables.iter()
+				.collect::<sp_std::collections::btree_set::BTreeSet<_>>();
+			assert!(
+				duplicate_invulnerables.len() == self.invulnerables.len(),
+				"duplicate invulnerables in genesis."
+			);
+			assert!(
+				T::MaxInvulnerables::get() >= (self.invulnerables.len() as u32),
+				"genesis invulnerables are more than T::MaxInvulnerables",
+			);
+			assert!(
+				T::MaxCandidates::get() >= self.desired_candidates,
+				"genesis desired_candidates are more than T::MaxCandidates",
+			);
+
+			<DesiredCandidates<T>>::put(&self.desired_candidates);
+			<MinCandidateBond<T>>::put(&self.min_candidate_bond);
+			<Invulnerables<T>>::put(&self.invulnerables);
+			<InflationRate<T>>::put(&self.inflation_rate);
+		}
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(_n: BlockNumberFor<T>) -> Weight {
+			Self::pay_pending()
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Set the collators which are always selected.
+		#[pallet::weight(T::WeightInfo::set_invulnerables(new.len() as u32))]
+		pub fn set_invulnerables(origin: OriginFor<T>, new: Vec<T::AccountId>) -> DispatchResult {
+			T::UpdateOrigin::ensure_origin(origin)?;
+			ensure!(
+				new.len() as u32 <= T::MaxInvulnerables::get(),
+				Error::<T>::TooManyInvulnerables,
+			);
+			<Invulnerables<T>>::put(&new);
+			Self::deposit_event(Event::NewInvulnerables(new));
+			Ok(())
+		}
+
+		/// Set the number of candidates selected next to the invulnerables.
+		#[pallet::weight(T::WeightInfo::set_desired_candidates())]
+		pub fn set_desired_candidates(origin: OriginFor<T>, max: u32) -> DispatchResult {
+			T::UpdateOrigin::ensure_origin(origin)?;
+			ensure!(max <= T::MaxCandidates::get(), Error::<T>::TooManyCandidates);
+			<DesiredCandidates<T>>::put(&max);
+			Self::deposit_event(Event::NewDesiredCandidates(max));
+			Ok(())
+		}
+
+		/// Set the minimum bond for new candidates. Existing candidates are not affected.
+		#[pallet::weight(T::WeightInfo::set_min_candidate_bond())]
+		pub fn set_min_candidate_bond(
+			origin: OriginFor<T>,
+			bond: BalanceOf<T>,
+		) -> DispatchResult {
+			T::UpdateOrigin::ensure_origin(origin)?;
+			<MinCandidateBond<T>>::put(&bond);
+			Self::deposit_event(Event::NewMinCandidateBond(bond));
+			Ok(())
+		}
+
+		/// Set the part of the total issuance minted as rewards over a year.
+		#[pallet::weight(T::WeightInfo::set_inflation_rate())]
+		pub fn set_inflation_rate(origin: OriginFor<T>, rate: Perbill) -> DispatchResult {
+			T::UpdateOrigin::ensure_origin(origin)?;
+			<InflationRate<T>>::put(&rate);
+			Self::deposit_event(Event::NewInflationRate(rate));
+			Ok(())
+		}
+
+		/// Become a candidate by bonding `bond`, keeping `commission` of future rewards before
+		/// they are shared with delegators.
+		///
+		/// The caller must have registered session keys.
+		#[pallet::weight(T::WeightInfo::join_candidates())]
+		pub fn join_candidates(
+			origin: OriginFor<T>,
+			#[pallet::compact] bond: BalanceOf<T>,
+			commission: Perbill,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(!<Candidates<T>>::contains_key(&who), Error::<T>::AlreadyCandidate);
+			ensure!(bond >= Self::min_candidate_bond(), Error::<T>::BondTooLow);
+			let count = <CandidateCount<T>>::get();
+			ensure!(count < T::MaxCandidates::get(), Error::<T>::TooManyCandidates);
+			let validator_key = T::ValidatorIdOf::convert(who.clone())
+				.ok_or(Error::<T>::ValidatorNotRegistered)?;
+			ensure!(
+				T::ValidatorRegistration::is_registered(&validator_key),
+				Error::<T>::ValidatorNotRegistered,
+			);
+
+			T::Currency::reserve(&who, bond)?;
+			<Candidates<T>>::insert(&who, CandidateInfo {
+				bond,
+				delegated: Zero::zero(),
+				commission,
+				delegators: 0,
+			});
+			<CandidateCount<T>>::put(count + 1);
+			Self::deposit_event(Event::CandidateJoined(who, bond, commission));
+			Ok(())
+		}
+
+		/// Add `more` to the caller's candidate bond.
+		#[pallet::weight(T::WeightInfo::candidate_bond_more())]
+		pub fn candidate_bond_more(
+			origin: OriginFor<T>,
+			#[pallet::compact] more: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let bond = <Candidates<T>>::try_mutate(&who, |maybe_info| -> Result<_, DispatchError> {
+				let info = maybe_info.as_mut().ok_or(Error::<T>::NotCandidate)?;
+				T::Currency::reserve(&who, more)?;
+				info.bond = info.bond.saturating_add(more);
+				Ok(info.bond)
+			})?;
+			Self::deposit_event(Event::CandidateBondedMore(who, bond));
+			Ok(())
+		}
+
+		/// Change the commission the caller keeps as a candidate.
+		#[pallet::weight(T::WeightInfo::set_commission())]
+		pub fn set_commission(origin: OriginFor<T>, commission: Perbill) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			<Candidates<T>>::try_mutate(&who, |maybe_info| -> DispatchResult {
+				maybe_info.as_mut().ok_or(Error::<T>::NotCandidate)?.commission = commission;
+				Ok(())
+			})?;
+			Self::deposit_event(Event::CommissionSet(who, commission));
+			Ok(())
+		}
+
+		/// Stop being a candidate.
+		///
+		/// The caller's bond and every delegation to it start unbonding. A caller selected for
+		/// the current or next session still collates in it.
+		#[pallet::weight(T::WeightInfo::leave_candidates(T::MaxDelegatorsPerCandidate::get()))]
+		pub fn leave_candidates(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let info = <Candidates<T>>::get(&who).ok_or(Error::<T>::NotCandidate)?;
+			Self::unbond(&who, info.bond)?;
+			Self::remove_candidate(&who);
+			Self::deposit_event(Event::CandidateLeft(who));
+			Ok(Some(T::WeightInfo::leave_candidates(info.delegators)).into())
+		}
+
+		/// Delegate `amount` to `candidate`, or add it to an existing delegation.
+		#[pallet::weight(T::WeightInfo::delegate())]
+		pub fn delegate(
+			origin: OriginFor<T>,
+			candidate: T::AccountId,
+			#[pallet::compact] amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(who != candidate, Error::<T>::SelfDelegation);
+			let mut info = <Candidates<T>>::get(&candidate).ok_or(Error::<T>::NotCandidate)?;
+			let current = <Delegations<T>>::get(&candidate, &who);
+			let delegation = current.saturating_add(amount);
+			ensure!(delegation >= T::MinDelegation::get(), Error::<T>::DelegationTooLow);
+
+			if current.is_zero() {
+				ensure!(
+					info.delegators < T::MaxDelegatorsPerCandidate::get(),
+					Error::<T>::TooManyDelegators,
+				);
+				let count = <DelegationCount<T>>::get(&who);
+				ensure!(
+					count < T::MaxDelegationsPerDelegator::get(),
+					Error::<T>::TooManyDelegations,
+				);
+				<DelegationCount<T>>::insert(&who, count + 1);
+				info.delegators += 1;
+			}
+
+			T::Currency::reserve(&who, amount)?;
+			info.delegated = info.delegated.saturating_add(amount);
+			<Candidates<T>>::insert(&candidate, info);
+			<Delegations<T>>::insert(&candidate, &who, delegation);
+			Self::deposit_event(Event::Delegated(who, candidate, amount));
+			Ok(())
+		}
+
+		/// Withdraw `amount` of the caller's delegation to `candidate`.
+		///
+		/// The withdrawn amount starts unbonding. What is left delegated must be either nothing or
+		/// at least `MinDelegation`.
+		#[pallet::weight(T::WeightInfo::undelegate())]
+		pub fn undelegate(
+			origin: OriginFor<T>,
+			candidate: T::AccountId,
+			#[pallet::compact] amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let mut info = <Candidates<T>>::get(&candidate).ok_or(Error::<T>::NotCandidate)?;
+			let current = <Delegations<T>>::get(&candidate, &who);
+			ensure!(!amount.is_zero() && current >= amount, Error::<T>::NotEnoughDelegated);
+			let remaining = current - amount;
+			ensure!(
+				remaining.is_zero() || remaining >= T::MinDelegation::get(),
+				Error::<T>::DelegationTooLow,
+			);
+
+			Self::unbond(&who, amount)?;
+			info.delegated = info.delegated.saturating_sub(amount);
+			if remaining.is_zero() {
+				info.delegators = info.delegators.saturating_sub(1);
+				<Delegations<T>>::remove(&candidate, &who);
+				<DelegationCount<T>>::mutate_exists(&who, |count| {
+					*count = count.unwrap_or(1).checked_sub(1).filter(|c| *c > 0)
+				});
+			} else {
+				<Delegations<T>>::insert(&candidate, &who, remaining);
+			}
+			<Candidates<T>>::insert(&candidate, info);
+			Self::deposit_event(Event::Undelegated(who, candidate, amount));
+			Ok(())
+		}
+
+		/// Unlock all of the caller's unbonding funds whose delay has passed.
+		#[pallet::weight(T::WeightInfo::withdraw_unbonded())]
+		pub fn withdraw_unbonded(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let now = Self::current_session();
+			let mut withdrawn: BalanceOf<T> = Zero::zero();
+			<Unlocking<T>>::mutate_exists(&who, |maybe_chunks| {
+				let chunks = maybe_chunks.get_or_insert_with(Vec::new);
+				chunks.retain(|chunk| {
+					if chunk.session <= now {
+						withdrawn = chunk.value.saturating_add(withdrawn);
+						false
+					} else {
+						true
+					}
+				});
+				if chunks.is_empty() {
+					*maybe_chunks = None;
+				}
+			});
+			if !withdrawn.is_zero() {
+				T::Currency::unreserve(&who, withdrawn);
+				Self::deposit_event(Event::Withdrawn(who, withdrawn));
+			}
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// The account rewards are paid from.
+	pub fn account_id() -> T::AccountId {
+		T::PotId::get().into_account()
+	}
+
+	/// Start unbonding `value` of `who`'s reserved funds.
+	fn unbond(who: &T::AccountId, value: BalanceOf<T>) -> Result<(), Error<T>> {
+		let session = Self::current_session().saturating_add(T::UnbondingDelay::get());
+		<Unlocking<T>>::try_mutate(who, |chunks| {
+			match chunks.last_mut() {
+				Some(last) if last.session == session => {
+					last.value = last.value.saturating_add(value);
+				}
+				_ => {
+					ensure!(
+						(chunks.len() as u32) < T::MaxUnlockingChunks::get(),
+						Error::<T>::TooManyUnlockingChunks,
+					);
+					chunks.push(UnlockChunk { value, session });
+				}
+			}
+			Ok(())
+		})
+	}
+
+	/// Remove `who` from the candidates, unbonding all delegations to it. Its own bond must have
+	/// been unbonded already.
+	fn remove_candidate(who: &T::AccountId) {
+		for (delegator, amount) in <Delegations<T>>::drain_prefix(who) {
+			<DelegationCount<T>>::mutate_exists(&delegator, |count| {
+				*count = count.unwrap_or(1).checked_sub(1).filter(|c| *c > 0)
+			});
+			// There is no other way for the delegator to get these funds back, so unbond them
+			// even if the delegator is out of chunks.
+			Self::force_unbond(&delegator, amount);
+		}
+
+		<Candidates<T>>::remove(who);
+		<CandidateCount<T>>::mutate(|count| *count = count.saturating_sub(1));
+	}
+
+	/// As `unbond`, but merges into the latest chunk if `who` is out of chunks.
+	fn force_unbond(who: &T::AccountId, value: BalanceOf<T>) {
+		if Self::unbond(who, value).is_err() {
+			let session = Self::current_session().saturating_add(T::UnbondingDelay::get());
+			<Unlocking<T>>::mutate(who, |chunks| {
+				if let Some(last) = chunks.last_mut() {
+					last.value = last.value.saturating_add(value);
+					last.session = session;
+				}
+			});
+		}
+	}
+
+	/// The `DesiredCandidates` candidates with the most stake and registered session keys.
+	pub fn select_candidates() -> Vec<T::AccountId> {
+		let mut candidates = <Candidates<T>>::iter()
+			.filter(|(who, _)| {
+				T::ValidatorIdOf::convert(who.clone())
+					.map_or(false, |key| T::ValidatorRegistration::is_registered(&key))
+			})
+			.map(|(who, info)| (who, info.total()))
+			.collect::<Vec<_>>();
+		// Highest stake first, ties broken by account so that the order does not depend on
+		// storage layout.
+		candidates.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+		candidates.into_iter()
+			.take(Self::desired_candidates() as usize)
+			.map(|(who, _)| who)
+			.collect()
+	}
+
+	/// Count the slots skipped since the latest block as missed by their expected authors.
+	///
+	/// Returns the number of slots counted.
+	fn note_missed_slots() -> u64 {
+		let slot = T::SlotSchedule::current_slot();
+		let last = <LastSlot<T>>::mutate(|last| sp_std::mem::replace(last, slot));
+		if last == 0 || slot <= last + 1 {
+			return 0
+		}
+		let missed = (slot - last - 1).min(MAX_MISSED_SLOTS_PER_BLOCK);
+		for missed_slot in (slot - missed)..slot {
+			if let Some(who) = T::SlotSchedule::expected_author(missed_slot) {
+				<MissedSlots<T>>::mutate(who, |count| *count = count.saturating_add(1));
+			}
+		}
+		missed
+	}
+
+	/// Archive the performance of the ending `session`, remove up to `MaxKicksPerSession`
+	/// candidates which missed their slots without authoring, and drop the session which fell out
+	/// of the history window.
+	///
+	/// Returns the weight used.
+	fn record_performance(session: SessionIndex) -> Weight {
+		let mut performance = BTreeMap::<T::AccountId, CollatorPerformance>::new();
+		for (who, authored) in <Points<T>>::iter() {
+			performance.entry(who).or_default().authored = authored;
+		}
+		for (who, missed) in <MissedSlots<T>>::drain() {
+			performance.entry(who).or_default().missed = missed;
+		}
+		let mut weight = T::WeightInfo::record_performance(performance.len() as u32);
+
+		let invulnerables = Self::invulnerables();
+		let mut silent = Vec::new();
+		for (who, record) in performance {
+			<History<T>>::insert(session, &who, record);
+			if record.authored == 0 && !invulnerables.contains(&who) {
+				silent.push((who, record));
+			}
+		}
+
+		// Those which missed the most slots go first; the others stay until they are silent in a
+		// later session again.
+		silent.sort_by(|a, b| b.1.missed.cmp(&a.1.missed).then_with(|| a.0.cmp(&b.0)));
+		let mut kicked = 0;
+		for (who, record) in silent {
+			if kicked == T::MaxKicksPerSession::get() {
+				break
+			}
+			if let Some(info) = <Candidates<T>>::get(&who) {
+				Self::force_unbond(&who, info.bond);
+				Self::remove_candidate(&who);
+				Self::deposit_event(Event::CandidateKicked(who, record));
+				weight = weight.saturating_add(T::WeightInfo::kick_candidate(info.delegators));
+				kicked += 1;
+			}
+		}
+
+		if let Some(expired) = session.checked_sub(T::PerformanceHistoryDepth::get()) {
+			<History<T>>::remove_prefix(expired, None);
+		}
+		weight
+	}
+
+	/// The performance of `who` in each session of the history window, oldest first.
+	pub fn performance_history(who: &T::AccountId) -> Vec<(SessionIndex, CollatorPerformance)> {
+		let current = Self::current_session();
+		(current.saturating_sub(T::PerformanceHistoryDepth::get())..=current)
+			.filter_map(|session| <History<T>>::get(session, who).map(|record| (session, record)))
+			.collect()
+	}
+
+	/// The performance of every collator in `session`, if it is in the history window.
+	pub fn session_performance(session: SessionIndex) -> Vec<(T::AccountId, CollatorPerformance)> {
+		<History<T>>::iter_prefix(session).collect()
+	}
+
+	/// Share the pot among the authors of the ending session, after minting the session's
+	/// inflation into it. The shares are paid out by [`Self::pay_pending`].
+	///
+	/// Returns the weight used.
+	fn distribute_rewards() -> Weight {
+		let points = <Points<T>>::drain().collect::<Vec<_>>();
+		let weight = T::WeightInfo::distribute_rewards(points.len() as u32);
+		let total_points = points.iter().fold(0u32, |acc, (_, p)| acc.saturating_add(*p));
+		if total_points == 0 {
+			return weight
+		}
+		Self::mint_inflation();
+
+		// Shares of earlier sessions which are not paid out yet are not shared again.
+		let pot = Self::account_id();
+		let reward = T::Currency::free_balance(&pot)
+			.saturating_sub(T::Currency::minimum_balance())
+			.saturating_sub(Self::pending_payout_total());
+		if reward.is_zero() {
+			return weight
+		}
+		let mut queued: BalanceOf<T> = Zero::zero();
+		for (author, author_points) in points {
+			let share = Perbill::from_rational(author_points, total_points) * reward;
+			if !share.is_zero() {
+				<PendingPayouts<T>>::mutate(&author, |pending| *pending = pending.saturating_add(share));
+				queued = queued.saturating_add(share);
+			}
+		}
+		<PendingPayoutTotal<T>>::mutate(|total| *total = total.saturating_add(queued));
+		weight
+	}
+
+	/// Pay out the pending rewards of up to `MaxPayoutsPerBlock` authors.
+	///
+	/// Returns the weight used.
+	pub fn pay_pending() -> Weight {
+		let payouts = <PendingPayouts<T>>::drain()
+			.take(T::MaxPayoutsPerBlock::get() as usize)
+			.collect::<Vec<_>>();
+		if payouts.is_empty() {
+			return T::DbWeight::get().reads(1)
+		}
+
+		let pot = Self::account_id();
+		let mut weight: Weight = 0;
+		let mut paid: BalanceOf<T> = Zero::zero();
+		for (author, share) in payouts {
+			// What cannot be paid stays in the pot for the next session.
+			let delegators = Self::pay_author(&pot, &author, share);
+			weight = weight.saturating_add(T::WeightInfo::pay_author(delegators));
+			paid = paid.saturating_add(share);
+		}
+		<PendingPayoutTotal<T>>::mutate(|total| *total = total.saturating_sub(paid));
+		weight
+	}
+
+	/// Mint a session's part of the annual inflation into the pot.
+	fn mint_inflation() {
+		let sessions = T::SessionsPerYear::get();
+		if sessions == 0 {
+			return
+		}
+		let annual = Self::inflation_rate() * T::Currency::total_issuance();
+		let amount = annual / BalanceOf::<T>::from(sessions);
+		if amount.is_zero() {
+			return
+		}
+		let minted = T::Currency::deposit_creating(&Self::account_id(), amount);
+		Self::deposit_event(Event::InflationMinted(minted.peek()));
+	}
+
+	/// Pay `author` its `share` of the rewards, passing on delegators' parts by stake.
+	///
+	/// Returns the number of delegators paid.
+	fn pay_author(pot: &T::AccountId, author: &T::AccountId, share: BalanceOf<T>) -> u32 {
+		let mut author_share = share;
+		let mut delegators = 0;
+		if let Some(info) = <Candidates<T>>::get(author) {
+			delegators = info.delegators;
+			let shared = share.saturating_sub(info.commission * share);
+			let total = info.total();
+			if !total.is_zero() {
+				for (delegator, amount) in <Delegations<T>>::iter_prefix(author) {
+					let part = Perbill::from_rational(amount, total) * shared;
+					if Self::pay(pot, &delegator, part) {
+						author_share = author_share.saturating_sub(part);
+					}
+				}
+			}
+		}
+		Self::pay(pot, author, author_share);
+		delegators
+	}
+
+	fn pay(pot: &T::AccountId, who: &T::AccountId, amount: BalanceOf<T>) -> bool {
+		if amount.is_zero() {
+			return false
+		}
+		match T::Currency::transfer(pot, who, amount, ExistenceRequirement::KeepAlive) {
+			Ok(()) => {
+				Self::deposit_event(Event::Rewarded(who.clone(), amount));
+				true
+			}
+			Err(e) => {
+				log::warn!(target: "runtime::collator-staking", "failed to pay reward: {:?}", e);
+				false
+			}
+		}
+	}
+}
+
+/// Keep track of the number of blocks authored and slots missed by each collator in the current
+/// session.
+impl<T: Config> pallet_authorship::EventHandler<T::AccountId, T::BlockNumber> for Pallet<T> {
+	fn note_author(author: T::AccountId) {
+		<Points<T>>::mutate(author, |points| *points = points.saturating_add(1));
+		let missed = Self::note_missed_slots();
+		frame_system::Pallet::<T>::register_extra_weight_unchecked(
+			T::DbWeight::get().reads_writes(2 + missed, 2 + missed),
+			DispatchClass::Mandatory,
+		);
+	}
+
+	fn note_uncle(_author: T::AccountId, _age: T::BlockNumber) {
+		// We don't care about uncles.
+	}
+}
+
+/// Play the role of the session manager.
+impl<T: Config> SessionManager<T::AccountId> for Pallet<T> {
+	fn new_session(index: SessionIndex) -> Option<Vec<T::AccountId>> {
+		frame_system::Pallet::<T>::register_extra_weight_unchecked(
+			T::WeightInfo::new_session(<CandidateCount<T>>::get()),
+			DispatchClass::Mandatory,
+		);
+		let selected = Self::select_candidates();
+		<SelectedCandidates<T>>::put(&selected);
+		Self::deposit_event(Event::CandidatesSelected(index, selected.clone()));
+
+		let mut collators = Self::invulnerables();
+		let selected = selected.into_iter().filter(|who| !collators.contains(who)).collect::<Vec<_>>();
+		collators.extend(selected);
+		Some(collators)
+	}
+
+	fn start_session(index: SessionIndex) {
+		<CurrentSession<T>>::put(index);
+	}
+
+	fn end_session(index: SessionIndex) {
+		let weight = Self::record_performance(index).saturating_add(Self::distribute_rewards());
+		frame_system::Pallet::<T>::register_extra_weight_unchecked(
+			weight,
+			DispatchClass::Mandatory,
+		);
+	}
+}
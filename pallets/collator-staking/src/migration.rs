@@ -0,0 +1,100 @@
+//! Migrations of other pallets' state into this one, and of this pallet's storage between
+//! versions.
+
+/// Takes over from `pallet_collator_selection`.
+pub mod from_collator_selection {
+	use crate::*;
+	use frame_support::{
+		storage::migration::{remove_storage_prefix, take_storage_value},
+		traits::{Get, StorageVersion},
+		weights::Weight,
+	};
+
+	#[derive(Decode)]
+	struct OldCandidateInfo<AccountId, Balance> {
+		who: AccountId,
+		deposit: Balance,
+	}
+
+	/// Carry over the invulnerables, the desired number of candidates and the candidacy bond of
+	/// `pallet_collator_selection`, stored under `old_prefix`, and release its candidates'
+	/// deposits so that they can bond here instead, bringing the storage to version 1. Only bumps
+	/// the storage version if there is no such storage.
+	///
+	/// Both pallets pay rewards from the pot of the same `PotId`, so the pot carries over as is.
+	pub fn migrate<T: Config>(old_prefix: &[u8]) -> Weight {
+		StorageVersion::new(1).put::<Pallet<T>>();
+		let invulnerables =
+			take_storage_value::<Vec<T::AccountId>>(old_prefix, b"Invulnerables", &[]);
+		let invulnerables = match invulnerables {
+			Some(invulnerables) => invulnerables,
+			None => return T::DbWeight::get().reads_writes(1, 1),
+		};
+		let mut writes = 1;
+		<Invulnerables<T>>::put(&invulnerables);
+
+		if let Some(desired) = take_storage_value::<u32>(old_prefix, b"DesiredCandidates", &[]) {
+			<DesiredCandidates<T>>::put(desired);
+			writes += 1;
+		}
+		if let Some(bond) = take_storage_value::<BalanceOf<T>>(old_prefix, b"CandidacyBond", &[]) {
+			<MinCandidateBond<T>>::put(bond);
+			writes += 1;
+		}
+
+		let candidates = take_storage_value::<Vec<OldCandidateInfo<T::AccountId, BalanceOf<T>>>>(
+			old_prefix,
+			b"Candidates",
+			&[],
+		)
+		.unwrap_or_default();
+		for candidate in &candidates {
+			T::Currency::unreserve(&candidate.who, candidate.deposit);
+		}
+		writes += candidates.len() as Weight;
+		remove_storage_prefix(old_prefix, b"LastAuthoredBlock", &[]);
+
+		log::info!(
+			target: "runtime::collator-staking",
+			"migrated from collator selection: {} invulnerables, {} candidates released",
+			invulnerables.len(),
+			candidates.len(),
+		);
+		T::DbWeight::get().reads_writes(4, writes + 5)
+	}
+
+	/// Number of invulnerables `migrate` will carry over, `None` if there is nothing to migrate.
+	#[cfg(feature = "try-runtime")]
+	pub fn pre_migrate<T: Config>(old_prefix: &[u8]) -> Result<Option<u32>, &'static str> {
+		use frame_support::storage::migration::get_storage_value;
+
+		let invulnerables =
+			get_storage_value::<Vec<T::AccountId>>(old_prefix, b"Invulnerables", &[]);
+		if invulnerables.is_none() {
+			log::info!(target: "runtime::collator-staking", "no collator selection to migrate");
+		}
+		Ok(invulnerables.map(|invulnerables| invulnerables.len() as u32))
+	}
+
+	/// Check that `migrate` took over the `invulnerables` reported by `pre_migrate` and left
+	/// nothing of `pallet_collator_selection` behind.
+	#[cfg(feature = "try-runtime")]
+	pub fn post_migrate<T: Config>(
+		old_prefix: &[u8],
+		invulnerables: Option<u32>,
+	) -> Result<(), &'static str> {
+		use frame_support::storage::migration::have_storage_value;
+
+		for item in [&b"Invulnerables"[..], b"DesiredCandidates", b"CandidacyBond", b"Candidates"] {
+			if have_storage_value(old_prefix, item, &[]) {
+				return Err("collator selection storage left behind")
+			}
+		}
+		if let Some(invulnerables) = invulnerables {
+			if <Invulnerables<T>>::get().len() as u32 != invulnerables {
+				return Err("invulnerables not carried over")
+			}
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,204 @@
+use crate as pallet_collator_staking;
+use crate::SlotSchedule;
+use frame_support::{
+	ord_parameter_types, parameter_types,
+	traits::{Everything, GenesisBuild, ValidatorRegistration},
+	PalletId,
+};
+use frame_system::EnsureSignedBy;
+use sp_core::H256;
+use std::cell::RefCell;
+use sp_runtime::{
+	testing::{Header, UintAuthorityId},
+	traits::{BlakeTwo256, IdentityLookup, OpaqueKeys},
+	KeyTypeId, Perbill, RuntimeAppPublic,
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		CollatorStaking: pallet_collator_staking::{Pallet, Call, Storage, Config<T>, Event<T>},
+		Session: pallet_session::{Pallet, Call, Storage, Event},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+}
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type OnSetCode = ();
+	type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 5;
+	pub const MaxReserves: u32 = 50;
+}
+
+impl pallet_balances::Config for Test {
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+	type MaxLocks = ();
+	type MaxReserves = MaxReserves;
+	type ReserveIdentifier = [u8; 8];
+}
+
+/// Accounts without session keys.
+pub const UNREGISTERED: u64 = 99;
+
+pub struct IsRegistered;
+impl ValidatorRegistration<u64> for IsRegistered {
+	fn is_registered(id: &u64) -> bool {
+		*id != UNREGISTERED
+	}
+}
+
+sp_runtime::impl_opaque_keys! {
+	pub struct MockSessionKeys {
+		pub aura: UintAuthorityId,
+	}
+}
+
+pub struct TestSessionHandler;
+impl pallet_session::SessionHandler<u64> for TestSessionHandler {
+	const KEY_TYPE_IDS: &'static [KeyTypeId] = &[UintAuthorityId::ID];
+	fn on_genesis_session<Ks: OpaqueKeys>(_validators: &[(u64, Ks)]) {}
+	fn on_new_session<Ks: OpaqueKeys>(_changed: bool, _validators: &[(u64, Ks)], _queued: &[(u64, Ks)]) {}
+	fn on_disabled(_validator_index: u32) {}
+}
+
+parameter_types! {
+	pub const Period: u64 = 10;
+	pub const Offset: u64 = 0;
+}
+
+// Only used by the benchmarks to register keys; sessions are driven by hand in the tests.
+impl pallet_session::Config for Test {
+	type Event = Event;
+	type ValidatorId = u64;
+	type ValidatorIdOf = pallet_collator_staking::IdentityCollator;
+	type ShouldEndSession = pallet_session::PeriodicSessions<Period, Offset>;
+	type NextSessionRotation = pallet_session::PeriodicSessions<Period, Offset>;
+	type SessionManager = CollatorStaking;
+	type SessionHandler = TestSessionHandler;
+	type Keys = MockSessionKeys;
+	type WeightInfo = ();
+}
+
+ord_parameter_types! {
+	pub const RootAccount: u64 = 777;
+}
+
+parameter_types! {
+	pub const PotId: PalletId = PalletId(*b"PotStake");
+	pub const MaxCandidates: u32 = 20;
+	pub const MaxInvulnerables: u32 = 20;
+	pub const MaxDelegatorsPerCandidate: u32 = 2;
+	pub const MaxDelegationsPerDelegator: u32 = 2;
+	pub const MinDelegation: u64 = 10;
+	pub const UnbondingDelay: u32 = 2;
+	pub const MaxUnlockingChunks: u32 = 2;
+	pub const SessionsPerYear: u32 = 10;
+	pub const PerformanceHistoryDepth: u32 = 2;
+	pub const MaxKicksPerSession: u32 = 1;
+	pub const MaxPayoutsPerBlock: u32 = 1;
+}
+
+thread_local! {
+	pub static SLOT: RefCell<u64> = RefCell::new(0);
+}
+
+/// Slots assigned round-robin to collators 1, 2 and 3.
+pub struct TestSlots;
+impl SlotSchedule<u64> for TestSlots {
+	fn current_slot() -> u64 {
+		SLOT.with(|slot| *slot.borrow())
+	}
+	fn expected_author(slot: u64) -> Option<u64> {
+		Some(slot % 3 + 1)
+	}
+}
+
+/// Author the block of `slot` as its expected author.
+pub fn author_in_slot(slot: u64) {
+	use pallet_authorship::EventHandler;
+	SLOT.with(|s| *s.borrow_mut() = slot);
+	CollatorStaking::note_author(TestSlots::expected_author(slot).unwrap());
+}
+
+impl pallet_collator_staking::Config for Test {
+	type Event = Event;
+	type Currency = Balances;
+	type UpdateOrigin = EnsureSignedBy<RootAccount, u64>;
+	type PotId = PotId;
+	type MaxCandidates = MaxCandidates;
+	type MaxInvulnerables = MaxInvulnerables;
+	type MaxDelegatorsPerCandidate = MaxDelegatorsPerCandidate;
+	type MaxDelegationsPerDelegator = MaxDelegationsPerDelegator;
+	type MinDelegation = MinDelegation;
+	type UnbondingDelay = UnbondingDelay;
+	type MaxUnlockingChunks = MaxUnlockingChunks;
+	type SessionsPerYear = SessionsPerYear;
+	type PerformanceHistoryDepth = PerformanceHistoryDepth;
+	type MaxKicksPerSession = MaxKicksPerSession;
+	type MaxPayoutsPerBlock = MaxPayoutsPerBlock;
+	type SlotSchedule = TestSlots;
+	type ValidatorId = u64;
+	type ValidatorIdOf = pallet_collator_staking::IdentityCollator;
+	type ValidatorRegistration = IsRegistered;
+	type WeightInfo = ();
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let balances = (1..=10).map(|i| (i, 100)).chain(Some((UNREGISTERED, 100))).collect();
+	pallet_balances::GenesisConfig::<Test> { balances }.assimilate_storage(&mut t).unwrap();
+	pallet_collator_staking::GenesisConfig::<Test> {
+		invulnerables: vec![1, 2],
+		desired_candidates: 2,
+		min_candidate_bond: 10,
+		inflation_rate: Perbill::zero(),
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
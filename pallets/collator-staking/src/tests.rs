@@ -0,0 +1,522 @@
+use crate::{
+	mock::*, CandidateInfo, CollatorPerformance, Error, Event as StakingEvent, UnlockChunk,
+};
+use frame_support::{assert_noop, assert_ok, traits::{Currency, Hooks}, weights::DispatchClass};
+use pallet_authorship::EventHandler;
+use pallet_session::SessionManager;
+use sp_runtime::{traits::BadOrigin, Perbill};
+
+fn join(who: u64, bond: u64) {
+	assert_ok!(CollatorStaking::join_candidates(Origin::signed(who), bond, Perbill::zero()));
+}
+
+fn last_event() -> Event {
+	System::events().pop().expect("an event was deposited").event
+}
+
+/// Initialize blocks until every pending reward is paid out.
+fn pay_out() {
+	while crate::PendingPayouts::<Test>::iter().next().is_some() {
+		System::set_block_number(System::block_number() + 1);
+		CollatorStaking::on_initialize(System::block_number());
+	}
+}
+
+#[test]
+fn genesis_is_set() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(CollatorStaking::invulnerables(), vec![1, 2]);
+		assert_eq!(CollatorStaking::desired_candidates(), 2);
+		assert_eq!(CollatorStaking::min_candidate_bond(), 10);
+	});
+}
+
+#[test]
+fn only_update_origin_sets_parameters() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(CollatorStaking::set_invulnerables(Origin::signed(1), vec![3]), BadOrigin);
+		assert_noop!(CollatorStaking::set_desired_candidates(Origin::signed(1), 3), BadOrigin);
+		assert_noop!(CollatorStaking::set_min_candidate_bond(Origin::signed(1), 3), BadOrigin);
+		assert_noop!(
+			CollatorStaking::set_inflation_rate(Origin::signed(1), Perbill::from_percent(5)),
+			BadOrigin,
+		);
+
+		assert_ok!(CollatorStaking::set_invulnerables(Origin::signed(RootAccount::get()), vec![3]));
+		assert_ok!(CollatorStaking::set_desired_candidates(Origin::signed(RootAccount::get()), 3));
+		assert_ok!(CollatorStaking::set_min_candidate_bond(Origin::signed(RootAccount::get()), 20));
+		assert_eq!(CollatorStaking::invulnerables(), vec![3]);
+		assert_eq!(CollatorStaking::desired_candidates(), 3);
+		assert_eq!(CollatorStaking::min_candidate_bond(), 20);
+		assert_ok!(CollatorStaking::set_inflation_rate(
+			Origin::signed(RootAccount::get()),
+			Perbill::from_percent(5),
+		));
+		assert_eq!(CollatorStaking::inflation_rate(), Perbill::from_percent(5));
+
+		assert_noop!(
+			CollatorStaking::set_desired_candidates(Origin::signed(RootAccount::get()), 21),
+			Error::<Test>::TooManyCandidates,
+		);
+		assert_noop!(
+			CollatorStaking::set_invulnerables(Origin::signed(RootAccount::get()), (0..21).collect()),
+			Error::<Test>::TooManyInvulnerables,
+		);
+	});
+}
+
+#[test]
+fn join_candidates_reserves_bond() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			CollatorStaking::join_candidates(Origin::signed(3), 9, Perbill::zero()),
+			Error::<Test>::BondTooLow,
+		);
+		assert_noop!(
+			CollatorStaking::join_candidates(Origin::signed(UNREGISTERED), 10, Perbill::zero()),
+			Error::<Test>::ValidatorNotRegistered,
+		);
+
+		let commission = Perbill::from_percent(10);
+		assert_ok!(CollatorStaking::join_candidates(Origin::signed(3), 10, commission));
+		assert_eq!(last_event(), Event::CollatorStaking(StakingEvent::CandidateJoined(3, 10, commission)));
+		assert_eq!(Balances::reserved_balance(3), 10);
+		assert_eq!(
+			CollatorStaking::candidates(3),
+			Some(CandidateInfo { bond: 10, delegated: 0, commission, delegators: 0 }),
+		);
+
+		assert_noop!(
+			CollatorStaking::join_candidates(Origin::signed(3), 10, commission),
+			Error::<Test>::AlreadyCandidate,
+		);
+
+		assert_ok!(CollatorStaking::candidate_bond_more(Origin::signed(3), 5));
+		assert_ok!(CollatorStaking::set_commission(Origin::signed(3), Perbill::from_percent(5)));
+		assert_eq!(Balances::reserved_balance(3), 15);
+		assert_eq!(
+			CollatorStaking::candidates(3),
+			Some(CandidateInfo { bond: 15, delegated: 0, commission: Perbill::from_percent(5), delegators: 0 }),
+		);
+	});
+}
+
+#[test]
+fn candidates_are_selected_by_stake() {
+	new_test_ext().execute_with(|| {
+		join(3, 10);
+		join(4, 20);
+		join(5, 30);
+		assert_ok!(CollatorStaking::delegate(Origin::signed(6), 3, 25));
+
+		assert_eq!(CollatorStaking::new_session(1), Some(vec![1, 2, 3, 5]));
+		assert_eq!(CollatorStaking::selected_candidates(), vec![3, 5]);
+		assert_eq!(last_event(), Event::CollatorStaking(StakingEvent::CandidatesSelected(1, vec![3, 5])));
+
+		assert_ok!(CollatorStaking::undelegate(Origin::signed(6), 3, 25));
+		assert_eq!(CollatorStaking::new_session(2), Some(vec![1, 2, 5, 4]));
+	});
+}
+
+#[test]
+fn invulnerables_are_not_selected_twice() {
+	new_test_ext().execute_with(|| {
+		join(1, 50);
+		join(3, 10);
+		assert_eq!(CollatorStaking::new_session(1), Some(vec![1, 2, 3]));
+	});
+}
+
+#[test]
+fn delegation_limits_are_enforced() {
+	new_test_ext().execute_with(|| {
+		join(3, 10);
+		join(4, 10);
+		join(5, 10);
+
+		assert_noop!(CollatorStaking::delegate(Origin::signed(6), 7, 10), Error::<Test>::NotCandidate);
+		assert_noop!(CollatorStaking::delegate(Origin::signed(3), 3, 10), Error::<Test>::SelfDelegation);
+		assert_noop!(CollatorStaking::delegate(Origin::signed(6), 3, 9), Error::<Test>::DelegationTooLow);
+
+		assert_ok!(CollatorStaking::delegate(Origin::signed(6), 3, 10));
+		assert_ok!(CollatorStaking::delegate(Origin::signed(6), 4, 10));
+		assert_noop!(
+			CollatorStaking::delegate(Origin::signed(6), 5, 10),
+			Error::<Test>::TooManyDelegations,
+		);
+		// Topping up an existing delegation is always fine.
+		assert_ok!(CollatorStaking::delegate(Origin::signed(6), 3, 1));
+
+		assert_ok!(CollatorStaking::delegate(Origin::signed(7), 3, 10));
+		assert_noop!(
+			CollatorStaking::delegate(Origin::signed(8), 3, 10),
+			Error::<Test>::TooManyDelegators,
+		);
+		assert_eq!(
+			CollatorStaking::candidates(3),
+			Some(CandidateInfo { bond: 10, delegated: 21, commission: Perbill::zero(), delegators: 2 }),
+		);
+		assert_eq!(Balances::reserved_balance(6), 21);
+	});
+}
+
+#[test]
+fn undelegated_funds_unlock_after_delay() {
+	new_test_ext().execute_with(|| {
+		join(3, 10);
+		assert_ok!(CollatorStaking::delegate(Origin::signed(6), 3, 30));
+
+		assert_noop!(
+			CollatorStaking::undelegate(Origin::signed(6), 3, 31),
+			Error::<Test>::NotEnoughDelegated,
+		);
+		assert_noop!(
+			CollatorStaking::undelegate(Origin::signed(6), 3, 25),
+			Error::<Test>::DelegationTooLow,
+		);
+
+		assert_ok!(CollatorStaking::undelegate(Origin::signed(6), 3, 20));
+		assert_eq!(CollatorStaking::unlocking(6), vec![UnlockChunk { value: 20, session: 2 }]);
+		assert_eq!(CollatorStaking::delegations(3, 6), 10);
+
+		CollatorStaking::start_session(1);
+		assert_ok!(CollatorStaking::undelegate(Origin::signed(6), 3, 10));
+		assert_eq!(CollatorStaking::delegations(3, 6), 0);
+		assert_eq!(CollatorStaking::candidates(3).unwrap().delegators, 0);
+		assert_eq!(CollatorStaking::unlocking(6), vec![
+			UnlockChunk { value: 20, session: 2 },
+			UnlockChunk { value: 10, session: 3 },
+		]);
+
+		// Nothing is unlocked before its session.
+		assert_ok!(CollatorStaking::withdraw_unbonded(Origin::signed(6)));
+		assert_eq!(Balances::reserved_balance(6), 30);
+
+		CollatorStaking::start_session(2);
+		assert_ok!(CollatorStaking::withdraw_unbonded(Origin::signed(6)));
+		assert_eq!(last_event(), Event::CollatorStaking(StakingEvent::Withdrawn(6, 20)));
+		assert_eq!(Balances::reserved_balance(6), 10);
+
+		CollatorStaking::start_session(3);
+		assert_ok!(CollatorStaking::withdraw_unbonded(Origin::signed(6)));
+		assert_eq!(Balances::reserved_balance(6), 0);
+		assert!(CollatorStaking::unlocking(6).is_empty());
+	});
+}
+
+#[test]
+fn unlocking_chunks_are_bounded() {
+	new_test_ext().execute_with(|| {
+		join(3, 10);
+		assert_ok!(CollatorStaking::delegate(Origin::signed(6), 3, 50));
+		assert_ok!(CollatorStaking::undelegate(Origin::signed(6), 3, 10));
+		// Unbonding twice in the same session adds to the same chunk.
+		assert_ok!(CollatorStaking::undelegate(Origin::signed(6), 3, 10));
+		CollatorStaking::start_session(1);
+		assert_ok!(CollatorStaking::undelegate(Origin::signed(6), 3, 10));
+		CollatorStaking::start_session(2);
+		assert_noop!(
+			CollatorStaking::undelegate(Origin::signed(6), 3, 10),
+			Error::<Test>::TooManyUnlockingChunks,
+		);
+	});
+}
+
+#[test]
+fn leaving_unbonds_bond_and_delegations() {
+	new_test_ext().execute_with(|| {
+		join(3, 10);
+		assert_ok!(CollatorStaking::delegate(Origin::signed(6), 3, 30));
+		assert_ok!(CollatorStaking::delegate(Origin::signed(7), 3, 20));
+
+		assert_ok!(CollatorStaking::leave_candidates(Origin::signed(3)));
+		assert_eq!(last_event(), Event::CollatorStaking(StakingEvent::CandidateLeft(3)));
+		assert_eq!(CollatorStaking::candidates(3), None);
+		assert_eq!(CollatorStaking::delegations(3, 6), 0);
+		assert_eq!(CollatorStaking::unlocking(3), vec![UnlockChunk { value: 10, session: 2 }]);
+		assert_eq!(CollatorStaking::unlocking(6), vec![UnlockChunk { value: 30, session: 2 }]);
+		assert_eq!(CollatorStaking::unlocking(7), vec![UnlockChunk { value: 20, session: 2 }]);
+		assert_eq!(CollatorStaking::new_session(1), Some(vec![1, 2]));
+
+		// The delegators may back another candidate right away.
+		join(4, 10);
+		assert_ok!(CollatorStaking::delegate(Origin::signed(6), 4, 10));
+	});
+}
+
+#[test]
+fn rewards_are_shared_by_points_and_stake() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(CollatorStaking::join_candidates(Origin::signed(3), 10, Perbill::from_percent(10)));
+		assert_ok!(CollatorStaking::delegate(Origin::signed(6), 3, 30));
+		let pot = CollatorStaking::account_id();
+		Balances::make_free_balance_be(&pot, 1_005);
+
+		CollatorStaking::note_author(3);
+		CollatorStaking::note_author(3);
+		CollatorStaking::note_author(3);
+		CollatorStaking::note_author(1);
+		assert_eq!(CollatorStaking::points(3), 3);
+
+		CollatorStaking::end_session(0);
+		pay_out();
+
+		// 3 gets 750 of which it keeps 75 commission; 6 has three quarters of the stake behind 3
+		// and gets 506 of the remaining 675.
+		assert_eq!(Balances::free_balance(6), 70 + 506);
+		assert_eq!(Balances::free_balance(3), 90 + 750 - 506);
+		assert_eq!(Balances::free_balance(1), 100 + 250);
+		assert_eq!(Balances::free_balance(&pot), 5);
+		assert_eq!(CollatorStaking::points(3), 0);
+	});
+}
+
+#[test]
+fn empty_pot_pays_nothing() {
+	new_test_ext().execute_with(|| {
+		CollatorStaking::note_author(1);
+		CollatorStaking::end_session(0);
+		assert_eq!(Balances::free_balance(1), 100);
+		assert_eq!(CollatorStaking::points(1), 0);
+	});
+}
+
+#[test]
+fn rewards_are_paid_out_over_blocks() {
+	new_test_ext().execute_with(|| {
+		let pot = CollatorStaking::account_id();
+		Balances::make_free_balance_be(&pot, 105);
+		CollatorStaking::note_author(1);
+		CollatorStaking::note_author(2);
+
+		CollatorStaking::end_session(0);
+		assert_eq!(CollatorStaking::pending_payout(1), 50);
+		assert_eq!(CollatorStaking::pending_payout(2), 50);
+		assert_eq!(CollatorStaking::pending_payout_total(), 100);
+		assert_eq!(Balances::free_balance(&pot), 105);
+
+		// Shares still pending are not shared again.
+		CollatorStaking::note_author(1);
+		CollatorStaking::end_session(1);
+		assert_eq!(CollatorStaking::pending_payout_total(), 100);
+
+		// One author per block.
+		CollatorStaking::on_initialize(2);
+		assert_eq!(Balances::free_balance(&pot), 55);
+		assert_eq!(CollatorStaking::pending_payout_total(), 50);
+		CollatorStaking::on_initialize(3);
+		assert_eq!(Balances::free_balance(1), 150);
+		assert_eq!(Balances::free_balance(2), 150);
+		assert_eq!(Balances::free_balance(&pot), 5);
+		assert_eq!(CollatorStaking::pending_payout_total(), 0);
+	});
+}
+
+#[test]
+fn session_hooks_register_their_weight() {
+	use crate::WeightInfo;
+
+	new_test_ext().execute_with(|| {
+		join(3, 10);
+		let mandatory = || *System::block_weight().get(DispatchClass::Mandatory);
+
+		CollatorStaking::new_session(1);
+		assert_eq!(mandatory(), <() as WeightInfo>::new_session(1));
+
+		CollatorStaking::note_author(3);
+		let before = mandatory();
+		CollatorStaking::end_session(0);
+		assert_eq!(
+			mandatory() - before,
+			<() as WeightInfo>::record_performance(1) + <() as WeightInfo>::distribute_rewards(1),
+		);
+	});
+}
+
+#[test]
+fn inflation_is_minted_and_paid_to_authors() {
+	new_test_ext().execute_with(|| {
+		let pot = CollatorStaking::account_id();
+		Balances::make_free_balance_be(&pot, 5);
+		assert_ok!(CollatorStaking::set_inflation_rate(
+			Origin::signed(RootAccount::get()),
+			Perbill::from_percent(20),
+		));
+		assert_eq!(Balances::total_issuance(), 1_105);
+
+		CollatorStaking::note_author(1);
+		CollatorStaking::end_session(0);
+		pay_out();
+
+		// A tenth of 20% of 1105 per session.
+		assert!(System::events().iter().any(|r| r.event ==
+			Event::CollatorStaking(StakingEvent::InflationMinted(22))));
+		assert_eq!(Balances::total_issuance(), 1_127);
+		assert_eq!(Balances::free_balance(1), 122);
+		assert_eq!(Balances::free_balance(&pot), 5);
+	});
+}
+
+#[test]
+fn inflation_is_shared_with_delegators() {
+	new_test_ext().execute_with(|| {
+		join(3, 10);
+		assert_ok!(CollatorStaking::delegate(Origin::signed(6), 3, 30));
+		let pot = CollatorStaking::account_id();
+		Balances::make_free_balance_be(&pot, 5);
+		assert_ok!(CollatorStaking::set_inflation_rate(
+			Origin::signed(RootAccount::get()),
+			Perbill::from_percent(40),
+		));
+
+		CollatorStaking::note_author(3);
+		CollatorStaking::end_session(0);
+		pay_out();
+
+		// 44 minted, of which 6 has three quarters of the stake.
+		assert_eq!(Balances::total_issuance(), 1_149);
+		assert_eq!(Balances::free_balance(6), 70 + 33);
+		assert_eq!(Balances::free_balance(3), 90 + 11);
+	});
+}
+
+#[test]
+fn nothing_is_minted_without_authors() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(CollatorStaking::set_inflation_rate(
+			Origin::signed(RootAccount::get()),
+			Perbill::from_percent(20),
+		));
+		CollatorStaking::end_session(0);
+		assert_eq!(Balances::total_issuance(), 1_100);
+	});
+}
+
+fn performance(authored: u32, missed: u32) -> CollatorPerformance {
+	CollatorPerformance { authored, missed }
+}
+
+#[test]
+fn skipped_slots_are_missed_by_their_authors() {
+	new_test_ext().execute_with(|| {
+		// Slot 3 belongs to 1 and slot 5 to 3.
+		author_in_slot(1);
+		author_in_slot(2);
+		author_in_slot(4);
+		author_in_slot(6);
+		assert_eq!(CollatorStaking::missed_slots(1), 1);
+		assert_eq!(CollatorStaking::missed_slots(3), 1);
+
+		CollatorStaking::end_session(0);
+
+		assert_eq!(CollatorStaking::performance(0, 1), Some(performance(1, 1)));
+		assert_eq!(CollatorStaking::performance(0, 2), Some(performance(2, 0)));
+		assert_eq!(CollatorStaking::performance(0, 3), Some(performance(1, 1)));
+		assert_eq!(CollatorStaking::missed_slots(1), 0);
+		// Invulnerables are kept however they perform.
+		assert_eq!(CollatorStaking::invulnerables(), vec![1, 2]);
+	});
+}
+
+#[test]
+fn candidate_missing_all_its_slots_is_kicked() {
+	new_test_ext().execute_with(|| {
+		join(3, 10);
+		assert_ok!(CollatorStaking::delegate(Origin::signed(6), 3, 30));
+		author_in_slot(1);
+		author_in_slot(3);
+
+		CollatorStaking::end_session(0);
+
+		assert_eq!(
+			last_event(),
+			Event::CollatorStaking(StakingEvent::CandidateKicked(3, performance(0, 1))),
+		);
+		assert_eq!(CollatorStaking::candidates(3), None);
+		assert_eq!(CollatorStaking::unlocking(3), vec![UnlockChunk { value: 10, session: 2 }]);
+		assert_eq!(CollatorStaking::unlocking(6), vec![UnlockChunk { value: 30, session: 2 }]);
+	});
+}
+
+#[test]
+fn kicks_are_bounded_per_session() {
+	new_test_ext().execute_with(|| {
+		join(3, 10);
+		join(4, 10);
+		crate::MissedSlots::<Test>::insert(3, 1);
+		crate::MissedSlots::<Test>::insert(4, 2);
+
+		CollatorStaking::end_session(0);
+
+		// Only the one which missed the most slots is kicked.
+		assert_eq!(
+			last_event(),
+			Event::CollatorStaking(StakingEvent::CandidateKicked(4, performance(0, 2))),
+		);
+		assert_eq!(CollatorStaking::candidates(4), None);
+		assert!(CollatorStaking::candidates(3).is_some());
+	});
+}
+
+#[test]
+fn history_is_kept_for_the_window() {
+	new_test_ext().execute_with(|| {
+		for session in 0..3 {
+			CollatorStaking::note_author(2);
+			CollatorStaking::end_session(session);
+			CollatorStaking::start_session(session + 1);
+		}
+
+		assert_eq!(CollatorStaking::performance(0, 2), None);
+		assert_eq!(
+			CollatorStaking::performance_history(&2),
+			vec![(1, performance(1, 0)), (2, performance(1, 0))],
+		);
+		assert_eq!(CollatorStaking::session_performance(2), vec![(2, performance(1, 0))]);
+		assert_eq!(CollatorStaking::session_performance(0), vec![]);
+	});
+}
+
+#[test]
+fn collator_selection_is_migrated() {
+	use frame_support::{
+		storage::migration::{have_storage_value, put_storage_value},
+		traits::{GetStorageVersion, ReservableCurrency, StorageVersion},
+	};
+	use crate::migration::from_collator_selection::migrate;
+
+	new_test_ext().execute_with(|| {
+		assert_eq!(CollatorStaking::on_chain_storage_version(), StorageVersion::new(0));
+		assert_ok!(Balances::reserve(&5, 40));
+		put_storage_value(b"CollatorSelection", b"Invulnerables", &[], vec![3u64, 4]);
+		put_storage_value(b"CollatorSelection", b"DesiredCandidates", &[], 5u32);
+		put_storage_value(b"CollatorSelection", b"CandidacyBond", &[], 7u64);
+		put_storage_value(b"CollatorSelection", b"Candidates", &[], vec![(5u64, 40u64)]);
+		put_storage_value(b"CollatorSelection", b"LastAuthoredBlock", &[5], 1u64);
+
+		migrate::<Test>(b"CollatorSelection");
+
+		assert_eq!(CollatorStaking::invulnerables(), vec![3, 4]);
+		assert_eq!(CollatorStaking::desired_candidates(), 5);
+		assert_eq!(CollatorStaking::min_candidate_bond(), 7);
+		assert_eq!(Balances::reserved_balance(5), 0);
+		for item in [&b"Invulnerables"[..], b"DesiredCandidates", b"CandidacyBond", b"Candidates"] {
+			assert!(!have_storage_value(b"CollatorSelection", item, &[]));
+		}
+		assert!(!have_storage_value(b"CollatorSelection", b"LastAuthoredBlock", &[5]));
+		assert_eq!(CollatorStaking::on_chain_storage_version(), StorageVersion::new(1));
+	});
+}
+
+#[test]
+fn migration_without_collator_selection_only_bumps_version() {
+	use frame_support::traits::{GetStorageVersion, StorageVersion};
+
+	new_test_ext().execute_with(|| {
+		crate::migration::from_collator_selection::migrate::<Test>(b"CollatorSelection");
+
+		assert_eq!(CollatorStaking::invulnerables(), vec![1, 2]);
+		assert_eq!(CollatorStaking::desired_candidates(), 2);
+		assert_eq!(CollatorStaking::on_chain_storage_version(), StorageVersion::new(1));
+	});
+}
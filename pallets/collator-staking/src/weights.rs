@@ -0,0 +1,125 @@
+//! Weights for pallet_collator_staking.
+//!
+//! These are estimates from the storage each call touches, for tests. The runtime uses the
+//! weights `scripts/benchmark.sh` writes to `runtime/src/weights/pallet_collator_staking.rs`.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::weights::{constants::RocksDbWeight, Weight};
+
+/// Weight functions needed for pallet_collator_staking.
+pub trait WeightInfo {
+	fn set_invulnerables(b: u32) -> Weight;
+	fn set_desired_candidates() -> Weight;
+	fn set_min_candidate_bond() -> Weight;
+	fn set_inflation_rate() -> Weight;
+	fn join_candidates() -> Weight;
+	fn candidate_bond_more() -> Weight;
+	fn set_commission() -> Weight;
+	fn leave_candidates(d: u32) -> Weight;
+	fn delegate() -> Weight;
+	fn undelegate() -> Weight;
+	fn withdraw_unbonded() -> Weight;
+	fn new_session(c: u32) -> Weight;
+	fn record_performance(c: u32) -> Weight;
+	fn kick_candidate(d: u32) -> Weight;
+	fn distribute_rewards(a: u32) -> Weight;
+	fn pay_author(d: u32) -> Weight;
+}
+
+impl WeightInfo for () {
+	fn set_invulnerables(b: u32) -> Weight {
+		(18_000_000 as Weight)
+			.saturating_add((70_000 as Weight).saturating_mul(b as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn set_desired_candidates() -> Weight {
+		(16_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn set_min_candidate_bond() -> Weight {
+		(17_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn set_inflation_rate() -> Weight {
+		(16_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn join_candidates() -> Weight {
+		(60_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn candidate_bond_more() -> Weight {
+		(45_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	fn set_commission() -> Weight {
+		(25_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn leave_candidates(d: u32) -> Weight {
+		(55_000_000 as Weight)
+			.saturating_add((30_000_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((3 as Weight).saturating_mul(d as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((3 as Weight).saturating_mul(d as Weight)))
+	}
+	fn delegate() -> Weight {
+		(70_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
+	fn undelegate() -> Weight {
+		(60_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
+	fn withdraw_unbonded() -> Weight {
+		(45_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	fn new_session(c: u32) -> Weight {
+		(20_000_000 as Weight)
+			.saturating_add((15_000_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((2 as Weight).saturating_mul(c as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn record_performance(c: u32) -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((2 as Weight).saturating_mul(c as Weight)))
+			.saturating_add(RocksDbWeight::get().writes((3 as Weight).saturating_mul(c as Weight)))
+	}
+	fn kick_candidate(d: u32) -> Weight {
+		(55_000_000 as Weight)
+			.saturating_add((30_000_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((3 as Weight).saturating_mul(d as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((3 as Weight).saturating_mul(d as Weight)))
+	}
+	fn distribute_rewards(a: u32) -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add((5_000_000 as Weight).saturating_mul(a as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((2 as Weight).saturating_mul(a as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((2 as Weight).saturating_mul(a as Weight)))
+	}
+	fn pay_author(d: u32) -> Weight {
+		(40_000_000 as Weight)
+			.saturating_add((35_000_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((2 as Weight).saturating_mul(d as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((1 as Weight).saturating_mul(d as Weight)))
+	}
+}
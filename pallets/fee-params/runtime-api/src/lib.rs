@@ -0,0 +1,18 @@
+//! Runtime API for querying the transaction fee parameters in effect.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+	pub trait FeeParamsApi<Balance, Coefficient> where
+		Balance: Codec,
+		Coefficient: Codec,
+	{
+		/// The polynomial the weight of a transaction is charged by.
+		fn weight_to_fee() -> Vec<Coefficient>;
+
+		/// The fee charged per byte of a transaction.
+		fn byte_fee() -> Balance;
+	}
+}
@@ -0,0 +1,36 @@
+//! Benchmarks for the fee parameters pallet.
+
+use super::*;
+use frame_benchmarking::{benchmarks, impl_benchmark_test_suite};
+use frame_support::{assert_ok, traits::EnsureOrigin};
+
+benchmarks! {
+	set_weight_to_fee {
+		let c in 1 .. T::MaxCoefficients::get();
+		// The default polynomial, padded with terms that add nothing to the fee, keeps within
+		// the bounds while every term is still checked.
+		let mut coefficients = Pallet::<T>::weight_to_fee();
+		coefficients.resize(
+			c as usize,
+			FeeCoefficient { integer: Zero::zero(), frac: Perbill::zero(), negative: false, degree: 0 },
+		);
+		let origin = T::UpdateOrigin::successful_origin();
+	}: {
+		assert_ok!(Pallet::<T>::set_weight_to_fee(origin, Some(coefficients.clone())));
+	}
+	verify {
+		assert_eq!(Pallet::<T>::weight_to_fee(), coefficients);
+	}
+
+	set_byte_fee {
+		let fee = T::MaxByteFee::get();
+		let origin = T::UpdateOrigin::successful_origin();
+	}: {
+		assert_ok!(Pallet::<T>::set_byte_fee(origin, Some(fee)));
+	}
+	verify {
+		assert_eq!(Pallet::<T>::byte_fee(), fee);
+	}
+}
+
+impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
@@ -0,0 +1,274 @@
+//! Transaction fee parameters set by governance.
+//!
+//! The polynomial the weight of a transaction is charged by and the fee charged per byte of it
+//! are kept in storage, so that they can be changed without a runtime upgrade. Until governance
+//! sets them, `Config::DefaultWeightToFee` and `Config::DefaultByteFee` apply.
+//!
+//! [`WeightToFee`] and [`ByteFee`] read the values in effect, to be used as
+//! `pallet_transaction_payment::Config::WeightToFee` and, through a `ConstantMultiplier`,
+//! `LengthToFee`.
+//!
+//! New values are checked against bounds, so that a mistaken proposal can neither make
+//! transactions close to free nor price a full block out of reach. Polynomials may not have
+//! negative terms, so the fee never falls as weight grows and the bounds checked at the base
+//! weight of an extrinsic and at a full block hold for every weight in between.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod weights;
+
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use frame_support::{
+	traits::Get,
+	weights::{
+		DispatchClass, Weight, WeightToFeeCoefficient, WeightToFeeCoefficients,
+		WeightToFeePolynomial,
+	},
+};
+use sp_runtime::{
+	traits::{AtLeast32BitUnsigned, SaturatedConversion, Saturating, Zero},
+	Perbill, RuntimeDebug,
+};
+use sp_std::{marker::PhantomData, prelude::*};
+
+pub use pallet::*;
+pub use weights::WeightInfo;
+
+/// A term of the weight-to-fee polynomial, `(integer + frac) * weight ^ degree`, subtracted if
+/// `negative`.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct FeeCoefficient<Balance> {
+	pub integer: Balance,
+	pub frac: Perbill,
+	pub negative: bool,
+	pub degree: u8,
+}
+
+impl<Balance> From<WeightToFeeCoefficient<Balance>> for FeeCoefficient<Balance> {
+	fn from(c: WeightToFeeCoefficient<Balance>) -> Self {
+		Self { integer: c.coeff_integer, frac: c.coeff_frac, negative: c.negative, degree: c.degree }
+	}
+}
+
+impl<Balance> From<FeeCoefficient<Balance>> for WeightToFeeCoefficient<Balance> {
+	fn from(c: FeeCoefficient<Balance>) -> Self {
+		Self { coeff_integer: c.integer, coeff_frac: c.frac, negative: c.negative, degree: c.degree }
+	}
+}
+
+/// The fee of `weight` under the polynomial `coefficients`, computed as
+/// [`WeightToFeePolynomial::calc`] does.
+pub fn evaluate<Balance: AtLeast32BitUnsigned + Copy>(
+	coefficients: &[FeeCoefficient<Balance>],
+	weight: Weight,
+) -> Balance {
+	coefficients.iter().fold(Balance::zero(), |acc, c| {
+		let w = weight.saturated_into::<Balance>().saturating_pow(c.degree.into());
+		let term = c.integer.saturating_mul(w).saturating_add(c.frac * w);
+		if c.negative {
+			acc.saturating_sub(term)
+		} else {
+			acc.saturating_add(term)
+		}
+	})
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The balance type fees are charged in.
+		type Balance: Parameter + Member + AtLeast32BitUnsigned + Default + Copy;
+
+		/// Origin allowed to set the fee parameters.
+		type UpdateOrigin: EnsureOrigin<Self::Origin>;
+
+		/// The weight-to-fee polynomial in effect until governance sets one.
+		type DefaultWeightToFee: WeightToFeePolynomial<Balance = Self::Balance>;
+
+		/// The fee per byte in effect until governance sets one.
+		#[pallet::constant]
+		type DefaultByteFee: Get<Self::Balance>;
+
+		/// The most terms a weight-to-fee polynomial may have.
+		#[pallet::constant]
+		type MaxCoefficients: Get<u32>;
+
+		/// The lowest fee the base weight of an extrinsic may be charged.
+		#[pallet::constant]
+		type MinExtrinsicFee: Get<Self::Balance>;
+
+		/// The highest fee the weight of a full block may be charged.
+		#[pallet::constant]
+		type MaxBlockFee: Get<Self::Balance>;
+
+		/// The lowest fee per byte.
+		#[pallet::constant]
+		type MinByteFee: Get<Self::Balance>;
+
+		/// The highest fee per byte.
+		#[pallet::constant]
+		type MaxByteFee: Get<Self::Balance>;
+
+		/// Weight information for the calls of this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The weight-to-fee polynomial in effect changed. \[ coefficients \]
+		WeightToFeeSet(Vec<FeeCoefficient<T::Balance>>),
+		/// The fee per byte in effect changed. \[ fee \]
+		ByteFeeSet(T::Balance),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The polynomial has no terms.
+		NoCoefficients,
+		/// The polynomial has more than `Config::MaxCoefficients` terms.
+		TooManyCoefficients,
+		/// The polynomial has a negative term, which could make fees fall as weight grows.
+		NegativeCoefficient,
+		/// The polynomial charges the base weight of an extrinsic less than
+		/// `Config::MinExtrinsicFee`.
+		ExtrinsicFeeTooLow,
+		/// The polynomial charges a full block more than `Config::MaxBlockFee`.
+		BlockFeeTooHigh,
+		/// The fee per byte is below `Config::MinByteFee`.
+		ByteFeeTooLow,
+		/// The fee per byte is above `Config::MaxByteFee`.
+		ByteFeeTooHigh,
+	}
+
+	/// The weight-to-fee polynomial set by governance.
+	#[pallet::storage]
+	pub(super) type WeightToFeeOverride<T: Config> =
+		StorageValue<_, Vec<FeeCoefficient<T::Balance>>, OptionQuery>;
+
+	/// The fee per byte set by governance.
+	#[pallet::storage]
+	pub(super) type ByteFeeOverride<T: Config> = StorageValue<_, T::Balance, OptionQuery>;
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Set the weight-to-fee polynomial, or go back to `Config::DefaultWeightToFee` if
+		/// `coefficients` is `None`.
+		///
+		/// The dispatch origin must be `Config::UpdateOrigin`.
+		#[pallet::weight(T::WeightInfo::set_weight_to_fee(T::MaxCoefficients::get()))]
+		pub fn set_weight_to_fee(
+			origin: OriginFor<T>,
+			coefficients: Option<Vec<FeeCoefficient<T::Balance>>>,
+		) -> DispatchResult {
+			T::UpdateOrigin::ensure_origin(origin)?;
+			match coefficients {
+				Some(coefficients) => {
+					Self::check_weight_to_fee(&coefficients)?;
+					WeightToFeeOverride::<T>::put(coefficients);
+				},
+				None => WeightToFeeOverride::<T>::kill(),
+			}
+			Self::deposit_event(Event::WeightToFeeSet(Self::weight_to_fee()));
+			Ok(())
+		}
+
+		/// Set the fee per byte, or go back to `Config::DefaultByteFee` if `fee` is `None`.
+		///
+		/// The dispatch origin must be `Config::UpdateOrigin`.
+		#[pallet::weight(T::WeightInfo::set_byte_fee())]
+		pub fn set_byte_fee(origin: OriginFor<T>, fee: Option<T::Balance>) -> DispatchResult {
+			T::UpdateOrigin::ensure_origin(origin)?;
+			match fee {
+				Some(fee) => {
+					ensure!(fee >= T::MinByteFee::get(), Error::<T>::ByteFeeTooLow);
+					ensure!(fee <= T::MaxByteFee::get(), Error::<T>::ByteFeeTooHigh);
+					ByteFeeOverride::<T>::put(fee);
+				},
+				None => ByteFeeOverride::<T>::kill(),
+			}
+			Self::deposit_event(Event::ByteFeeSet(Self::byte_fee()));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// The weight-to-fee polynomial in effect.
+	pub fn weight_to_fee() -> Vec<FeeCoefficient<T::Balance>> {
+		WeightToFeeOverride::<T>::get().unwrap_or_else(|| {
+			T::DefaultWeightToFee::polynomial().into_iter().map(Into::into).collect()
+		})
+	}
+
+	/// The fee per byte in effect.
+	pub fn byte_fee() -> T::Balance {
+		ByteFeeOverride::<T>::get().unwrap_or_else(T::DefaultByteFee::get)
+	}
+
+	/// Check `coefficients` against the bounds in `Config`.
+	fn check_weight_to_fee(coefficients: &[FeeCoefficient<T::Balance>]) -> Result<(), Error<T>> {
+		frame_support::ensure!(!coefficients.is_empty(), Error::<T>::NoCoefficients);
+		frame_support::ensure!(
+			coefficients.len() <= T::MaxCoefficients::get() as usize,
+			Error::<T>::TooManyCoefficients
+		);
+		frame_support::ensure!(
+			coefficients.iter().all(|c| !c.negative),
+			Error::<T>::NegativeCoefficient
+		);
+
+		// With no negative terms the fee grows with weight, so it is bounded at both ends.
+		let weights = T::BlockWeights::get();
+		let base = weights.get(DispatchClass::Normal).base_extrinsic;
+		frame_support::ensure!(
+			evaluate(coefficients, base) >= T::MinExtrinsicFee::get(),
+			Error::<T>::ExtrinsicFeeTooLow
+		);
+		frame_support::ensure!(
+			evaluate(coefficients, weights.max_block) <= T::MaxBlockFee::get(),
+			Error::<T>::BlockFeeTooHigh
+		);
+		Ok(())
+	}
+}
+
+/// The weight-to-fee polynomial in effect in pallet `T`.
+pub struct WeightToFee<T>(PhantomData<T>);
+impl<T: Config> WeightToFeePolynomial for WeightToFee<T> {
+	type Balance = T::Balance;
+
+	fn polynomial() -> WeightToFeeCoefficients<Self::Balance> {
+		Pallet::<T>::weight_to_fee().into_iter().map(Into::into).collect()
+	}
+}
+
+/// The fee per byte in effect in pallet `T`.
+pub struct ByteFee<T>(PhantomData<T>);
+impl<T: Config> Get<T::Balance> for ByteFee<T> {
+	fn get() -> T::Balance {
+		Pallet::<T>::byte_fee()
+	}
+}
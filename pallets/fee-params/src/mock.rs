@@ -0,0 +1,96 @@
+use crate as pallet_fee_params;
+use frame_support::{
+	parameter_types,
+	traits::Everything,
+	weights::{DispatchClass, IdentityFee},
+};
+use frame_system::{limits::BlockWeights, EnsureRoot};
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		FeeParams: pallet_fee_params::{Pallet, Call, Storage, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+	// Extrinsics weigh at least 10 and blocks at most 1000.
+	pub TestBlockWeights: BlockWeights = BlockWeights::builder()
+		.base_block(0)
+		.for_class(DispatchClass::all(), |weights| {
+			weights.base_extrinsic = 10;
+			weights.max_total = Some(1_000);
+		})
+		.build_or_panic();
+}
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = Everything;
+	type BlockWeights = TestBlockWeights;
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type OnSetCode = ();
+	type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+parameter_types! {
+	pub const DefaultByteFee: u64 = 1;
+	pub const MaxCoefficients: u32 = 2;
+	pub const MinExtrinsicFee: u64 = 5;
+	pub const MaxBlockFee: u64 = 10_000;
+	pub const MinByteFee: u64 = 1;
+	pub const MaxByteFee: u64 = 100;
+}
+
+impl pallet_fee_params::Config for Test {
+	type Event = Event;
+	type Balance = u64;
+	type UpdateOrigin = EnsureRoot<u64>;
+	type DefaultWeightToFee = IdentityFee<u64>;
+	type DefaultByteFee = DefaultByteFee;
+	type MaxCoefficients = MaxCoefficients;
+	type MinExtrinsicFee = MinExtrinsicFee;
+	type MaxBlockFee = MaxBlockFee;
+	type MinByteFee = MinByteFee;
+	type MaxByteFee = MaxByteFee;
+	type WeightInfo = ();
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
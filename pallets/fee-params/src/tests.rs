@@ -0,0 +1,140 @@
+use crate::{
+	evaluate, mock::*, ByteFee, Error, Event as FeeParamsEvent, FeeCoefficient, WeightToFee,
+	WeightToFeeOverride,
+};
+use frame_support::{assert_noop, assert_ok, traits::Get, weights::WeightToFeePolynomial};
+use sp_runtime::{traits::BadOrigin, Perbill};
+
+fn term(integer: u64, frac: Perbill, negative: bool, degree: u8) -> FeeCoefficient<u64> {
+	FeeCoefficient { integer, frac, negative, degree }
+}
+
+fn linear(integer: u64) -> Vec<FeeCoefficient<u64>> {
+	vec![term(integer, Perbill::zero(), false, 1)]
+}
+
+fn last_event() -> Event {
+	System::events().pop().expect("an event was deposited").event
+}
+
+#[test]
+fn defaults_apply_until_set() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(FeeParams::weight_to_fee(), linear(1));
+		assert_eq!(WeightToFee::<Test>::calc(&7), 7);
+		assert_eq!(FeeParams::byte_fee(), 1);
+		assert_eq!(ByteFee::<Test>::get(), 1);
+	});
+}
+
+#[test]
+fn set_weight_to_fee_works() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(FeeParams::set_weight_to_fee(Origin::signed(1), Some(linear(2))), BadOrigin);
+
+		assert_ok!(FeeParams::set_weight_to_fee(Origin::root(), Some(linear(2))));
+		assert_eq!(FeeParams::weight_to_fee(), linear(2));
+		assert_eq!(WeightToFee::<Test>::calc(&7), 14);
+		assert_eq!(last_event(), Event::FeeParams(FeeParamsEvent::WeightToFeeSet(linear(2))));
+
+		assert_ok!(FeeParams::set_weight_to_fee(Origin::root(), None));
+		assert_eq!(WeightToFee::<Test>::calc(&7), 7);
+		assert_eq!(last_event(), Event::FeeParams(FeeParamsEvent::WeightToFeeSet(linear(1))));
+	});
+}
+
+#[test]
+fn weight_to_fee_is_bounded() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			FeeParams::set_weight_to_fee(Origin::root(), Some(vec![])),
+			Error::<Test>::NoCoefficients
+		);
+		let three_terms = [linear(1), linear(1), linear(1)].concat();
+		assert_noop!(
+			FeeParams::set_weight_to_fee(Origin::root(), Some(three_terms)),
+			Error::<Test>::TooManyCoefficients
+		);
+		// The base extrinsic weight of 10 would cost 1.
+		assert_noop!(
+			FeeParams::set_weight_to_fee(
+				Origin::root(),
+				Some(vec![term(0, Perbill::from_percent(10), false, 1)])
+			),
+			Error::<Test>::ExtrinsicFeeTooLow
+		);
+		// A full block of 1000 would cost 20_000.
+		assert_noop!(
+			FeeParams::set_weight_to_fee(Origin::root(), Some(linear(20))),
+			Error::<Test>::BlockFeeTooHigh
+		);
+		// A full block would cost 1_000_000.
+		let quadratic = vec![term(1, Perbill::zero(), false, 2)];
+		assert_noop!(
+			FeeParams::set_weight_to_fee(Origin::root(), Some(quadratic)),
+			Error::<Test>::BlockFeeTooHigh
+		);
+
+		assert_ok!(FeeParams::set_weight_to_fee(Origin::root(), Some(linear(10))));
+	});
+}
+
+#[test]
+fn negative_terms_are_rejected() {
+	new_test_ext().execute_with(|| {
+		// Within both bounds, yet cheaper at a weight of 400 than at 100.
+		let dipping = vec![
+			term(8, Perbill::zero(), false, 1),
+			term(0, Perbill::from_percent(2), true, 2),
+		];
+		assert_eq!(evaluate(&dipping, 10), 78);
+		assert_eq!(evaluate(&dipping, 1_000), 0);
+		assert!(evaluate(&dipping, 400) < evaluate(&dipping, 100));
+		assert_noop!(
+			FeeParams::set_weight_to_fee(Origin::root(), Some(dipping)),
+			Error::<Test>::NegativeCoefficient
+		);
+	});
+}
+
+#[test]
+fn evaluate_matches_the_polynomial_charged() {
+	new_test_ext().execute_with(|| {
+		let coefficients = vec![
+			term(3, Perbill::from_percent(50), false, 1),
+			term(10, Perbill::zero(), true, 0),
+		];
+		// Stored directly, as the setter rejects negative terms.
+		WeightToFeeOverride::<Test>::put(coefficients.clone());
+
+		for weight in [0, 1, 3, 10, 333, 1_000].iter() {
+			assert_eq!(evaluate(&coefficients, *weight), WeightToFee::<Test>::calc(weight));
+		}
+		assert_eq!(evaluate(&coefficients, 100), 340);
+		// The negative term saturates at zero.
+		assert_eq!(evaluate(&coefficients, 2), 0);
+	});
+}
+
+#[test]
+fn set_byte_fee_works() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(FeeParams::set_byte_fee(Origin::signed(1), Some(2)), BadOrigin);
+		assert_noop!(
+			FeeParams::set_byte_fee(Origin::root(), Some(0)),
+			Error::<Test>::ByteFeeTooLow
+		);
+		assert_noop!(
+			FeeParams::set_byte_fee(Origin::root(), Some(101)),
+			Error::<Test>::ByteFeeTooHigh
+		);
+
+		assert_ok!(FeeParams::set_byte_fee(Origin::root(), Some(100)));
+		assert_eq!(ByteFee::<Test>::get(), 100);
+		assert_eq!(last_event(), Event::FeeParams(FeeParamsEvent::ByteFeeSet(100)));
+
+		assert_ok!(FeeParams::set_byte_fee(Origin::root(), None));
+		assert_eq!(ByteFee::<Test>::get(), 1);
+		assert_eq!(last_event(), Event::FeeParams(FeeParamsEvent::ByteFeeSet(1)));
+	});
+}
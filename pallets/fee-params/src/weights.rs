@@ -0,0 +1,27 @@
+//! Weights for pallet_fee_params.
+//!
+//! These are estimates from the storage each call touches, until the pallet is benchmarked with
+//! `scripts/benchmark.sh`.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::weights::{constants::RocksDbWeight, Weight};
+
+/// Weight functions needed for pallet_fee_params.
+pub trait WeightInfo {
+	fn set_weight_to_fee(c: u32) -> Weight;
+	fn set_byte_fee() -> Weight;
+}
+
+impl WeightInfo for () {
+	fn set_weight_to_fee(c: u32) -> Weight {
+		(12_000_000 as Weight)
+			.saturating_add((1_000_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn set_byte_fee() -> Weight {
+		(10_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+}
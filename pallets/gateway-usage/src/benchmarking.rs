@@ -0,0 +1,57 @@
+//! Benchmarks for the gateway usage pallet.
+
+use super::*;
+use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_support::{assert_ok, traits::EnsureOrigin};
+use frame_system::RawOrigin;
+
+/// Register a gateway run by `operator`.
+fn register<T: Config>(operator: &T::AccountId) -> GatewayId {
+	let gateway = NextGatewayId::<T>::get();
+	assert_ok!(Pallet::<T>::register_gateway(
+		T::RegistrarOrigin::successful_origin(),
+		operator.clone(),
+	));
+	gateway
+}
+
+benchmarks! {
+	register_gateway {
+		let operator: T::AccountId = account("operator", 0, 0);
+		let gateway = NextGatewayId::<T>::get();
+		let origin = T::RegistrarOrigin::successful_origin();
+	}: {
+		assert_ok!(Pallet::<T>::register_gateway(origin, operator.clone()));
+	}
+	verify {
+		assert_eq!(Pallet::<T>::operator(gateway), Some(operator));
+	}
+
+	deregister_gateway {
+		let gateway = register::<T>(&account("operator", 0, 0));
+		let origin = T::RegistrarOrigin::successful_origin();
+	}: {
+		assert_ok!(Pallet::<T>::deregister_gateway(origin, gateway));
+	}
+	verify {
+		assert!(Pallet::<T>::operator(gateway).is_none());
+	}
+
+	report_usage {
+		let operator: T::AccountId = whitelisted_caller();
+		let gateway = register::<T>(&operator);
+		let consumer: T::AccountId = account("consumer", 0, 0);
+		// Adding to usage already reported touches the same storage as the first report.
+		Pallet::<T>::report_usage(
+			RawOrigin::Signed(operator.clone()).into(),
+			gateway,
+			consumer.clone(),
+			1,
+		)?;
+	}: _(RawOrigin::Signed(operator), gateway, consumer.clone(), 1)
+	verify {
+		assert_eq!(Pallet::<T>::usage(gateway, &consumer), 2);
+	}
+}
+
+impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
@@ -0,0 +1,313 @@
+//! Registered API gateways and the usage they report.
+//!
+//! Governance registers gateways with [`Pallet::register_gateway`], each run by one operator
+//! account. The operator reports the API calls its gateway served to each consumer with
+//! [`Pallet::report_usage`].
+//!
+//! Gateways report often, and their reports are what the protocol runs on, so
+//! [`FeelessUsageReports`] waives the fees of reports by a gateway's operator. In exchange, a
+//! gateway may only have `Config::MaxReportsPerBlock` reports in a block. A report over the quota
+//! is rejected as invalid while the transaction is validated, before it is dispatched.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod weights;
+
+use codec::{Decode, Encode};
+use frame_support::traits::IsSubType;
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{DispatchInfoOf, PostDispatchInfoOf, SignedExtension},
+	transaction_validity::{
+		InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
+};
+use sp_std::{fmt, marker::PhantomData};
+
+pub use pallet::*;
+pub use weights::WeightInfo;
+
+/// The identifier of a gateway.
+pub type GatewayId = u32;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// Origin allowed to register and deregister gateways.
+		type RegistrarOrigin: EnsureOrigin<Self::Origin>;
+
+		/// The number of feeless usage reports a gateway may have included per block.
+		#[pallet::constant]
+		type MaxReportsPerBlock: Get<u32>;
+
+		/// Weight information for the calls of this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A gateway was registered. \[ gateway, operator \]
+		GatewayRegistered(GatewayId, T::AccountId),
+		/// A gateway was deregistered. \[ gateway \]
+		GatewayDeregistered(GatewayId),
+		/// A gateway reported the calls it served to a consumer. \[ gateway, consumer, calls \]
+		UsageReported(GatewayId, T::AccountId, u32),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The gateway is not registered.
+		UnknownGateway,
+		/// The caller does not operate the gateway.
+		NotOperator,
+	}
+
+	/// The identifier the next registered gateway gets.
+	#[pallet::storage]
+	pub(super) type NextGatewayId<T: Config> = StorageValue<_, GatewayId, ValueQuery>;
+
+	/// The operators of registered gateways.
+	#[pallet::storage]
+	#[pallet::getter(fn operator)]
+	pub(super) type Gateways<T: Config> =
+		StorageMap<_, Twox64Concat, GatewayId, T::AccountId, OptionQuery>;
+
+	/// The calls each gateway reported serving to each consumer, in total.
+	#[pallet::storage]
+	#[pallet::getter(fn usage)]
+	pub(super) type Usage<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		GatewayId,
+		Blake2_128Concat,
+		T::AccountId,
+		u64,
+		ValueQuery,
+	>;
+
+	/// The last block a gateway had a feeless report in, and the number of its feeless reports in
+	/// that block.
+	#[pallet::storage]
+	#[pallet::getter(fn reports)]
+	pub(super) type Reports<T: Config> =
+		StorageMap<_, Twox64Concat, GatewayId, (T::BlockNumber, u32), OptionQuery>;
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Register a gateway run by `operator`.
+		///
+		/// The dispatch origin must be `Config::RegistrarOrigin`.
+		#[pallet::weight(T::WeightInfo::register_gateway())]
+		pub fn register_gateway(origin: OriginFor<T>, operator: T::AccountId) -> DispatchResult {
+			T::RegistrarOrigin::ensure_origin(origin)?;
+			let gateway = NextGatewayId::<T>::get();
+			NextGatewayId::<T>::put(gateway.saturating_add(1));
+			Gateways::<T>::insert(gateway, &operator);
+			Self::deposit_event(Event::GatewayRegistered(gateway, operator));
+			Ok(())
+		}
+
+		/// Deregister `gateway`. The usage it reported is kept.
+		///
+		/// The dispatch origin must be `Config::RegistrarOrigin`.
+		#[pallet::weight(T::WeightInfo::deregister_gateway())]
+		pub fn deregister_gateway(
+			origin: OriginFor<T>,
+			#[pallet::compact] gateway: GatewayId,
+		) -> DispatchResult {
+			T::RegistrarOrigin::ensure_origin(origin)?;
+			ensure!(Gateways::<T>::contains_key(gateway), Error::<T>::UnknownGateway);
+			Gateways::<T>::remove(gateway);
+			Reports::<T>::remove(gateway);
+			Self::deposit_event(Event::GatewayDeregistered(gateway));
+			Ok(())
+		}
+
+		/// Report that `gateway` served `calls` API calls to `consumer`.
+		///
+		/// The dispatch origin must be signed by the operator of `gateway`.
+		#[pallet::weight(T::WeightInfo::report_usage())]
+		pub fn report_usage(
+			origin: OriginFor<T>,
+			#[pallet::compact] gateway: GatewayId,
+			consumer: T::AccountId,
+			#[pallet::compact] calls: u32,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let operator = Gateways::<T>::get(gateway).ok_or(Error::<T>::UnknownGateway)?;
+			ensure!(who == operator, Error::<T>::NotOperator);
+			Usage::<T>::mutate(gateway, &consumer, |usage| {
+				*usage = usage.saturating_add(calls.into())
+			});
+			Self::deposit_event(Event::UsageReported(gateway, consumer, calls));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// The gateway `call` reports usage for, if it is a usage report by the gateway's operator.
+	pub fn reporting_gateway(
+		who: &T::AccountId,
+		call: &<T as frame_system::Config>::Call,
+	) -> Option<GatewayId>
+	where
+		<T as frame_system::Config>::Call: IsSubType<Call<T>>,
+	{
+		match call.is_sub_type() {
+			Some(Call::report_usage { gateway, .. })
+				if Gateways::<T>::get(gateway).as_ref() == Some(who) =>
+				Some(*gateway),
+			_ => None,
+		}
+	}
+
+	/// The number of feeless reports `gateway` has in the current block.
+	fn reports_in_block(gateway: GatewayId) -> u32 {
+		match Reports::<T>::get(gateway) {
+			Some((at, reports)) if at == frame_system::Pallet::<T>::block_number() => reports,
+			_ => 0,
+		}
+	}
+
+	/// Whether `gateway` may have another feeless report in the current block.
+	pub fn has_quota(gateway: GatewayId) -> bool {
+		Self::reports_in_block(gateway) < T::MaxReportsPerBlock::get()
+	}
+
+	/// Count a feeless report of `gateway` in the current block against its quota.
+	fn note_report(gateway: GatewayId) {
+		let now = frame_system::Pallet::<T>::block_number();
+		let reports = Self::reports_in_block(gateway).saturating_add(1);
+		Reports::<T>::insert(gateway, (now, reports));
+	}
+
+	fn ensure_quota(gateway: GatewayId) -> Result<(), TransactionValidityError> {
+		if Self::has_quota(gateway) {
+			Ok(())
+		} else {
+			Err(InvalidTransaction::ExhaustsResources.into())
+		}
+	}
+}
+
+/// Waives the fees `S` charges for usage reports by the operator of the reporting gateway, up to
+/// the gateway's quota per block, and rejects the operator's reports beyond it. Every other
+/// transaction is passed on to `S`.
+///
+/// Meant to wrap the fee extension in a runtime's signed extensions. It encodes as `S` and goes by
+/// its identifier, so transactions are signed as if `S` were used directly.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct FeelessUsageReports<T, S>(S, PhantomData<T>);
+
+impl<T, S> FeelessUsageReports<T, S> {
+	/// The extension charging the fees of everything but usage reports.
+	pub fn inner(&self) -> &S {
+		&self.0
+	}
+}
+
+impl<T, S> From<S> for FeelessUsageReports<T, S> {
+	fn from(inner: S) -> Self {
+		Self(inner, PhantomData)
+	}
+}
+
+impl<T, S: fmt::Debug> fmt::Debug for FeelessUsageReports<T, S> {
+	#[cfg(feature = "std")]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "FeelessUsageReports<{:?}>", self.0)
+	}
+	#[cfg(not(feature = "std"))]
+	fn fmt(&self, _: &mut fmt::Formatter) -> fmt::Result {
+		Ok(())
+	}
+}
+
+impl<T, S> SignedExtension for FeelessUsageReports<T, S>
+where
+	T: Config + Send + Sync,
+	<T as frame_system::Config>::Call: IsSubType<Call<T>>,
+	S: SignedExtension<AccountId = T::AccountId, Call = <T as frame_system::Config>::Call>,
+{
+	const IDENTIFIER: &'static str = S::IDENTIFIER;
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::Call;
+	type AdditionalSigned = S::AdditionalSigned;
+	/// What `S` charged, or `None` for a feeless report.
+	type Pre = Option<S::Pre>;
+
+	fn additional_signed(&self) -> Result<Self::AdditionalSigned, TransactionValidityError> {
+		self.0.additional_signed()
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> TransactionValidity {
+		match Pallet::<T>::reporting_gateway(who, call) {
+			Some(gateway) => {
+				Pallet::<T>::ensure_quota(gateway)?;
+				Ok(ValidTransaction::default())
+			},
+			None => self.0.validate(who, call, info, len),
+		}
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		match Pallet::<T>::reporting_gateway(who, call) {
+			Some(gateway) => {
+				Pallet::<T>::ensure_quota(gateway)?;
+				Pallet::<T>::note_report(gateway);
+				Ok(None)
+			},
+			None => self.0.pre_dispatch(who, call, info, len).map(Some),
+		}
+	}
+
+	fn post_dispatch(
+		pre: Option<Self::Pre>,
+		info: &DispatchInfoOf<Self::Call>,
+		post_info: &PostDispatchInfoOf<Self::Call>,
+		len: usize,
+		result: &sp_runtime::DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		match pre {
+			Some(None) => Ok(()),
+			Some(Some(pre)) => S::post_dispatch(Some(pre), info, post_info, len, result),
+			None => S::post_dispatch(None, info, post_info, len, result),
+		}
+	}
+}
@@ -0,0 +1,127 @@
+use crate as pallet_gateway_usage;
+use frame_support::{
+	parameter_types,
+	traits::Everything,
+	weights::{ConstantMultiplier, DispatchClass, IdentityFee},
+};
+use frame_system::{limits::BlockWeights, EnsureRoot};
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		TransactionPayment: pallet_transaction_payment::{Pallet, Storage},
+		GatewayUsage: pallet_gateway_usage::{Pallet, Call, Storage, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+	// No base fee per extrinsic, so that a fee is its length plus its weight.
+	pub TestBlockWeights: BlockWeights = BlockWeights::builder()
+		.base_block(0)
+		.for_class(DispatchClass::all(), |weights| weights.base_extrinsic = 0)
+		.build_or_panic();
+}
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = Everything;
+	type BlockWeights = TestBlockWeights;
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type OnSetCode = ();
+	type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+	pub const MaxReserves: u32 = 50;
+}
+
+impl pallet_balances::Config for Test {
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+	type MaxLocks = ();
+	type MaxReserves = MaxReserves;
+	type ReserveIdentifier = [u8; 8];
+}
+
+parameter_types! {
+	pub const TransactionByteFee: u64 = 1;
+	pub const OperationalFeeMultiplier: u8 = 5;
+}
+
+impl pallet_transaction_payment::Config for Test {
+	type OnChargeTransaction = pallet_transaction_payment::CurrencyAdapter<Balances, ()>;
+	type OperationalFeeMultiplier = OperationalFeeMultiplier;
+	type WeightToFee = IdentityFee<u64>;
+	type LengthToFee = ConstantMultiplier<u64, TransactionByteFee>;
+	type FeeMultiplierUpdate = ();
+}
+
+parameter_types! {
+	pub const MaxReportsPerBlock: u32 = 2;
+}
+
+impl pallet_gateway_usage::Config for Test {
+	type Event = Event;
+	type RegistrarOrigin = EnsureRoot<u64>;
+	type MaxReportsPerBlock = MaxReportsPerBlock;
+	type WeightInfo = ();
+}
+
+/// Operator of `GATEWAY`.
+pub const OPERATOR: u64 = 1;
+/// A consumer of the API `GATEWAY` serves.
+pub const CONSUMER: u64 = 2;
+/// The gateway registered at genesis.
+pub const GATEWAY: u32 = 0;
+
+/// `OPERATOR` and `CONSUMER` hold 100, and `OPERATOR` runs `GATEWAY`.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> { balances: vec![(OPERATOR, 100), (CONSUMER, 100)] }
+		.assimilate_storage(&mut t)
+		.unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		GatewayUsage::register_gateway(Origin::root(), OPERATOR).unwrap();
+	});
+	ext
+}
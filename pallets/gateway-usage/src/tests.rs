@@ -0,0 +1,136 @@
+use crate::{mock::*, Error, Event as GatewayEvent, FeelessUsageReports};
+use frame_support::{
+	assert_noop, assert_ok,
+	weights::{DispatchInfo, PostDispatchInfo},
+};
+use pallet_transaction_payment::ChargeTransactionPayment;
+use sp_runtime::{
+	traits::{BadOrigin, SignedExtension},
+	transaction_validity::{InvalidTransaction, TransactionValidityError},
+};
+
+type Extension = FeelessUsageReports<Test, ChargeTransactionPayment<Test>>;
+
+const LEN: usize = 10;
+
+fn last_event() -> Event {
+	System::events().pop().expect("an event was deposited").event
+}
+
+fn extension() -> Extension {
+	ChargeTransactionPayment::<Test>::from(0).into()
+}
+
+fn report(gateway: u32) -> Call {
+	Call::GatewayUsage(crate::Call::report_usage { gateway, consumer: CONSUMER, calls: 3 })
+}
+
+fn info() -> DispatchInfo {
+	DispatchInfo { weight: 5, ..Default::default() }
+}
+
+/// Apply `call` by `who` through the extension, as a block would.
+fn apply(who: u64, call: &Call) -> Result<(), InvalidTransaction> {
+	let pre = extension().pre_dispatch(&who, call, &info(), LEN).map_err(|e| match e {
+		TransactionValidityError::Invalid(e) => e,
+		_ => unreachable!("the extension only rejects transactions as invalid"),
+	})?;
+	assert_ok!(Extension::post_dispatch(
+		Some(pre),
+		&info(),
+		&PostDispatchInfo::default(),
+		LEN,
+		&Ok(())
+	));
+	Ok(())
+}
+
+#[test]
+fn registrar_manages_gateways() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(GatewayUsage::register_gateway(Origin::signed(CONSUMER), CONSUMER), BadOrigin);
+		assert_ok!(GatewayUsage::register_gateway(Origin::root(), CONSUMER));
+		assert_eq!(GatewayUsage::operator(GATEWAY + 1), Some(CONSUMER));
+		assert_eq!(last_event(), Event::GatewayUsage(GatewayEvent::GatewayRegistered(1, CONSUMER)));
+
+		assert_noop!(GatewayUsage::deregister_gateway(Origin::signed(OPERATOR), GATEWAY), BadOrigin);
+		assert_ok!(GatewayUsage::deregister_gateway(Origin::root(), GATEWAY));
+		assert_eq!(GatewayUsage::operator(GATEWAY), None);
+		assert_eq!(last_event(), Event::GatewayUsage(GatewayEvent::GatewayDeregistered(GATEWAY)));
+		assert_noop!(
+			GatewayUsage::deregister_gateway(Origin::root(), GATEWAY),
+			Error::<Test>::UnknownGateway,
+		);
+	});
+}
+
+#[test]
+fn operators_report_usage() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			GatewayUsage::report_usage(Origin::signed(CONSUMER), GATEWAY, CONSUMER, 3),
+			Error::<Test>::NotOperator,
+		);
+		assert_noop!(
+			GatewayUsage::report_usage(Origin::signed(OPERATOR), GATEWAY + 1, CONSUMER, 3),
+			Error::<Test>::UnknownGateway,
+		);
+
+		assert_ok!(GatewayUsage::report_usage(Origin::signed(OPERATOR), GATEWAY, CONSUMER, 3));
+		assert_ok!(GatewayUsage::report_usage(Origin::signed(OPERATOR), GATEWAY, CONSUMER, 4));
+		assert_eq!(GatewayUsage::usage(GATEWAY, CONSUMER), 7);
+		assert_eq!(
+			last_event(),
+			Event::GatewayUsage(GatewayEvent::UsageReported(GATEWAY, CONSUMER, 4))
+		);
+	});
+}
+
+#[test]
+fn operator_reports_are_feeless() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(extension().validate(&OPERATOR, &report(GATEWAY), &info(), LEN));
+		assert_eq!(apply(OPERATOR, &report(GATEWAY)), Ok(()));
+		assert_eq!(Balances::free_balance(OPERATOR), 100);
+		assert_eq!(GatewayUsage::reports(GATEWAY), Some((1, 1)));
+	});
+}
+
+#[test]
+fn everything_else_pays_fees() {
+	new_test_ext().execute_with(|| {
+		// Reports by anyone but the operator, and other calls by the operator.
+		assert_eq!(apply(CONSUMER, &report(GATEWAY)), Ok(()));
+		assert_eq!(Balances::free_balance(CONSUMER), 100 - 15);
+		let remark = Call::System(frame_system::Call::remark { remark: vec![] });
+		assert_eq!(apply(OPERATOR, &remark), Ok(()));
+		assert_eq!(Balances::free_balance(OPERATOR), 100 - 15);
+		assert_eq!(GatewayUsage::reports(GATEWAY), None);
+
+		// Reports for a gateway that was deregistered.
+		assert_ok!(GatewayUsage::deregister_gateway(Origin::root(), GATEWAY));
+		assert_eq!(apply(OPERATOR, &report(GATEWAY)), Ok(()));
+		assert_eq!(Balances::free_balance(OPERATOR), 100 - 30);
+	});
+}
+
+#[test]
+fn reports_beyond_the_quota_are_rejected() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(apply(OPERATOR, &report(GATEWAY)), Ok(()));
+		assert_eq!(apply(OPERATOR, &report(GATEWAY)), Ok(()));
+
+		assert_eq!(
+			extension().validate(&OPERATOR, &report(GATEWAY), &info(), LEN),
+			Err(InvalidTransaction::ExhaustsResources.into())
+		);
+		assert_eq!(apply(OPERATOR, &report(GATEWAY)), Err(InvalidTransaction::ExhaustsResources));
+		assert_eq!(Balances::free_balance(OPERATOR), 100);
+
+		// The quota is back in the next block.
+		System::set_block_number(2);
+		assert_ok!(extension().validate(&OPERATOR, &report(GATEWAY), &info(), LEN));
+		assert_eq!(apply(OPERATOR, &report(GATEWAY)), Ok(()));
+		assert_eq!(GatewayUsage::reports(GATEWAY), Some((2, 1)));
+	});
+}
@@ -0,0 +1,34 @@
+//! Weights for pallet_gateway_usage.
+//!
+//! These are estimates from the storage each call touches, until the pallet is benchmarked with
+//! `scripts/benchmark.sh`.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::weights::{constants::RocksDbWeight, Weight};
+
+/// Weight functions needed for pallet_gateway_usage.
+pub trait WeightInfo {
+	fn register_gateway() -> Weight;
+	fn deregister_gateway() -> Weight;
+	fn report_usage() -> Weight;
+}
+
+impl WeightInfo for () {
+	fn register_gateway() -> Weight {
+		(20_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	fn deregister_gateway() -> Weight {
+		(20_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	fn report_usage() -> Weight {
+		(25_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+}
@@ -0,0 +1,162 @@
+//! Governance control of this parachain's HRMP channels.
+//!
+//! Opening, accepting and closing an HRMP channel are calls of the relay chain's `Hrmp` pallet,
+//! which must be dispatched with this parachain's origin. The calls of this pallet build the
+//! matching relay-chain call and send it up in a `Transact`, paying the relay-chain fees from the
+//! parachain's sovereign account there.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+use codec::Encode;
+use cumulus_primitives_core::ParaId;
+use frame_support::weights::Weight;
+use sp_std::prelude::*;
+use xcm::latest::prelude::*;
+
+pub use pallet::*;
+
+/// Call indices of the relay chain's `Hrmp` pallet.
+pub mod call_index {
+	pub const HRMP_INIT_OPEN_CHANNEL: u8 = 0;
+	pub const HRMP_ACCEPT_OPEN_CHANNEL: u8 = 1;
+	pub const HRMP_CLOSE_CHANNEL: u8 = 2;
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// Origin allowed to manage HRMP channels.
+		type UpdateOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Means of sending XCM to the relay chain.
+		type XcmSender: SendXcm;
+
+		/// This parachain's id, to which fees left over on the relay chain are refunded.
+		type SelfParaId: Get<ParaId>;
+
+		/// Index of the `Hrmp` pallet in the relay chain's runtime.
+		#[pallet::constant]
+		type RelayHrmpPalletIndex: Get<u8>;
+
+		/// Amount of the relay chain's native asset withdrawn from the sovereign account to pay
+		/// for each call.
+		#[pallet::constant]
+		type RelayFee: Get<u128>;
+
+		/// Weight the relay chain is allowed to spend on each call.
+		#[pallet::constant]
+		type RelayCallWeight: Get<Weight>;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A request to open a channel to a recipient was sent to the relay chain.
+		/// \[ recipient, max_capacity, max_message_size \]
+		OpenChannelRequested(ParaId, u32, u32),
+		/// Acceptance of a channel requested by a sender was sent to the relay chain.
+		/// \[ sender \]
+		AcceptChannelRequested(ParaId),
+		/// A request to close a channel was sent to the relay chain. \[ sender, recipient \]
+		CloseChannelRequested(ParaId, ParaId),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The message could not be sent to the relay chain.
+		SendFailure,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Request a channel from this parachain to `recipient`.
+		///
+		/// The channel is opened once `recipient` accepts it.
+		#[pallet::weight(100_000_000)]
+		pub fn open_channel(
+			origin: OriginFor<T>,
+			recipient: ParaId,
+			max_capacity: u32,
+			max_message_size: u32,
+		) -> DispatchResult {
+			T::UpdateOrigin::ensure_origin(origin)?;
+			Self::send_to_relay(
+				call_index::HRMP_INIT_OPEN_CHANNEL,
+				(recipient, max_capacity, max_message_size),
+			)?;
+			Self::deposit_event(Event::OpenChannelRequested(recipient, max_capacity, max_message_size));
+			Ok(())
+		}
+
+		/// Accept a channel from `sender` to this parachain.
+		#[pallet::weight(100_000_000)]
+		pub fn accept_channel(origin: OriginFor<T>, sender: ParaId) -> DispatchResult {
+			T::UpdateOrigin::ensure_origin(origin)?;
+			Self::send_to_relay(call_index::HRMP_ACCEPT_OPEN_CHANNEL, sender)?;
+			Self::deposit_event(Event::AcceptChannelRequested(sender));
+			Ok(())
+		}
+
+		/// Close the channel from `sender` to `recipient`, one of which must be this parachain.
+		#[pallet::weight(100_000_000)]
+		pub fn close_channel(
+			origin: OriginFor<T>,
+			sender: ParaId,
+			recipient: ParaId,
+		) -> DispatchResult {
+			T::UpdateOrigin::ensure_origin(origin)?;
+			// The argument is an `HrmpChannelId`, which encodes as its two fields.
+			Self::send_to_relay(call_index::HRMP_CLOSE_CHANNEL, (sender, recipient))?;
+			Self::deposit_event(Event::CloseChannelRequested(sender, recipient));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Build the message dispatching the `Hrmp` call `call_index` with `args` on the relay chain
+	/// as this parachain.
+	pub fn relay_message(call_index: u8, args: impl Encode) -> Xcm<()> {
+		let call = (T::RelayHrmpPalletIndex::get(), call_index, args).encode();
+		let fees: MultiAsset = (Here, T::RelayFee::get()).into();
+		Xcm(vec![
+			WithdrawAsset(fees.clone().into()),
+			BuyExecution { fees, weight_limit: Unlimited },
+			Transact {
+				origin_type: OriginKind::Native,
+				require_weight_at_most: T::RelayCallWeight::get(),
+				call: call.into(),
+			},
+			RefundSurplus,
+			DepositAsset {
+				assets: All.into(),
+				max_assets: 1,
+				beneficiary: Parachain(T::SelfParaId::get().into()).into(),
+			},
+		])
+	}
+
+	fn send_to_relay(call_index: u8, args: impl Encode) -> Result<(), Error<T>> {
+		let message = Self::relay_message(call_index, args);
+		T::XcmSender::send_xcm(Parent, message).map_err(|_| Error::<T>::SendFailure)
+	}
+}
@@ -0,0 +1,103 @@
+use crate as pallet_hrmp_manager;
+use cumulus_primitives_core::ParaId;
+use frame_support::{parameter_types, traits::Everything, weights::Weight};
+use frame_system::EnsureRoot;
+use sp_core::H256;
+use sp_runtime::{testing::Header, traits::{BlakeTwo256, IdentityLookup}};
+use std::cell::RefCell;
+use xcm::latest::prelude::*;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		HrmpManager: pallet_hrmp_manager::{Pallet, Call, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+}
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type OnSetCode = ();
+	type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+thread_local! {
+	pub static SENT_XCM: RefCell<Vec<(MultiLocation, Xcm<()>)>> = RefCell::new(Vec::new());
+	pub static SEND_FAILS: RefCell<bool> = RefCell::new(false);
+}
+
+pub fn sent_xcm() -> Vec<(MultiLocation, Xcm<()>)> {
+	SENT_XCM.with(|q| (*q.borrow()).clone())
+}
+
+pub fn set_send_fails(fails: bool) {
+	SEND_FAILS.with(|f| *f.borrow_mut() = fails);
+}
+
+/// Records sent messages instead of routing them anywhere.
+pub struct TestSendXcm;
+impl SendXcm for TestSendXcm {
+	fn send_xcm(dest: impl Into<MultiLocation>, msg: Xcm<()>) -> SendResult {
+		if SEND_FAILS.with(|f| *f.borrow()) {
+			return Err(SendError::Transport("queue full"))
+		}
+		SENT_XCM.with(|q| q.borrow_mut().push((dest.into(), msg)));
+		Ok(())
+	}
+}
+
+parameter_types! {
+	pub const SelfParaId: ParaId = ParaId::new(2019);
+	pub const RelayHrmpPalletIndex: u8 = 60;
+	pub const RelayFee: u128 = 1_000_000_000;
+	pub const RelayCallWeight: Weight = 2_000_000_000;
+}
+
+impl pallet_hrmp_manager::Config for Test {
+	type Event = Event;
+	type UpdateOrigin = EnsureRoot<u64>;
+	type XcmSender = TestSendXcm;
+	type SelfParaId = SelfParaId;
+	type RelayHrmpPalletIndex = RelayHrmpPalletIndex;
+	type RelayFee = RelayFee;
+	type RelayCallWeight = RelayCallWeight;
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -0,0 +1,83 @@
+use crate::{mock::*, Error, Event as HrmpEvent};
+use cumulus_primitives_core::ParaId;
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::traits::BadOrigin;
+use xcm::latest::prelude::*;
+
+fn expected_message(call: Vec<u8>) -> Xcm<()> {
+	let fees: MultiAsset = (Here, 1_000_000_000u128).into();
+	Xcm(vec![
+		WithdrawAsset(fees.clone().into()),
+		BuyExecution { fees, weight_limit: Unlimited },
+		Transact {
+			origin_type: OriginKind::Native,
+			require_weight_at_most: 2_000_000_000,
+			call: call.into(),
+		},
+		RefundSurplus,
+		DepositAsset { assets: All.into(), max_assets: 1, beneficiary: Parachain(2019).into() },
+	])
+}
+
+fn last_event() -> Event {
+	System::events().pop().expect("an event was deposited").event
+}
+
+#[test]
+fn open_channel_sends_init_open_channel() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(HrmpManager::open_channel(Origin::root(), 2000.into(), 8, 1024));
+
+		// Pallet 60, call 0, then recipient, capacity and message size as little-endian u32s.
+		let call = vec![60, 0, 0xd0, 0x07, 0, 0, 8, 0, 0, 0, 0, 4, 0, 0];
+		assert_eq!(sent_xcm(), vec![(Parent.into(), expected_message(call))]);
+		assert_eq!(last_event(), Event::HrmpManager(HrmpEvent::OpenChannelRequested(2000.into(), 8, 1024)));
+	});
+}
+
+#[test]
+fn accept_channel_sends_accept_open_channel() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(HrmpManager::accept_channel(Origin::root(), 2000.into()));
+
+		let call = vec![60, 1, 0xd0, 0x07, 0, 0];
+		assert_eq!(sent_xcm(), vec![(Parent.into(), expected_message(call))]);
+		assert_eq!(last_event(), Event::HrmpManager(HrmpEvent::AcceptChannelRequested(2000.into())));
+	});
+}
+
+#[test]
+fn close_channel_sends_close_channel() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(HrmpManager::close_channel(Origin::root(), 2019.into(), 2000.into()));
+
+		let call = vec![60, 2, 0xe3, 0x07, 0, 0, 0xd0, 0x07, 0, 0];
+		assert_eq!(sent_xcm(), vec![(Parent.into(), expected_message(call))]);
+		assert_eq!(
+			last_event(),
+			Event::HrmpManager(HrmpEvent::CloseChannelRequested(2019.into(), 2000.into())),
+		);
+	});
+}
+
+#[test]
+fn only_update_origin_manages_channels() {
+	new_test_ext().execute_with(|| {
+		let recipient = ParaId::from(2000);
+		assert_noop!(HrmpManager::open_channel(Origin::signed(1), recipient, 8, 1024), BadOrigin);
+		assert_noop!(HrmpManager::accept_channel(Origin::signed(1), recipient), BadOrigin);
+		assert_noop!(HrmpManager::close_channel(Origin::signed(1), 2019.into(), recipient), BadOrigin);
+		assert!(sent_xcm().is_empty());
+	});
+}
+
+#[test]
+fn send_failure_is_reported() {
+	new_test_ext().execute_with(|| {
+		set_send_fails(true);
+		assert_noop!(
+			HrmpManager::open_channel(Origin::root(), 2000.into(), 8, 1024),
+			Error::<Test>::SendFailure,
+		);
+	});
+}
@@ -0,0 +1,55 @@
+//! Benchmarks for the service payments pallet.
+
+use super::*;
+use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_support::traits::Get;
+use frame_system::RawOrigin;
+use sp_runtime::traits::Bounded;
+
+fn funded<T: Config>(who: &T::AccountId) {
+	T::Currency::make_free_balance_be(who, BalanceOf::<T>::max_value() / 2u32.into());
+}
+
+benchmarks! {
+	deposit {
+		let consumer: T::AccountId = whitelisted_caller();
+		let provider: T::AccountId = account("provider", 0, 0);
+		funded::<T>(&consumer);
+		let amount = T::MinDeposit::get();
+		// Adding to an existing escrow reads and writes the same storage as opening one.
+		Pallet::<T>::deposit(RawOrigin::Signed(consumer.clone()).into(), provider.clone(), amount)?;
+	}: _(RawOrigin::Signed(consumer.clone()), provider.clone(), amount)
+	verify {
+		assert_eq!(Escrows::<T>::get(&consumer, &provider).unwrap().amount, amount + amount);
+	}
+
+	charge {
+		let consumer: T::AccountId = account("consumer", 0, 0);
+		let provider: T::AccountId = whitelisted_caller();
+		funded::<T>(&consumer);
+		let amount = T::MinDeposit::get();
+		Pallet::<T>::deposit(RawOrigin::Signed(consumer.clone()).into(), provider.clone(), amount)?;
+	}: _(RawOrigin::Signed(provider.clone()), consumer.clone(), amount)
+	verify {
+		assert!(Escrows::<T>::get(&consumer, &provider).is_none());
+	}
+
+	refund {
+		let consumer: T::AccountId = whitelisted_caller();
+		let provider: T::AccountId = account("provider", 0, 0);
+		funded::<T>(&consumer);
+		Pallet::<T>::deposit(
+			RawOrigin::Signed(consumer.clone()).into(),
+			provider.clone(),
+			T::MinDeposit::get(),
+		)?;
+		frame_system::Pallet::<T>::set_block_number(
+			frame_system::Pallet::<T>::block_number() + T::RefundDelay::get(),
+		);
+	}: _(RawOrigin::Signed(consumer.clone()), provider.clone())
+	verify {
+		assert!(Escrows::<T>::get(&consumer, &provider).is_none());
+	}
+}
+
+impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
@@ -0,0 +1,186 @@
+//! Escrow for paying service providers.
+//!
+//! A consumer deposits funds for a provider with [`Pallet::deposit`]; the funds stay reserved in
+//! the consumer's account under [`RESERVE_ID`], apart from what other pallets reserve. The
+//! provider charges for the calls it served with [`Pallet::charge`], which moves funds out of the
+//! escrow into its free balance. Whatever the provider has not charged can be taken back by the
+//! consumer with [`Pallet::refund`] once `Config::RefundDelay` blocks have passed since the last
+//! deposit, so a provider always has that long to charge for calls served against a deposit.
+//!
+//! Consumers on other parachains use the same calls through an XCM `Transact`, dispatched as the
+//! local account their location converts to.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod weights;
+
+use codec::{Decode, Encode};
+use frame_support::traits::{BalanceStatus, Currency, NamedReservableCurrency};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{Saturating, Zero},
+	RuntimeDebug,
+};
+
+pub use pallet::*;
+pub use weights::WeightInfo;
+
+type BalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// The identifier escrowed funds are reserved under.
+pub const RESERVE_ID: [u8; 8] = *b"svcescrw";
+
+/// Funds a consumer holds in escrow for a provider.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct Escrow<Balance, BlockNumber> {
+	/// The amount still reserved for the provider.
+	pub amount: Balance,
+	/// The first block in which the consumer may take the amount back.
+	pub refundable_at: BlockNumber,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The currency services are paid in.
+		type Currency: NamedReservableCurrency<Self::AccountId, ReserveIdentifier = [u8; 8]>;
+
+		/// The smallest amount that may be deposited at once.
+		#[pallet::constant]
+		type MinDeposit: Get<BalanceOf<Self>>;
+
+		/// How many blocks after a deposit the provider is guaranteed to be able to charge it.
+		#[pallet::constant]
+		type RefundDelay: Get<Self::BlockNumber>;
+
+		/// Weight information for the calls of this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Funds were put in escrow. \[ consumer, provider, amount \]
+		Deposited(T::AccountId, T::AccountId, BalanceOf<T>),
+		/// A provider was paid from an escrow. \[ consumer, provider, amount \]
+		Charged(T::AccountId, T::AccountId, BalanceOf<T>),
+		/// The rest of an escrow went back to the consumer. \[ consumer, provider, amount \]
+		Refunded(T::AccountId, T::AccountId, BalanceOf<T>),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The deposit is below `Config::MinDeposit`.
+		DepositTooLow,
+		/// The consumer holds no escrow for the provider.
+		NoEscrow,
+		/// The escrow holds less than the amount charged.
+		InsufficientEscrow,
+		/// The provider may still charge the escrow.
+		NotRefundableYet,
+	}
+
+	/// Escrows by consumer and provider.
+	#[pallet::storage]
+	#[pallet::getter(fn escrow)]
+	pub type Escrows<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		T::AccountId,
+		Escrow<BalanceOf<T>, T::BlockNumber>,
+		OptionQuery,
+	>;
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Reserve `amount` of the caller's funds for `provider`, adding to any escrow already
+		/// held for it. The whole escrow becomes refundable `Config::RefundDelay` blocks from now.
+		#[pallet::weight(T::WeightInfo::deposit())]
+		pub fn deposit(
+			origin: OriginFor<T>,
+			provider: T::AccountId,
+			#[pallet::compact] amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let consumer = ensure_signed(origin)?;
+			ensure!(amount >= T::MinDeposit::get(), Error::<T>::DepositTooLow);
+			T::Currency::reserve_named(&RESERVE_ID, &consumer, amount)?;
+
+			let refundable_at =
+				frame_system::Pallet::<T>::block_number().saturating_add(T::RefundDelay::get());
+			Escrows::<T>::mutate(&consumer, &provider, |escrow| {
+				let amount = escrow.as_ref().map_or(amount, |e| e.amount.saturating_add(amount));
+				*escrow = Some(Escrow { amount, refundable_at });
+			});
+			Self::deposit_event(Event::Deposited(consumer, provider, amount));
+			Ok(())
+		}
+
+		/// Pay the caller `amount` out of the escrow `consumer` holds for it.
+		#[pallet::weight(T::WeightInfo::charge())]
+		pub fn charge(
+			origin: OriginFor<T>,
+			consumer: T::AccountId,
+			#[pallet::compact] amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let provider = ensure_signed(origin)?;
+			let paid = Escrows::<T>::try_mutate_exists(&consumer, &provider, |maybe_escrow| {
+				let escrow = maybe_escrow.as_mut().ok_or(Error::<T>::NoEscrow)?;
+				ensure!(amount <= escrow.amount, Error::<T>::InsufficientEscrow);
+				let missing = T::Currency::repatriate_reserved_named(
+					&RESERVE_ID,
+					&consumer,
+					&provider,
+					amount,
+					BalanceStatus::Free,
+				)?;
+				let paid = amount.saturating_sub(missing);
+				escrow.amount = escrow.amount.saturating_sub(paid);
+				if escrow.amount.is_zero() {
+					*maybe_escrow = None;
+				}
+				Ok::<_, DispatchError>(paid)
+			})?;
+			Self::deposit_event(Event::Charged(consumer, provider, paid));
+			Ok(())
+		}
+
+		/// Take back what is left of the escrow the caller holds for `provider`.
+		#[pallet::weight(T::WeightInfo::refund())]
+		pub fn refund(origin: OriginFor<T>, provider: T::AccountId) -> DispatchResult {
+			let consumer = ensure_signed(origin)?;
+			let escrow = Escrows::<T>::get(&consumer, &provider).ok_or(Error::<T>::NoEscrow)?;
+			ensure!(
+				frame_system::Pallet::<T>::block_number() >= escrow.refundable_at,
+				Error::<T>::NotRefundableYet
+			);
+			Escrows::<T>::remove(&consumer, &provider);
+			T::Currency::unreserve_named(&RESERVE_ID, &consumer, escrow.amount);
+			Self::deposit_event(Event::Refunded(consumer, provider, escrow.amount));
+			Ok(())
+		}
+	}
+}
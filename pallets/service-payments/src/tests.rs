@@ -0,0 +1,118 @@
+use crate::{mock::*, Error, Escrow, Event as PaymentEvent, RESERVE_ID};
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{Currency, NamedReservableCurrency, ReservableCurrency},
+};
+
+fn last_event() -> Event {
+	System::events().pop().expect("an event was deposited").event
+}
+
+#[test]
+fn deposit_reserves_funds_for_provider() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ServicePayments::deposit(Origin::signed(CONSUMER), PROVIDER, 100));
+		assert_eq!(Balances::free_balance(CONSUMER), 900);
+		assert_eq!(Balances::reserved_balance(CONSUMER), 100);
+		assert_eq!(
+			ServicePayments::escrow(CONSUMER, PROVIDER),
+			Some(Escrow { amount: 100, refundable_at: 6 }),
+		);
+		assert_eq!(last_event(), Event::ServicePayments(PaymentEvent::Deposited(CONSUMER, PROVIDER, 100)));
+
+		// Topping up adds to the escrow and pushes the refund back.
+		System::set_block_number(3);
+		assert_ok!(ServicePayments::deposit(Origin::signed(CONSUMER), PROVIDER, 50));
+		assert_eq!(
+			ServicePayments::escrow(CONSUMER, PROVIDER),
+			Some(Escrow { amount: 150, refundable_at: 8 }),
+		);
+	});
+}
+
+#[test]
+fn deposit_checks_minimum_and_balance() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			ServicePayments::deposit(Origin::signed(CONSUMER), PROVIDER, 9),
+			Error::<Test>::DepositTooLow,
+		);
+		assert_noop!(
+			ServicePayments::deposit(Origin::signed(CONSUMER), PROVIDER, 1_001),
+			pallet_balances::Error::<Test>::InsufficientBalance,
+		);
+	});
+}
+
+#[test]
+fn provider_charges_escrow() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ServicePayments::deposit(Origin::signed(CONSUMER), PROVIDER, 100));
+
+		assert_ok!(ServicePayments::charge(Origin::signed(PROVIDER), CONSUMER, 30));
+		assert_eq!(Balances::free_balance(PROVIDER), 130);
+		assert_eq!(Balances::reserved_balance(CONSUMER), 70);
+		assert_eq!(last_event(), Event::ServicePayments(PaymentEvent::Charged(CONSUMER, PROVIDER, 30)));
+
+		assert_noop!(
+			ServicePayments::charge(Origin::signed(PROVIDER), CONSUMER, 71),
+			Error::<Test>::InsufficientEscrow,
+		);
+		assert_ok!(ServicePayments::charge(Origin::signed(PROVIDER), CONSUMER, 70));
+		assert_eq!(ServicePayments::escrow(CONSUMER, PROVIDER), None);
+		assert_eq!(Balances::free_balance(PROVIDER), 200);
+		assert_eq!(Balances::total_balance(&CONSUMER), 900);
+	});
+}
+
+#[test]
+fn only_the_provider_can_charge() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ServicePayments::deposit(Origin::signed(CONSUMER), PROVIDER, 100));
+		assert_noop!(
+			ServicePayments::charge(Origin::signed(3), CONSUMER, 10),
+			Error::<Test>::NoEscrow,
+		);
+	});
+}
+
+#[test]
+fn escrow_is_refundable_after_delay() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ServicePayments::deposit(Origin::signed(CONSUMER), PROVIDER, 100));
+		assert_ok!(ServicePayments::charge(Origin::signed(PROVIDER), CONSUMER, 40));
+
+		System::set_block_number(5);
+		assert_noop!(
+			ServicePayments::refund(Origin::signed(CONSUMER), PROVIDER),
+			Error::<Test>::NotRefundableYet,
+		);
+
+		System::set_block_number(6);
+		assert_ok!(ServicePayments::refund(Origin::signed(CONSUMER), PROVIDER));
+		assert_eq!(last_event(), Event::ServicePayments(PaymentEvent::Refunded(CONSUMER, PROVIDER, 60)));
+		assert_eq!(Balances::free_balance(CONSUMER), 960);
+		assert_eq!(Balances::reserved_balance(CONSUMER), 0);
+		assert_noop!(
+			ServicePayments::refund(Origin::signed(CONSUMER), PROVIDER),
+			Error::<Test>::NoEscrow,
+		);
+	});
+}
+
+#[test]
+fn escrow_is_kept_apart_from_other_reserves() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Balances::reserve(&CONSUMER, 20));
+		assert_ok!(ServicePayments::deposit(Origin::signed(CONSUMER), PROVIDER, 100));
+		assert_eq!(Balances::reserved_balance_named(&RESERVE_ID, &CONSUMER), 100);
+		assert_eq!(Balances::reserved_balance(CONSUMER), 120);
+
+		assert_ok!(ServicePayments::charge(Origin::signed(PROVIDER), CONSUMER, 30));
+		System::set_block_number(6);
+		assert_ok!(ServicePayments::refund(Origin::signed(CONSUMER), PROVIDER));
+		assert_eq!(Balances::reserved_balance_named(&RESERVE_ID, &CONSUMER), 0);
+		assert_eq!(Balances::reserved_balance(CONSUMER), 20);
+		assert_eq!(Balances::free_balance(CONSUMER), 950);
+	});
+}
@@ -0,0 +1,34 @@
+//! Weights for pallet_service_payments.
+//!
+//! These are estimates from the storage each call touches, until the pallet is benchmarked with
+//! `scripts/benchmark.sh`.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::weights::{constants::RocksDbWeight, Weight};
+
+/// Weight functions needed for pallet_service_payments.
+pub trait WeightInfo {
+	fn deposit() -> Weight;
+	fn charge() -> Weight;
+	fn refund() -> Weight;
+}
+
+impl WeightInfo for () {
+	fn deposit() -> Weight {
+		(40_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn charge() -> Weight {
+		(50_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
+	fn refund() -> Weight {
+		(40_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+}
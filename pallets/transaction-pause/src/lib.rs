@@ -0,0 +1,164 @@
+//! Emergency pausing of pallets and calls.
+//!
+//! During an incident, `Config::PauseOrigin` can pause all calls of a pallet, or a single call, by
+//! the names they have in the runtime's `construct_runtime!`. [`PausedCallFilter`] is meant to be
+//! the runtime's `frame_system::Config::BaseCallFilter`, and refuses paused calls from every origin
+//! but root.
+//!
+//! `frame_system`, this pallet and the pallets in `Config::UnpausablePallets` cannot be paused, so
+//! that blocks can still be built and paused calls can always be unpaused.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+use frame_support::traits::{CallMetadata, Contains, GetCallMetadata, PalletInfoAccess};
+use sp_std::{marker::PhantomData, prelude::*};
+
+pub use pallet::*;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type Event: From<Event> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The runtime's call type, whose pallet and call names can be paused.
+		type Call: GetCallMetadata;
+
+		/// Origin allowed to pause and unpause calls.
+		type PauseOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Names of pallets which cannot be paused, besides `frame_system` and this pallet.
+		type UnpausablePallets: Contains<Vec<u8>>;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event {
+		/// Calls were paused: all calls of the pallet if the call name is `None`.
+		/// \[ pallet_name, call_name \]
+		CallsPaused(Vec<u8>, Option<Vec<u8>>),
+		/// Calls were unpaused. \[ pallet_name, call_name \]
+		CallsUnpaused(Vec<u8>, Option<Vec<u8>>),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The runtime has no such pallet or call.
+		UnknownCall,
+		/// The pallet cannot be paused.
+		Unpausable,
+		/// The calls are paused already.
+		AlreadyPaused,
+		/// The calls are not paused.
+		NotPaused,
+	}
+
+	/// The paused calls, by pallet name and call name, or `None` for all calls of the pallet.
+	#[pallet::storage]
+	#[pallet::getter(fn paused)]
+	pub(super) type PausedCalls<T: Config> =
+		StorageMap<_, Blake2_128Concat, (Vec<u8>, Option<Vec<u8>>), (), OptionQuery>;
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Pause `call_name` of `pallet_name`, or all its calls if `call_name` is `None`.
+		///
+		/// The dispatch origin must be `Config::PauseOrigin`.
+		#[pallet::weight(10_000_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn pause(
+			origin: OriginFor<T>,
+			pallet_name: Vec<u8>,
+			call_name: Option<Vec<u8>>,
+		) -> DispatchResult {
+			T::PauseOrigin::ensure_origin(origin)?;
+			Self::ensure_known(&pallet_name, call_name.as_deref())?;
+			ensure!(Self::is_pausable(&pallet_name), Error::<T>::Unpausable);
+
+			let key = (pallet_name, call_name);
+			ensure!(!PausedCalls::<T>::contains_key(&key), Error::<T>::AlreadyPaused);
+			PausedCalls::<T>::insert(&key, ());
+			Self::deposit_event(Event::CallsPaused(key.0, key.1));
+			Ok(())
+		}
+
+		/// Unpause calls paused with [`Pallet::pause`] with the same arguments.
+		///
+		/// Unpausing a whole pallet leaves the calls of it that were paused one by one paused.
+		///
+		/// The dispatch origin must be `Config::PauseOrigin`.
+		#[pallet::weight(10_000_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn unpause(
+			origin: OriginFor<T>,
+			pallet_name: Vec<u8>,
+			call_name: Option<Vec<u8>>,
+		) -> DispatchResult {
+			T::PauseOrigin::ensure_origin(origin)?;
+			let key = (pallet_name, call_name);
+			ensure!(PausedCalls::<T>::take(&key).is_some(), Error::<T>::NotPaused);
+			Self::deposit_event(Event::CallsUnpaused(key.0, key.1));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Whether `call_name` of `pallet_name` is paused, by itself or with its whole pallet.
+	pub fn is_paused(pallet_name: &[u8], call_name: &[u8]) -> bool {
+		// Unpausable pallets may still have been paused before they were made unpausable.
+		if !Self::is_pausable(pallet_name) {
+			return false
+		}
+		let pallet_name = pallet_name.to_vec();
+		PausedCalls::<T>::contains_key((pallet_name.clone(), None::<Vec<u8>>)) ||
+			PausedCalls::<T>::contains_key((pallet_name, Some(call_name.to_vec())))
+	}
+
+	/// Whether the calls of `pallet_name` can be paused.
+	fn is_pausable(pallet_name: &[u8]) -> bool {
+		pallet_name != <frame_system::Pallet<T> as PalletInfoAccess>::name().as_bytes() &&
+			pallet_name != <Self as PalletInfoAccess>::name().as_bytes() &&
+			!T::UnpausablePallets::contains(&pallet_name.to_vec())
+	}
+
+	/// Ensure the runtime has a pallet `pallet_name`, with a call `call_name` if given.
+	fn ensure_known(pallet_name: &[u8], call_name: Option<&[u8]>) -> Result<(), Error<T>> {
+		let pallet = <T as Config>::Call::get_module_names()
+			.iter()
+			.find(|name| name.as_bytes() == pallet_name)
+			.ok_or(Error::<T>::UnknownCall)?;
+		if let Some(call_name) = call_name {
+			frame_support::ensure!(
+				<T as Config>::Call::get_call_names(pallet)
+					.iter()
+					.any(|name| name.as_bytes() == call_name),
+				Error::<T>::UnknownCall
+			);
+		}
+		Ok(())
+	}
+}
+
+/// Call filter refusing the calls paused in pallet `T`.
+pub struct PausedCallFilter<T>(PhantomData<T>);
+impl<T: Config> Contains<<T as Config>::Call> for PausedCallFilter<T> {
+	fn contains(call: &<T as Config>::Call) -> bool {
+		let CallMetadata { pallet_name, function_name } = call.get_call_metadata();
+		!Pallet::<T>::is_paused(pallet_name.as_bytes(), function_name.as_bytes())
+	}
+}
@@ -0,0 +1,113 @@
+use crate as pallet_transaction_pause;
+use frame_support::{ord_parameter_types, parameter_types, traits::Contains};
+use frame_system::EnsureSignedBy;
+use sp_core::H256;
+use std::cell::RefCell;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		TransactionPause: pallet_transaction_pause::{Pallet, Call, Storage, Event},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+}
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = pallet_transaction_pause::PausedCallFilter<Test>;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type OnSetCode = ();
+	type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+	pub const MaxReserves: u32 = 50;
+}
+
+impl pallet_balances::Config for Test {
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+	type MaxLocks = ();
+	type MaxReserves = MaxReserves;
+	type ReserveIdentifier = [u8; 8];
+}
+
+/// The account allowed to pause calls.
+pub const ADMIN: u64 = 42;
+
+ord_parameter_types! {
+	pub const Admin: u64 = ADMIN;
+}
+
+thread_local! {
+	pub static UNPAUSABLE: RefCell<Vec<Vec<u8>>> = RefCell::new(vec![]);
+}
+
+/// Make the pallet `name` unpausable.
+pub fn make_unpausable(name: &[u8]) {
+	UNPAUSABLE.with(|u| u.borrow_mut().push(name.to_vec()));
+}
+
+pub struct UnpausablePallets;
+impl Contains<Vec<u8>> for UnpausablePallets {
+	fn contains(name: &Vec<u8>) -> bool {
+		UNPAUSABLE.with(|u| u.borrow().contains(name))
+	}
+}
+
+impl pallet_transaction_pause::Config for Test {
+	type Event = Event;
+	type Call = Call;
+	type PauseOrigin = EnsureSignedBy<Admin, u64>;
+	type UnpausablePallets = UnpausablePallets;
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> { balances: vec![(1, 100), (2, 100), (ADMIN, 100)] }
+		.assimilate_storage(&mut t)
+		.unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -0,0 +1,137 @@
+use crate::{mock::*, Error, Event as PauseEvent, PausedCallFilter};
+use frame_support::{assert_noop, assert_ok, traits::Contains};
+use sp_runtime::{
+	traits::{BadOrigin, Dispatchable},
+	DispatchError,
+};
+
+fn transfer() -> Call {
+	Call::Balances(pallet_balances::Call::transfer { dest: 2, value: 10 })
+}
+
+fn transfer_keep_alive() -> Call {
+	Call::Balances(pallet_balances::Call::transfer_keep_alive { dest: 2, value: 10 })
+}
+
+fn remark() -> Call {
+	Call::System(frame_system::Call::remark { remark: vec![] })
+}
+
+fn filtered() -> DispatchError {
+	frame_system::Error::<Test>::CallFiltered.into()
+}
+
+fn last_event() -> Event {
+	System::events().pop().expect("an event was deposited").event
+}
+
+fn pause(pallet: &[u8], call: Option<&[u8]>) -> sp_runtime::DispatchResult {
+	TransactionPause::pause(Origin::signed(ADMIN), pallet.to_vec(), call.map(|c| c.to_vec()))
+}
+
+fn unpause(pallet: &[u8], call: Option<&[u8]>) -> sp_runtime::DispatchResult {
+	TransactionPause::unpause(Origin::signed(ADMIN), pallet.to_vec(), call.map(|c| c.to_vec()))
+}
+
+#[test]
+fn paused_calls_are_filtered() {
+	new_test_ext().execute_with(|| {
+		assert!(PausedCallFilter::<Test>::contains(&transfer()));
+
+		assert_ok!(pause(b"Balances", Some(b"transfer")));
+		assert_eq!(
+			last_event(),
+			Event::TransactionPause(PauseEvent::CallsPaused(
+				b"Balances".to_vec(),
+				Some(b"transfer".to_vec())
+			))
+		);
+		let key = (b"Balances".to_vec(), Some(b"transfer".to_vec()));
+		assert_eq!(TransactionPause::paused(key), Some(()));
+		assert!(!PausedCallFilter::<Test>::contains(&transfer()));
+		assert!(PausedCallFilter::<Test>::contains(&transfer_keep_alive()));
+
+		assert_eq!(transfer().dispatch(Origin::signed(1)).map_err(|e| e.error), Err(filtered()));
+		assert_ok!(transfer_keep_alive().dispatch(Origin::signed(1)));
+		assert_eq!(Balances::free_balance(2), 110);
+
+		assert_ok!(unpause(b"Balances", Some(b"transfer")));
+		assert_eq!(
+			last_event(),
+			Event::TransactionPause(PauseEvent::CallsUnpaused(
+				b"Balances".to_vec(),
+				Some(b"transfer".to_vec())
+			))
+		);
+		assert_ok!(transfer().dispatch(Origin::signed(1)));
+		assert_eq!(Balances::free_balance(2), 120);
+	});
+}
+
+#[test]
+fn whole_pallets_can_be_paused() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(pause(b"Balances", Some(b"transfer")));
+		assert_ok!(pause(b"Balances", None));
+		assert!(!PausedCallFilter::<Test>::contains(&transfer()));
+		assert!(!PausedCallFilter::<Test>::contains(&transfer_keep_alive()));
+		assert_eq!(
+			transfer_keep_alive().dispatch(Origin::signed(1)).map_err(|e| e.error),
+			Err(filtered())
+		);
+
+		// The call paused by itself stays paused.
+		assert_ok!(unpause(b"Balances", None));
+		assert!(!PausedCallFilter::<Test>::contains(&transfer()));
+		assert!(PausedCallFilter::<Test>::contains(&transfer_keep_alive()));
+	});
+}
+
+#[test]
+fn root_is_not_filtered() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(pause(b"Balances", None));
+		let force_transfer = Call::Balances(pallet_balances::Call::force_transfer { source: 1, dest: 2, value: 10 });
+		assert_ok!(force_transfer.dispatch(Origin::root()));
+		assert_eq!(Balances::free_balance(2), 110);
+	});
+}
+
+#[test]
+fn pause_checks_origin_and_names() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			TransactionPause::pause(Origin::signed(1), b"Balances".to_vec(), None),
+			BadOrigin
+		);
+		assert_noop!(
+			TransactionPause::unpause(Origin::signed(1), b"Balances".to_vec(), None),
+			BadOrigin
+		);
+		assert_noop!(pause(b"Nope", None), Error::<Test>::UnknownCall);
+		assert_noop!(pause(b"Balances", Some(b"nope")), Error::<Test>::UnknownCall);
+		// Call names are those of the pallet named.
+		assert_noop!(pause(b"Balances", Some(b"remark")), Error::<Test>::UnknownCall);
+
+		assert_ok!(pause(b"Balances", None));
+		assert_noop!(pause(b"Balances", None), Error::<Test>::AlreadyPaused);
+		assert_noop!(unpause(b"Balances", Some(b"transfer")), Error::<Test>::NotPaused);
+	});
+}
+
+#[test]
+fn system_and_governance_cannot_be_paused() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(pause(b"System", None), Error::<Test>::Unpausable);
+		assert_noop!(pause(b"System", Some(b"remark")), Error::<Test>::Unpausable);
+		assert_noop!(pause(b"TransactionPause", Some(b"unpause")), Error::<Test>::Unpausable);
+		assert!(PausedCallFilter::<Test>::contains(&remark()));
+
+		// A pallet made unpausable is let through even if it was paused before.
+		assert_ok!(pause(b"Balances", None));
+		make_unpausable(b"Balances");
+		assert!(PausedCallFilter::<Test>::contains(&transfer()));
+		assert_noop!(pause(b"Balances", Some(b"transfer")), Error::<Test>::Unpausable);
+		assert_ok!(unpause(b"Balances", None));
+	});
+}
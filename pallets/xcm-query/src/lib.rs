@@ -0,0 +1,421 @@
+//! Query tracking for outbound XCM.
+//!
+//! Pallets that send XCM expecting an answer (e.g. the outcome of a cross-chain API payment)
+//! register a query here first, either through [`Pallet::report_outcome`] or by embedding the
+//! `QueryId` from [`Pallet::new_query`] in their message themselves. When the matching
+//! `QueryResponse` comes back it is routed here by the XCM executor's `ResponseHandler`; the
+//! response is either stored for the caller to pick up with [`Pallet::take_response`], or handed
+//! to a callback call registered with [`Pallet::new_notify_query`]. Responses to queries that
+//! were not registered here are passed on to `Config::FallbackResponseHandler`.
+//!
+//! Pending queries whose timeout has passed, and responses not taken within
+//! `Config::ResponseRetention` blocks of arriving, are pruned in `on_idle`, a bounded number per
+//! block, resuming from where the previous block stopped.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use frame_support::{
+	dispatch::{Dispatchable, GetDispatchInfo, PostDispatchInfo},
+	traits::{Contains, EnsureOrigin, Get, OriginTrait},
+	weights::Weight,
+};
+use sp_runtime::{traits::Saturating, RuntimeDebug};
+use sp_std::{
+	convert::{TryFrom, TryInto},
+	marker::PhantomData,
+	prelude::*,
+};
+use xcm::{latest::prelude::*, VersionedMultiLocation, VersionedResponse};
+use xcm_executor::traits::{InvertLocation, OnResponse};
+
+pub use pallet::*;
+
+/// Identifier of a query, carried in the outbound message and echoed back in `QueryResponse`.
+pub type QueryId = u64;
+
+/// Queries registered here are numbered from this value upwards, keeping them apart from the
+/// ones `pallet_xcm` numbers from zero for version discovery. Responses to ids below it are
+/// always passed on to `Config::FallbackResponseHandler`.
+pub const FIRST_QUERY_ID: QueryId = 1 << 63;
+
+/// The state of a registered query.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub enum QueryStatus<BlockNumber> {
+	/// The query was sent but no response has been accepted yet.
+	Pending {
+		/// The only location a response will be accepted from.
+		responder: VersionedMultiLocation,
+		/// Pallet and call index of the callback to dispatch once the response arrives.
+		maybe_notify: Option<(u8, u8)>,
+		/// Responses arriving after this block are dropped.
+		timeout: BlockNumber,
+	},
+	/// A response was received and is waiting to be taken by the querier, until
+	/// `Config::ResponseRetention` blocks after `at`.
+	Ready { response: VersionedResponse, at: BlockNumber },
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The outer origin type, which must be able to represent a response origin.
+		type Origin: From<Origin> + From<<Self as frame_system::Config>::Origin>;
+
+		/// The outer call type, used to dispatch response notifications.
+		type Call: Parameter
+			+ GetDispatchInfo
+			+ Dispatchable<Origin = <Self as Config>::Origin, PostInfo = PostDispatchInfo>;
+
+		/// Means of inverting a location, used to tell a responder where to send its answer.
+		type LocationInverter: InvertLocation;
+
+		/// Handler for responses to queries which were not registered with this pallet.
+		type FallbackResponseHandler: OnResponse;
+
+		/// The maximum number of queries looked at for expiry in a single block.
+		#[pallet::constant]
+		type MaxPrunedPerBlock: Get<u32>;
+
+		/// The number of blocks a stored response is kept for the querier to take.
+		#[pallet::constant]
+		type ResponseRetention: Get<Self::BlockNumber>;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A response was received and stored for the querier. \[ query_id, response \]
+		ResponseReady(QueryId, Response),
+		/// A response was received and its notification call dispatched successfully.
+		/// \[ query_id, pallet_index, call_index \]
+		Notified(QueryId, u8, u8),
+		/// A response was received but its notification call would have exceeded the weight
+		/// allowed for it. \[ query_id, pallet_index, call_index, required, max \]
+		NotifyOverweight(QueryId, u8, u8, Weight, Weight),
+		/// A response was received but its notification call returned an error.
+		/// \[ query_id, pallet_index, call_index \]
+		NotifyDispatchError(QueryId, u8, u8),
+		/// A response was received but the notification call could not be built from it.
+		/// \[ query_id, pallet_index, call_index \]
+		NotifyDecodeFailed(QueryId, u8, u8),
+		/// A response arrived from a location other than the registered responder.
+		/// \[ origin, query_id, expected_responder \]
+		InvalidResponder(MultiLocation, QueryId, Option<MultiLocation>),
+		/// A response arrived for a query that is not pending. \[ origin, query_id \]
+		UnexpectedResponse(MultiLocation, QueryId),
+		/// A response arrived after the query timed out and was dropped. \[ query_id \]
+		ResponseTimedOut(QueryId),
+		/// A pending query timed out without a response and was removed. \[ query_id \]
+		QueryExpired(QueryId),
+		/// A stored response was not taken in time and was removed. \[ query_id \]
+		ResponseExpired(QueryId),
+	}
+
+	/// Origin used to dispatch response notifications.
+	#[pallet::origin]
+	#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
+	pub enum Origin {
+		/// The call is a notification carrying a response from the given location.
+		Response(MultiLocation),
+	}
+
+	impl From<MultiLocation> for Origin {
+		fn from(location: MultiLocation) -> Origin {
+			Origin::Response(location)
+		}
+	}
+
+	/// The id to be assigned to the next query, if above `FIRST_QUERY_ID`.
+	#[pallet::storage]
+	pub(super) type QueryCount<T: Config> = StorageValue<_, QueryId, ValueQuery>;
+
+	/// Registered queries, pending or answered.
+	#[pallet::storage]
+	#[pallet::getter(fn query)]
+	pub(super) type Queries<T: Config> =
+		StorageMap<_, Blake2_128Concat, QueryId, QueryStatus<T::BlockNumber>, OptionQuery>;
+
+	/// The last query looked at for a timeout; pruning resumes after it in the next block.
+	#[pallet::storage]
+	pub(super) type PruneCursor<T: Config> = StorageValue<_, QueryId, OptionQuery>;
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_idle(now: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			Self::prune_expired(now, remaining_weight)
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Remove pending queries which timed out before `now` and responses whose retention ended
+	/// before `now`, looking at no more than `MaxPrunedPerBlock` queries and using no more than
+	/// `max_weight`.
+	fn prune_expired(now: T::BlockNumber, max_weight: Weight) -> Weight {
+		let db = T::DbWeight::get();
+		// Reading and writing the cursor.
+		let base = db.reads_writes(1, 1);
+		let per_query = db.reads_writes(1, 1);
+		let limit = max_weight.saturating_sub(base) / per_query.max(1);
+		let limit = limit.min(T::MaxPrunedPerBlock::get() as Weight) as usize;
+		if max_weight < base || limit == 0 {
+			return 0
+		}
+
+		let batch: Vec<_> = match PruneCursor::<T>::get() {
+			Some(last) => Queries::<T>::iter_from(Queries::<T>::hashed_key_for(last)),
+			None => Queries::<T>::iter(),
+		}
+		.take(limit)
+		.collect();
+
+		let retention = T::ResponseRetention::get();
+		for (query_id, status) in batch.iter() {
+			let event = match status {
+				QueryStatus::Pending { timeout, .. } if *timeout < now =>
+					Event::QueryExpired(*query_id),
+				QueryStatus::Ready { at, .. } if at.saturating_add(retention) < now =>
+					Event::ResponseExpired(*query_id),
+				_ => continue,
+			};
+			Queries::<T>::remove(query_id);
+			Self::deposit_event(event);
+		}
+		// A short batch means the end of the map was reached; start over next time.
+		match batch.last() {
+			Some((last, _)) if batch.len() == limit => PruneCursor::<T>::put(last),
+			_ => PruneCursor::<T>::kill(),
+		}
+		base.saturating_add(per_query.saturating_mul(batch.len() as Weight))
+	}
+
+	fn do_new_query(
+		responder: impl Into<MultiLocation>,
+		maybe_notify: Option<(u8, u8)>,
+		timeout: T::BlockNumber,
+	) -> QueryId {
+		QueryCount::<T>::mutate(|q| {
+			let r = (*q).max(FIRST_QUERY_ID);
+			*q = r.saturating_add(1);
+			Queries::<T>::insert(r, QueryStatus::Pending {
+				responder: responder.into().into(),
+				maybe_notify,
+				timeout,
+			});
+			r
+		})
+	}
+
+	/// Register a query whose response will be stored until taken with `take_response`.
+	///
+	/// `timeout` is the last block in which a response will be accepted.
+	pub fn new_query(responder: impl Into<MultiLocation>, timeout: T::BlockNumber) -> QueryId {
+		Self::do_new_query(responder, None, timeout)
+	}
+
+	/// Register a query whose response will be passed to `notify`.
+	///
+	/// `notify` must be a call which takes two trailing arguments, a `QueryId` and a `Response`;
+	/// they are filled in when the response arrives and the call is dispatched with
+	/// `Origin::Response(responder)`.
+	pub fn new_notify_query(
+		responder: impl Into<MultiLocation>,
+		notify: impl Into<<T as Config>::Call>,
+		timeout: T::BlockNumber,
+	) -> QueryId {
+		let notify = notify.into().using_encoded(|mut bytes| Decode::decode(&mut bytes))
+			.expect("decoding a pallet and call index from an encoded call is infallible; qed");
+		Self::do_new_query(responder, Some(notify), timeout)
+	}
+
+	/// Register a query for the outcome of executing `message` on `responder`, and append an
+	/// error report to `message` so that the outcome is sent back here.
+	///
+	/// The response is stored until taken with `take_response`; it is `Response::ExecutionResult`
+	/// carrying `None` on success or the index and error of the failing instruction.
+	pub fn report_outcome(
+		message: &mut Xcm<()>,
+		responder: impl Into<MultiLocation>,
+		timeout: T::BlockNumber,
+	) -> Result<QueryId, XcmError> {
+		let responder = responder.into();
+		let dest = T::LocationInverter::invert_location(&responder)
+			.map_err(|()| XcmError::MultiLocationNotInvertible)?;
+		let query_id = Self::new_query(responder, timeout);
+		let report_error = Xcm(vec![ReportError { dest, query_id, max_response_weight: 0 }]);
+		message.0.insert(0, SetAppendix(report_error));
+		Ok(query_id)
+	}
+
+	/// As `report_outcome`, but the outcome is passed to `notify` instead of being stored.
+	pub fn report_outcome_notify(
+		message: &mut Xcm<()>,
+		responder: impl Into<MultiLocation>,
+		notify: impl Into<<T as Config>::Call>,
+		timeout: T::BlockNumber,
+	) -> Result<QueryId, XcmError> {
+		let responder = responder.into();
+		let dest = T::LocationInverter::invert_location(&responder)
+			.map_err(|()| XcmError::MultiLocationNotInvertible)?;
+		let notify: <T as Config>::Call = notify.into();
+		let max_response_weight = notify.get_dispatch_info().weight;
+		let query_id = Self::new_notify_query(responder, notify, timeout);
+		let report_error = Xcm(vec![ReportError { dest, query_id, max_response_weight }]);
+		message.0.insert(0, SetAppendix(report_error));
+		Ok(query_id)
+	}
+
+	/// Build an instruction which reports the holding register of an execution on `responder`
+	/// back to this chain under `query_id`.
+	pub fn report_holding(
+		responder: &MultiLocation,
+		query_id: QueryId,
+		assets: MultiAssetFilter,
+	) -> Result<Instruction<()>, XcmError> {
+		let dest = T::LocationInverter::invert_location(responder)
+			.map_err(|()| XcmError::MultiLocationNotInvertible)?;
+		Ok(QueryHolding { query_id, dest, assets, max_response_weight: 0 })
+	}
+
+	/// Remove and return the response of `query_id` together with the block it arrived in, if
+	/// one is available.
+	pub fn take_response(query_id: QueryId) -> Option<(Response, T::BlockNumber)> {
+		match Queries::<T>::get(query_id) {
+			Some(QueryStatus::Ready { response, at }) => {
+				Queries::<T>::remove(query_id);
+				Response::try_from(response).ok().map(|response| (response, at))
+			}
+			_ => None,
+		}
+	}
+}
+
+impl<T: Config> OnResponse for Pallet<T> {
+	fn expecting_response(origin: &MultiLocation, query_id: QueryId) -> bool {
+		if query_id < FIRST_QUERY_ID {
+			return T::FallbackResponseHandler::expecting_response(origin, query_id)
+		}
+		match Queries::<T>::get(query_id) {
+			Some(QueryStatus::Pending { responder, .. }) =>
+				MultiLocation::try_from(responder).map_or(false, |r| &r == origin),
+			_ => T::FallbackResponseHandler::expecting_response(origin, query_id),
+		}
+	}
+
+	fn on_response(
+		origin: &MultiLocation,
+		query_id: QueryId,
+		response: Response,
+		max_weight: Weight,
+	) -> Weight {
+		let status = if query_id < FIRST_QUERY_ID { None } else { Queries::<T>::get(query_id) };
+		let (responder, maybe_notify, timeout) = match status {
+			Some(QueryStatus::Pending { responder, maybe_notify, timeout }) =>
+				(responder, maybe_notify, timeout),
+			_ if T::FallbackResponseHandler::expecting_response(origin, query_id) =>
+				return T::FallbackResponseHandler::on_response(origin, query_id, response, max_weight),
+			_ => {
+				Self::deposit_event(Event::UnexpectedResponse(origin.clone(), query_id));
+				return 0;
+			}
+		};
+		let responder = MultiLocation::try_from(responder).ok();
+		if responder.as_ref() != Some(origin) {
+			Self::deposit_event(Event::InvalidResponder(origin.clone(), query_id, responder));
+			return 0;
+		}
+
+		let now = frame_system::Pallet::<T>::block_number();
+		if now > timeout {
+			Queries::<T>::remove(query_id);
+			Self::deposit_event(Event::ResponseTimedOut(query_id));
+			return T::DbWeight::get().reads_writes(1, 1);
+		}
+
+		match maybe_notify {
+			Some((pallet_index, call_index)) => {
+				// Drop the query first so that a failing callback cannot be replayed.
+				Queries::<T>::remove(query_id);
+				let bare = (pallet_index, call_index, query_id, response);
+				let call = match bare.using_encoded(|mut bytes| <T as Config>::Call::decode(&mut bytes)) {
+					Ok(call) => call,
+					Err(_) => {
+						Self::deposit_event(Event::NotifyDecodeFailed(query_id, pallet_index, call_index));
+						return T::DbWeight::get().reads_writes(1, 1);
+					}
+				};
+				let weight = call.get_dispatch_info().weight;
+				if weight > max_weight {
+					Self::deposit_event(Event::NotifyOverweight(
+						query_id, pallet_index, call_index, weight, max_weight,
+					));
+					return T::DbWeight::get().reads_writes(1, 1);
+				}
+				let dispatch_origin = Origin::Response(origin.clone()).into();
+				let actual_weight = match call.dispatch(dispatch_origin) {
+					Ok(post_info) => {
+						Self::deposit_event(Event::Notified(query_id, pallet_index, call_index));
+						post_info.actual_weight
+					}
+					Err(error) => {
+						Self::deposit_event(Event::NotifyDispatchError(query_id, pallet_index, call_index));
+						error.post_info.actual_weight
+					}
+				};
+				actual_weight.unwrap_or(weight)
+					.saturating_add(T::DbWeight::get().reads_writes(1, 1))
+			}
+			None => {
+				Self::deposit_event(Event::ResponseReady(query_id, response.clone()));
+				Queries::<T>::insert(query_id, QueryStatus::Ready { response: response.into(), at: now });
+				T::DbWeight::get().reads_writes(1, 1)
+			}
+		}
+	}
+}
+
+/// Ensure that the origin `o` represents a response from a location matching `F`, returning
+/// that location on success.
+pub struct EnsureResponse<F>(PhantomData<F>);
+impl<O: OriginTrait + From<Origin>, F: Contains<MultiLocation>> EnsureOrigin<O> for EnsureResponse<F>
+	where O::PalletsOrigin: From<Origin> + TryInto<Origin, Error = O::PalletsOrigin>
+{
+	type Success = MultiLocation;
+
+	fn try_origin(outer: O) -> Result<Self::Success, O> {
+		outer.try_with_caller(|caller| caller.try_into()
+			.and_then(|Origin::Response(location)|
+				if F::contains(&location) {
+					Ok(location)
+				} else {
+					Err(Origin::Response(location).into())
+				}
+			))
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn successful_origin() -> O {
+		O::from(Origin::Response(Here.into()))
+	}
+}
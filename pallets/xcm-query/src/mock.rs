@@ -0,0 +1,119 @@
+use crate as pallet_xcm_query;
+use frame_support::{parameter_types, traits::Everything};
+use sp_core::H256;
+use sp_runtime::{testing::Header, traits::{BlakeTwo256, IdentityLookup}};
+use xcm::latest::prelude::*;
+use xcm_builder::LocationInverter;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+/// A pallet receiving response notifications, standing in for e.g. a payments pallet.
+#[frame_support::pallet]
+pub mod pallet_test_notifier {
+	use crate::QueryId;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+	use xcm::latest::{MultiLocation, Response};
+
+	#[pallet::pallet]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		type ResponseOrigin: EnsureOrigin<Self::Origin, Success = MultiLocation>;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		ResponseReceived(MultiLocation, QueryId, Response),
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		#[pallet::weight(1_000_000)]
+		pub fn notification_received(
+			origin: OriginFor<T>,
+			query_id: QueryId,
+			response: Response,
+		) -> DispatchResult {
+			let responder = T::ResponseOrigin::ensure_origin(origin)?;
+			Self::deposit_event(Event::ResponseReceived(responder, query_id, response));
+			Ok(())
+		}
+	}
+}
+
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		XcmQuery: pallet_xcm_query::{Pallet, Call, Storage, Event<T>, Origin},
+		TestNotifier: pallet_test_notifier::{Pallet, Call, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+	pub Ancestry: MultiLocation = Parachain(2019).into();
+	pub const MaxPrunedPerBlock: u32 = 2;
+	pub const ResponseRetention: u64 = 5;
+}
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type OnSetCode = ();
+	type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+impl pallet_xcm_query::Config for Test {
+	type Event = Event;
+	type Origin = Origin;
+	type Call = Call;
+	type LocationInverter = LocationInverter<Ancestry>;
+	type FallbackResponseHandler = ();
+	type MaxPrunedPerBlock = MaxPrunedPerBlock;
+	type ResponseRetention = ResponseRetention;
+}
+
+impl pallet_test_notifier::Config for Test {
+	type Event = Event;
+	type ResponseOrigin = pallet_xcm_query::EnsureResponse<Everything>;
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
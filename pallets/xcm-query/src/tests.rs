@@ -0,0 +1,208 @@
+use crate::{mock::*, Event as QueryEvent, PruneCursor, Queries, QueryStatus, FIRST_QUERY_ID};
+use frame_support::traits::Hooks;
+use xcm::latest::prelude::*;
+use xcm_executor::traits::OnResponse;
+
+const MAX_WEIGHT: u64 = 1_000_000_000;
+
+fn relay() -> MultiLocation {
+	MultiLocation::parent()
+}
+
+fn sibling() -> MultiLocation {
+	(Parent, Parachain(2000)).into()
+}
+
+fn response() -> Response {
+	Response::Assets(vec![(Parent, 10).into()].into())
+}
+
+fn last_event() -> Event {
+	System::events().pop().expect("an event was deposited").event
+}
+
+#[test]
+fn new_query_is_pending_and_ids_increase() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(XcmQuery::new_query(relay(), 10), FIRST_QUERY_ID);
+		assert_eq!(XcmQuery::new_query(sibling(), 10), FIRST_QUERY_ID + 1);
+		assert_eq!(
+			XcmQuery::query(FIRST_QUERY_ID + 1),
+			Some(QueryStatus::Pending { responder: sibling().into(), maybe_notify: None, timeout: 10 }),
+		);
+		assert!(XcmQuery::expecting_response(&relay(), FIRST_QUERY_ID));
+		assert!(!XcmQuery::expecting_response(&relay(), FIRST_QUERY_ID + 1));
+		assert!(!XcmQuery::expecting_response(&relay(), FIRST_QUERY_ID + 2));
+	});
+}
+
+#[test]
+fn response_is_stored_until_taken() {
+	new_test_ext().execute_with(|| {
+		let query_id = XcmQuery::new_query(relay(), 10);
+		System::set_block_number(5);
+
+		XcmQuery::on_response(&relay(), query_id, response(), MAX_WEIGHT);
+		assert_eq!(last_event(), Event::XcmQuery(QueryEvent::ResponseReady(query_id, response())));
+		assert!(!XcmQuery::expecting_response(&relay(), query_id));
+
+		assert_eq!(XcmQuery::take_response(query_id), Some((response(), 5)));
+		assert_eq!(XcmQuery::take_response(query_id), None);
+		assert_eq!(XcmQuery::query(query_id), None);
+	});
+}
+
+#[test]
+fn response_from_wrong_location_is_ignored() {
+	new_test_ext().execute_with(|| {
+		let query_id = XcmQuery::new_query(relay(), 10);
+
+		XcmQuery::on_response(&sibling(), query_id, response(), MAX_WEIGHT);
+		assert_eq!(
+			last_event(),
+			Event::XcmQuery(QueryEvent::InvalidResponder(sibling(), query_id, Some(relay()))),
+		);
+		assert!(XcmQuery::expecting_response(&relay(), query_id));
+		assert_eq!(XcmQuery::take_response(query_id), None);
+	});
+}
+
+#[test]
+fn unknown_query_is_reported() {
+	new_test_ext().execute_with(|| {
+		XcmQuery::on_response(&relay(), 7, response(), MAX_WEIGHT);
+		assert_eq!(last_event(), Event::XcmQuery(QueryEvent::UnexpectedResponse(relay(), 7)));
+	});
+}
+
+#[test]
+fn late_response_is_dropped() {
+	new_test_ext().execute_with(|| {
+		let query_id = XcmQuery::new_query(relay(), 3);
+		System::set_block_number(4);
+
+		XcmQuery::on_response(&relay(), query_id, response(), MAX_WEIGHT);
+		assert_eq!(last_event(), Event::XcmQuery(QueryEvent::ResponseTimedOut(query_id)));
+		assert_eq!(XcmQuery::query(query_id), None);
+	});
+}
+
+#[test]
+fn notify_query_dispatches_callback() {
+	new_test_ext().execute_with(|| {
+		let notify = pallet_test_notifier::Call::<Test>::notification_received(0, Response::Null);
+		let query_id = XcmQuery::new_notify_query(sibling(), notify, 10);
+
+		XcmQuery::on_response(&sibling(), query_id, response(), MAX_WEIGHT);
+
+		let events: Vec<_> = System::events().into_iter().map(|r| r.event).collect();
+		assert_eq!(events, vec![
+			Event::TestNotifier(pallet_test_notifier::Event::ResponseReceived(sibling(), query_id, response())),
+			Event::XcmQuery(QueryEvent::Notified(query_id, 2, 0)),
+		]);
+		assert_eq!(XcmQuery::query(query_id), None);
+	});
+}
+
+#[test]
+fn notify_query_respects_max_weight() {
+	new_test_ext().execute_with(|| {
+		let notify = pallet_test_notifier::Call::<Test>::notification_received(0, Response::Null);
+		let query_id = XcmQuery::new_notify_query(sibling(), notify, 10);
+
+		XcmQuery::on_response(&sibling(), query_id, response(), 1);
+		assert_eq!(
+			last_event(),
+			Event::XcmQuery(QueryEvent::NotifyOverweight(query_id, 2, 0, 1_000_000, 1)),
+		);
+		assert_eq!(XcmQuery::query(query_id), None);
+	});
+}
+
+#[test]
+fn report_outcome_appends_error_report() {
+	new_test_ext().execute_with(|| {
+		let mut message = Xcm(vec![ClearOrigin]);
+		let query_id = XcmQuery::report_outcome(&mut message, sibling(), 10).unwrap();
+
+		let dest: MultiLocation = (Parent, Parachain(2019)).into();
+		assert_eq!(message, Xcm(vec![
+			SetAppendix(Xcm(vec![ReportError { query_id, dest, max_response_weight: 0 }])),
+			ClearOrigin,
+		]));
+		assert!(XcmQuery::expecting_response(&sibling(), query_id));
+
+		let outcome = Response::ExecutionResult(Some((1, XcmError::Unimplemented)));
+		XcmQuery::on_response(&sibling(), query_id, outcome.clone(), MAX_WEIGHT);
+		assert_eq!(XcmQuery::take_response(query_id), Some((outcome, 1)));
+	});
+}
+
+#[test]
+fn ids_below_first_query_id_are_left_to_the_fallback() {
+	new_test_ext().execute_with(|| {
+		// Planted directly, as `new_query` never hands out such an id.
+		Queries::<Test>::insert(3, QueryStatus::Pending {
+			responder: relay().into(),
+			maybe_notify: None,
+			timeout: 10,
+		});
+		assert!(!XcmQuery::expecting_response(&relay(), 3));
+
+		XcmQuery::on_response(&relay(), 3, response(), MAX_WEIGHT);
+		assert_eq!(last_event(), Event::XcmQuery(QueryEvent::UnexpectedResponse(relay(), 3)));
+		assert!(matches!(XcmQuery::query(3), Some(QueryStatus::Pending { .. })));
+	});
+}
+
+#[test]
+fn expired_queries_are_pruned_on_idle() {
+	new_test_ext().execute_with(|| {
+		let expired = XcmQuery::new_query(relay(), 2);
+		let answered = XcmQuery::new_query(relay(), 2);
+		let live = XcmQuery::new_query(relay(), 10);
+		XcmQuery::on_response(&relay(), answered, response(), MAX_WEIGHT);
+		System::set_block_number(3);
+
+		// Two queries per block at most; the cursor carries over to the next block.
+		XcmQuery::on_idle(3, MAX_WEIGHT);
+		assert!(PruneCursor::<Test>::get().is_some());
+		XcmQuery::on_idle(3, MAX_WEIGHT);
+		assert_eq!(PruneCursor::<Test>::get(), None);
+
+		assert_eq!(XcmQuery::query(expired), None);
+		assert!(System::events().iter().any(|r| r.event == Event::XcmQuery(QueryEvent::QueryExpired(expired))));
+		assert_eq!(XcmQuery::take_response(answered), Some((response(), 1)));
+		assert!(XcmQuery::expecting_response(&relay(), live));
+	});
+}
+
+#[test]
+fn untaken_responses_are_pruned_after_retention() {
+	new_test_ext().execute_with(|| {
+		let query_id = XcmQuery::new_query(relay(), 10);
+		XcmQuery::on_response(&relay(), query_id, response(), MAX_WEIGHT);
+
+		// Kept for `ResponseRetention` blocks after arriving in block 1.
+		System::set_block_number(6);
+		XcmQuery::on_idle(6, MAX_WEIGHT);
+		assert!(matches!(XcmQuery::query(query_id), Some(QueryStatus::Ready { .. })));
+
+		System::set_block_number(7);
+		XcmQuery::on_idle(7, MAX_WEIGHT);
+		assert_eq!(XcmQuery::query(query_id), None);
+		assert_eq!(last_event(), Event::XcmQuery(QueryEvent::ResponseExpired(query_id)));
+		assert_eq!(XcmQuery::take_response(query_id), None);
+	});
+}
+
+#[test]
+fn pruning_is_bounded_by_remaining_weight() {
+	new_test_ext().execute_with(|| {
+		let query_id = XcmQuery::new_query(relay(), 2);
+		System::set_block_number(3);
+
+		assert_eq!(XcmQuery::on_idle(3, 0), 0);
+		assert!(XcmQuery::expecting_response(&relay(), query_id));
+	});
+}
@@ -0,0 +1,189 @@
+//! Per-origin rate limiting of inbound XCM.
+//!
+//! [`RateLimitedExecutor`] wraps the XCM executor of a message queue and counts every message from
+//! a limited origin that it executes. Once an origin has used up its budget for the current block,
+//! its further messages are left in the queue until a later block, so that a single sibling
+//! cannot flood the block with its messages. Every origin gets `Config::DefaultBudget` messages per
+//! block unless governance sets a different budget for it with [`Pallet::set_budget`].
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+use frame_support::{
+	traits::{Contains, Get},
+	weights::Weight,
+};
+use sp_std::{boxed::Box, convert::TryInto, marker::PhantomData};
+use xcm::{latest::prelude::*, VersionedMultiLocation};
+
+pub use pallet::*;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The number of messages an origin may have executed per block, unless overridden.
+		#[pallet::constant]
+		type DefaultBudget: Get<u32>;
+
+		/// Origin allowed to override the budget of an origin.
+		type BudgetOrigin: EnsureOrigin<Self::Origin>;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The budget of an origin was overridden, or reset to the default if `None`.
+		/// \[ origin, budget \]
+		BudgetSet(MultiLocation, Option<u32>),
+		/// An origin used up its budget and its further messages wait for a later block.
+		/// \[ origin, block \]
+		BudgetExhausted(MultiLocation, T::BlockNumber),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The location could not be converted to the latest XCM version.
+		BadVersion,
+	}
+
+	/// Budgets overriding `Config::DefaultBudget` for individual origins.
+	#[pallet::storage]
+	#[pallet::getter(fn budget_override)]
+	pub(super) type Budgets<T: Config> =
+		StorageMap<_, Blake2_128Concat, MultiLocation, u32, OptionQuery>;
+
+	/// The last block an origin had a message counted in, and the number of messages counted in
+	/// that block.
+	#[pallet::storage]
+	#[pallet::getter(fn usage)]
+	pub(super) type Usage<T: Config> =
+		StorageMap<_, Blake2_128Concat, MultiLocation, (T::BlockNumber, u32), OptionQuery>;
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Set the number of messages `location` may have executed per block, or reset it to
+		/// `Config::DefaultBudget` if `budget` is `None`.
+		///
+		/// The dispatch origin must be `Config::BudgetOrigin`.
+		#[pallet::weight(10_000_000 + T::DbWeight::get().writes(1))]
+		pub fn set_budget(
+			origin: OriginFor<T>,
+			location: Box<VersionedMultiLocation>,
+			budget: Option<u32>,
+		) -> DispatchResult {
+			T::BudgetOrigin::ensure_origin(origin)?;
+			let location: MultiLocation =
+				(*location).try_into().map_err(|()| Error::<T>::BadVersion)?;
+			match budget {
+				Some(budget) => Budgets::<T>::insert(&location, budget),
+				None => Budgets::<T>::remove(&location),
+			}
+			Self::deposit_event(Event::BudgetSet(location, budget));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// The number of messages `origin` may have executed per block.
+	pub fn budget(origin: &MultiLocation) -> u32 {
+		Budgets::<T>::get(origin).unwrap_or_else(T::DefaultBudget::get)
+	}
+
+	/// The number of messages from `origin` executed in the current block.
+	fn used(origin: &MultiLocation) -> u32 {
+		match Usage::<T>::get(origin) {
+			Some((at, used)) if at == frame_system::Pallet::<T>::block_number() => used,
+			_ => 0,
+		}
+	}
+
+	/// Whether `origin` may have another message executed in the current block.
+	pub fn has_budget(origin: &MultiLocation) -> bool {
+		Self::used(origin) < Self::budget(origin)
+	}
+
+	/// Count a message from `origin` executed in the current block against its budget.
+	pub fn note_executed(origin: &MultiLocation) {
+		let now = frame_system::Pallet::<T>::block_number();
+		let used = Self::used(origin).saturating_add(1);
+		Usage::<T>::insert(origin, (now, used));
+		if used == Self::budget(origin) {
+			Self::deposit_event(Event::BudgetExhausted(origin.clone(), now));
+		}
+	}
+
+	/// The weight of checking and counting a message against its budget.
+	pub fn accounting_weight() -> Weight {
+		// `Usage` and `Budgets` are read once for the check and once for the count; the second
+		// reads hit the overlay.
+		T::DbWeight::get().reads_writes(2, 1)
+	}
+}
+
+/// XCM executor counting the messages from origins matching `Limited` that `Inner` executes
+/// against their budget in pallet `T`. Meant to be the `XcmExecutor` of a message queue.
+///
+/// A message from an origin which used up its budget for the block is not executed, and reported
+/// as not fitting the weight left in the block. The XCMP queue then keeps it, together with any
+/// later messages of the same channel, and tries again in the next block. Messages from any other
+/// origin are passed straight on to `Inner`.
+pub struct RateLimitedExecutor<T, Inner, Limited>(PhantomData<(T, Inner, Limited)>);
+impl<T, Inner, Limited, Call> ExecuteXcm<Call> for RateLimitedExecutor<T, Inner, Limited>
+where
+	T: Config,
+	Inner: ExecuteXcm<Call>,
+	Limited: Contains<MultiLocation>,
+{
+	fn execute_xcm_in_credit(
+		origin: impl Into<MultiLocation>,
+		message: Xcm<Call>,
+		weight_limit: Weight,
+		weight_credit: Weight,
+	) -> Outcome {
+		let origin = origin.into();
+		if !Limited::contains(&origin) {
+			return Inner::execute_xcm_in_credit(origin, message, weight_limit, weight_credit)
+		}
+
+		let overhead = Pallet::<T>::accounting_weight();
+		if !Pallet::<T>::has_budget(&origin) {
+			// Claiming no more than the overhead keeps the queue from treating the message as
+			// overweight; it only needs to wait for the next block.
+			return Outcome::Error(XcmError::WeightLimitReached(overhead))
+		}
+		let inner_limit = weight_limit.saturating_sub(overhead);
+		match Inner::execute_xcm_in_credit(origin.clone(), message, inner_limit, weight_credit) {
+			Outcome::Complete(weight) => {
+				Pallet::<T>::note_executed(&origin);
+				Outcome::Complete(weight.saturating_add(overhead))
+			},
+			Outcome::Incomplete(weight, error) => {
+				Pallet::<T>::note_executed(&origin);
+				Outcome::Incomplete(weight.saturating_add(overhead), error)
+			},
+			// Not executed, so not counted.
+			Outcome::Error(XcmError::WeightLimitReached(required)) =>
+				Outcome::Error(XcmError::WeightLimitReached(required.saturating_add(overhead))),
+			Outcome::Error(error) => Outcome::Error(error),
+		}
+	}
+}
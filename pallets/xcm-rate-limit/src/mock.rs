@@ -0,0 +1,99 @@
+use crate as pallet_xcm_rate_limit;
+use frame_support::{
+	parameter_types,
+	traits::Everything,
+	weights::{constants::RocksDbWeight, Weight},
+};
+use frame_system::EnsureRoot;
+use sp_core::H256;
+use sp_runtime::{testing::Header, traits::{BlakeTwo256, IdentityLookup}};
+use xcm::latest::prelude::*;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		XcmRateLimit: pallet_xcm_rate_limit::{Pallet, Call, Storage, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+	pub const DefaultBudget: u32 = 2;
+}
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = RocksDbWeight;
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type OnSetCode = ();
+	type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+impl pallet_xcm_rate_limit::Config for Test {
+	type Event = Event;
+	type DefaultBudget = DefaultBudget;
+	type BudgetOrigin = EnsureRoot<u64>;
+}
+
+frame_support::match_type! {
+	pub type Siblings: impl frame_support::traits::Contains<MultiLocation> = {
+		MultiLocation { parents: 1, interior: X1(Parachain(_)) }
+	};
+}
+
+/// Stands in for the XCM executor: every instruction weighs 10, and a `Trap` makes the message
+/// fail part way.
+pub struct MockExecutor;
+impl ExecuteXcm<Call> for MockExecutor {
+	fn execute_xcm_in_credit(
+		_origin: impl Into<MultiLocation>,
+		message: Xcm<Call>,
+		weight_limit: Weight,
+		_weight_credit: Weight,
+	) -> Outcome {
+		let weight = 10 * message.0.len() as Weight;
+		if weight > weight_limit {
+			return Outcome::Error(XcmError::WeightLimitReached(weight))
+		}
+		match message.0.iter().find(|instruction| matches!(instruction, Trap(_))) {
+			Some(_) => Outcome::Incomplete(weight, XcmError::Trap(0)),
+			None => Outcome::Complete(weight),
+		}
+	}
+}
+
+pub type Executor = pallet_xcm_rate_limit::RateLimitedExecutor<Test, MockExecutor, Siblings>;
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
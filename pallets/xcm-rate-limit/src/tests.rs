@@ -0,0 +1,145 @@
+use crate::{mock::*, Error, Event as RateLimitEvent};
+use frame_support::{assert_noop, assert_ok, weights::Weight};
+use sp_runtime::traits::BadOrigin;
+use xcm::latest::prelude::*;
+
+fn sibling() -> MultiLocation {
+	(Parent, Parachain(2000)).into()
+}
+
+fn other_sibling() -> MultiLocation {
+	(Parent, Parachain(2001)).into()
+}
+
+const LIMIT: Weight = 1_000_000_000;
+
+fn execute(origin: &MultiLocation, message: Xcm<Call>) -> Outcome {
+	Executor::execute_xcm(origin.clone(), message, LIMIT)
+}
+
+fn allowed(origin: &MultiLocation) -> bool {
+	!matches!(execute(origin, Xcm(vec![ClearOrigin])), Outcome::Error(_))
+}
+
+fn events() -> Vec<Event> {
+	System::events().into_iter().map(|r| r.event).collect()
+}
+
+#[test]
+fn budget_is_per_origin_and_per_block() {
+	new_test_ext().execute_with(|| {
+		assert!(allowed(&sibling()));
+		assert!(allowed(&sibling()));
+		assert!(!allowed(&sibling()));
+		assert!(!allowed(&sibling()));
+		assert!(allowed(&other_sibling()));
+
+		assert_eq!(events(), vec![
+			Event::XcmRateLimit(RateLimitEvent::BudgetExhausted(sibling(), 1)),
+		]);
+
+		System::set_block_number(2);
+		assert!(allowed(&sibling()));
+		assert_eq!(XcmRateLimit::usage(sibling()), Some((2, 1)));
+	});
+}
+
+#[test]
+fn deferred_messages_claim_only_the_accounting_weight() {
+	new_test_ext().execute_with(|| {
+		assert!(allowed(&sibling()));
+		assert!(allowed(&sibling()));
+		// Within the weight left, so the queue keeps the message for the next block rather than
+		// setting it aside as overweight.
+		assert_eq!(
+			execute(&sibling(), Xcm(vec![ClearOrigin])),
+			Outcome::Error(XcmError::WeightLimitReached(XcmRateLimit::accounting_weight())),
+		);
+	});
+}
+
+#[test]
+fn executed_messages_are_charged_the_accounting_weight() {
+	new_test_ext().execute_with(|| {
+		let overhead = XcmRateLimit::accounting_weight();
+		assert!(overhead > 0);
+		assert_eq!(execute(&sibling(), Xcm(vec![ClearOrigin])), Outcome::Complete(10 + overhead));
+		assert_eq!(
+			execute(&sibling(), Xcm(vec![ClearOrigin, Trap(1)])),
+			Outcome::Incomplete(20 + overhead, XcmError::Trap(0)),
+		);
+		// Failed part way, but executed, so counted.
+		assert_eq!(XcmRateLimit::usage(sibling()), Some((1, 2)));
+	});
+}
+
+#[test]
+fn messages_not_executed_are_not_counted() {
+	new_test_ext().execute_with(|| {
+		let overhead = XcmRateLimit::accounting_weight();
+		// The inner executor gets what is left after the accounting, too little for the message.
+		assert_eq!(
+			Executor::execute_xcm(sibling(), Xcm(vec![ClearOrigin]), overhead + 5),
+			Outcome::Error(XcmError::WeightLimitReached(10 + overhead)),
+		);
+		assert_eq!(XcmRateLimit::usage(sibling()), None);
+		assert!(allowed(&sibling()));
+		assert!(allowed(&sibling()));
+	});
+}
+
+#[test]
+fn unlimited_origins_are_not_counted() {
+	new_test_ext().execute_with(|| {
+		for _ in 0..5 {
+			assert!(allowed(&MultiLocation::parent()));
+		}
+		assert_eq!(XcmRateLimit::usage(MultiLocation::parent()), None);
+	});
+}
+
+#[test]
+fn zero_budget_refuses_every_message() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(XcmRateLimit::set_budget(Origin::root(), Box::new(sibling().into()), Some(0)));
+		assert!(!allowed(&sibling()));
+	});
+}
+
+#[test]
+fn set_budget_overrides_default() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			XcmRateLimit::set_budget(Origin::signed(1), Box::new(sibling().into()), Some(3)),
+			BadOrigin,
+		);
+
+		assert_ok!(XcmRateLimit::set_budget(Origin::root(), Box::new(sibling().into()), Some(3)));
+		assert_eq!(XcmRateLimit::budget(&sibling()), 3);
+		assert_eq!(XcmRateLimit::budget(&other_sibling()), 2);
+		for _ in 0..3 {
+			assert!(allowed(&sibling()));
+		}
+		assert!(!allowed(&sibling()));
+
+		assert_ok!(XcmRateLimit::set_budget(Origin::root(), Box::new(sibling().into()), None));
+		assert_eq!(XcmRateLimit::budget(&sibling()), 2);
+		assert_eq!(XcmRateLimit::budget_override(sibling()), None);
+		assert!(events().contains(&Event::XcmRateLimit(RateLimitEvent::BudgetSet(sibling(), None))));
+	});
+}
+
+#[test]
+fn set_budget_rejects_unconvertible_location() {
+	new_test_ext().execute_with(|| {
+		// A v0 location with a `Parent` after a `Parachain` has no v1 equivalent.
+		let location = xcm::VersionedMultiLocation::V0(xcm::v0::MultiLocation::X2(
+			xcm::v0::Junction::Parachain(2000),
+			xcm::v0::Junction::Parent,
+		));
+		assert_noop!(
+			XcmRateLimit::set_budget(Origin::root(), Box::new(location), Some(1)),
+			Error::<Test>::BadVersion,
+		);
+	});
+}
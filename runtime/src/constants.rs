@@ -2,7 +2,7 @@ pub use currency::*;
 pub use address::*;
 
 pub mod currency {
-	use node_primitives::Balance;
+	use parachains_common::Balance;
 	pub const EXISTENTIAL_DEPOSIT: Balance = KPN;
 
 	pub const SYMBOL: &str = "KPN";
@@ -18,12 +18,17 @@ pub mod currency {
 	}
 }
 pub mod address {
-	pub const SS58_PREFIX: u8 = 42;
+	/// The SS58 prefix of kpron addresses, kpron's para id. It is unassigned in the ss58-registry.
+	pub const SS58_PREFIX: u16 = 2019;
+	/// The SS58 prefix of Kusama addresses.
+	pub const KUSAMA_SS58_PREFIX: u16 = 2;
+	/// The generic Substrate SS58 prefix, used by development tools.
+	pub const SUBSTRATE_SS58_PREFIX: u16 = 42;
 }
 
 /// Fee-related.
 pub mod fee {
-	use node_primitives::Balance;
+	use parachains_common::Balance;
 	pub use sp_runtime::Perbill;
 	use frame_support::weights::{
 		constants::ExtrinsicBaseWeight, WeightToFeeCoefficient, WeightToFeeCoefficients,
@@ -0,0 +1,159 @@
+//! Fee handling, the slot schedule and XCM building blocks specific to kpron.
+
+use codec::{Decode, Encode};
+use frame_support::{
+	ensure,
+	traits::{Contains, Currency, Get, Imbalance, OnUnbalanced},
+	weights::Weight,
+};
+use sp_consensus_aura::{Slot, AURA_ENGINE_ID};
+use sp_io::hashing::blake2_256;
+use sp_std::marker::PhantomData;
+use xcm::latest::prelude::*;
+use xcm_executor::traits::{Convert, ShouldExecute};
+
+type NegativeImbalance<R> = <pallet_balances::Pallet<R> as Currency<
+	<R as frame_system::Config>::AccountId,
+>>::NegativeImbalance;
+
+/// Deposits imbalances into the collator staking pot, from which they are paid out to collators
+/// and their delegators at the end of the session.
+pub struct ToStakingPot<R>(PhantomData<R>);
+impl<R> OnUnbalanced<NegativeImbalance<R>> for ToStakingPot<R>
+where
+	R: pallet_balances::Config + pallet_collator_staking::Config,
+	<R as frame_system::Config>::Event: From<pallet_balances::Event<R>>,
+{
+	fn on_nonzero_unbalanced(amount: NegativeImbalance<R>) {
+		let numeric_amount = amount.peek();
+		let staking_pot = pallet_collator_staking::Pallet::<R>::account_id();
+		pallet_balances::Pallet::<R>::resolve_creating(&staking_pot, amount);
+		frame_system::Pallet::<R>::deposit_event(pallet_balances::Event::Deposit {
+			who: staking_pot,
+			amount: numeric_amount,
+		});
+	}
+}
+
+/// Aura's slot schedule: the author of a slot is the session validator at the slot's index modulo
+/// the number of validators, in the order `pallet_session` hands them to Aura.
+pub struct AuraSlotSchedule<R>(PhantomData<R>);
+impl<R> pallet_collator_staking::SlotSchedule<R::AccountId> for AuraSlotSchedule<R>
+where
+	R: pallet_session::Config<ValidatorId = <R as frame_system::Config>::AccountId>,
+{
+	fn current_slot() -> u64 {
+		// Read from the digest, as Aura's own storage is only updated in its `on_initialize`.
+		frame_system::Pallet::<R>::digest()
+			.logs()
+			.iter()
+			.filter_map(|item| item.as_pre_runtime())
+			.find_map(|(id, mut data)| {
+				if id == AURA_ENGINE_ID {
+					Slot::decode(&mut data).ok()
+				} else {
+					None
+				}
+			})
+			.map_or(0, |slot| *slot)
+	}
+
+	fn expected_author(slot: u64) -> Option<R::AccountId> {
+		let validators = pallet_session::Pallet::<R>::validators();
+		if validators.is_empty() {
+			return None
+		}
+		validators.get((slot % validators.len() as u64) as usize).cloned()
+	}
+}
+
+/// Sends transaction fees and tips to the collator staking pot.
+pub struct DealWithFees<R>(PhantomData<R>);
+impl<R> OnUnbalanced<NegativeImbalance<R>> for DealWithFees<R>
+where
+	R: pallet_balances::Config + pallet_collator_staking::Config,
+	<R as frame_system::Config>::Event: From<pallet_balances::Event<R>>,
+{
+	fn on_unbalanceds<B>(mut fees_then_tips: impl Iterator<Item = NegativeImbalance<R>>) {
+		if let Some(mut fees) = fees_then_tips.next() {
+			if let Some(tips) = fees_then_tips.next() {
+				tips.merge_into(&mut fees);
+			}
+			<ToStakingPot<R> as OnUnbalanced<_>>::on_unbalanced(fees);
+		}
+	}
+}
+
+/// The author of the current block, who keeps the transaction fees paid in assets, as the staking
+/// pot only pays out the native currency.
+///
+/// Outside of a block, e.g. while the transaction pool validates a transaction, no author is known
+/// and the staking pot stands in.
+pub struct BlockAuthor<R>(PhantomData<R>);
+impl<R> Get<R::AccountId> for BlockAuthor<R>
+where
+	R: pallet_authorship::Config + pallet_collator_staking::Config,
+{
+	fn get() -> R::AccountId {
+		pallet_authorship::Pallet::<R>::author()
+			.unwrap_or_else(pallet_collator_staking::Pallet::<R>::account_id)
+	}
+}
+
+/// Converts an `AccountId32` on a sibling parachain into a local account derived from the sibling's
+/// id and the remote account.
+///
+/// This lets users of other parachains hold funds and dispatch calls here, e.g. to pay for Apron
+/// services, without their account clashing with a local one or with the sibling's sovereign
+/// account.
+pub struct SiblingAccountId32Derived<AccountId>(PhantomData<AccountId>);
+impl<AccountId: From<[u8; 32]> + Clone> Convert<MultiLocation, AccountId>
+	for SiblingAccountId32Derived<AccountId>
+{
+	fn convert(location: MultiLocation) -> Result<AccountId, MultiLocation> {
+		match location {
+			MultiLocation {
+				parents: 1,
+				interior: X2(Parachain(para_id), AccountId32 { id, .. }),
+			} => Ok((b"sibling-account", para_id, id).using_encoded(blake2_256).into()),
+			_ => Err(location),
+		}
+	}
+}
+
+/// Allows paid execution of a message from a location matching `T` which first descends into an
+/// account on that location, and then takes the shape `AllowTopLevelPaidExecutionFrom` accepts.
+///
+/// This is how `pallet_xcm::send` on a sibling presents a message signed by one of its users, so
+/// fees are withdrawn from, and calls dispatched as, that user's derived account.
+pub struct AllowDescendedPaidExecutionFrom<T>(PhantomData<T>);
+impl<T: Contains<MultiLocation>> ShouldExecute for AllowDescendedPaidExecutionFrom<T> {
+	fn should_execute<Call>(
+		origin: &MultiLocation,
+		message: &mut Xcm<Call>,
+		max_weight: Weight,
+		_weight_credit: &mut Weight,
+	) -> Result<(), ()> {
+		ensure!(T::contains(origin), ());
+		let mut iter = message.0.iter_mut();
+		match iter.next().ok_or(())? {
+			DescendOrigin(X1(AccountId32 { .. })) => (),
+			_ => return Err(()),
+		}
+		match iter.next().ok_or(())? {
+			WithdrawAsset(..) | ReserveAssetDeposited(..) | ReceiveTeleportedAsset(..) => (),
+			_ => return Err(()),
+		}
+		match iter.next().ok_or(())? {
+			BuyExecution { weight_limit: Limited(ref mut weight), .. } if *weight >= max_weight => {
+				*weight = max_weight;
+				Ok(())
+			}
+			BuyExecution { ref mut weight_limit, .. } if weight_limit == &Unlimited => {
+				*weight_limit = Limited(max_weight);
+				Ok(())
+			}
+			_ => Err(()),
+		}
+	}
+}
@@ -7,11 +7,13 @@
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
 pub mod constants;
+pub mod impls;
+pub mod migrations;
 mod weights;
 
 use sp_api::impl_runtime_apis;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
-use sp_runtime::traits::{AccountIdLookup, BlakeTwo256, Block as BlockT};
+use sp_runtime::traits::{BlakeTwo256, Block as BlockT, ConvertInto};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
 	transaction_validity::{TransactionSource, TransactionValidity},
@@ -24,24 +26,24 @@ use sp_version::NativeVersion;
 use sp_version::RuntimeVersion;
 
 use frame_system::{
-	EnsureOneOf, EnsureRoot, limits::{BlockLength, BlockWeights},
+	EnsureRoot, limits::{BlockLength, BlockWeights},
 };
-use statemint_common::{
-	BlockNumber, Signature, AccountId, Balance, Index, Hash, AuraId, Header,
+use parachains_common::{
+	BlockNumber, Signature, AccountId, AccountIndex, Balance, Index, Hash, AuraId, Header,
 	NORMAL_DISPATCH_RATIO, AVERAGE_ON_INITIALIZE_RATIO, MAXIMUM_BLOCK_WEIGHT, SLOT_DURATION, HOURS, DAYS
 };
-pub use statemint_common as common;
-use statemint_common::impls::DealWithFees;
-use codec::{Decode, Encode};
+pub use parachains_common as common;
 use constants::{currency::*, fee::WeightToFee};
 use frame_support::{
 	construct_runtime, parameter_types, match_type,
-	traits::{InstanceFilter, All, Filter, MaxEncodedLen},
+	traits::{
+		ConstU32, Contains, EitherOfDiverse, EqualPrivilegeOnly, Everything, PalletInfoAccess,
+	},
 	weights::{
 		constants::{BlockExecutionWeight, ExtrinsicBaseWeight},
-		DispatchClass, IdentityFee, Weight,
+		ConstantMultiplier, DispatchClass, IdentityFee, Weight,
 	},
-	RuntimeDebug, PalletId,
+	PalletId,
 };
 use sp_runtime::Perbill;
 
@@ -53,13 +55,14 @@ use polkadot_parachain::primitives::Sibling;
 use polkadot_runtime_common::{
 	BlockHashCount, RocksDbWeight, SlowAdjustingFeeUpdate,
 };
-use xcm::v0::{MultiAsset, Junction, MultiLocation, NetworkId, Xcm, BodyId};
+use xcm::latest::prelude::*;
 use xcm_builder::{
-	AccountId32Aliases, CurrencyAdapter, LocationInverter, ParentIsDefault, RelayChainAsNative,
+	AccountId32Aliases, CurrencyAdapter, LocationInverter, ParentIsPreset, RelayChainAsNative,
 	SiblingParachainAsNative, SiblingParachainConvertsVia, SignedAccountId32AsNative,
 	SovereignSignedViaLocation, EnsureXcmOrigin,
-	AllowTopLevelPaidExecutionFrom, TakeWeightCredit, FixedWeightBounds, IsConcrete, NativeAsset,
+	AllowTopLevelPaidExecutionFrom, TakeWeightCredit, WeightInfoBounds, IsConcrete, NativeAsset,
 	AllowUnpaidExecutionFrom, ParentAsSuperuser, SignedToAccountId32, UsingComponents,
+	AllowKnownQueryResponses, AllowSubscriptionsFrom,
 };
 use xcm_executor::{Config, XcmExecutor};
 use pallet_xcm::{XcmPassthrough, EnsureXcm, IsMajorityOfBody};
@@ -93,10 +96,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("kpron"),
 	impl_name: create_runtime_str!("kpron"),
 	authoring_version: 1,
-	spec_version: 1,
+	spec_version: 26,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 3,
 };
 
 /// The version information used to identify this runtime when compiled natively.
@@ -130,7 +133,7 @@ parameter_types! {
 		})
 		.avg_block_initialization(AVERAGE_ON_INITIALIZE_RATIO)
 		.build_or_panic();
-	pub const SS58Prefix: u8 = constants::SS58_PREFIX;
+	pub const SS58Prefix: u16 = constants::SS58_PREFIX;
 }
 
 //TODO temporary remove contract pallet, wait kusama support
@@ -182,12 +185,12 @@ parameter_types! {
 
 // Configure FRAME pallets to include in runtime.
 impl frame_system::Config for Runtime {
-	type BaseCallFilter = ();
+	type BaseCallFilter = pallet_transaction_pause::PausedCallFilter<Runtime>;
 	type BlockWeights = RuntimeBlockWeights;
 	type BlockLength = RuntimeBlockLength;
 	type AccountId = AccountId;
 	type Call = Call;
-	type Lookup = AccountIdLookup<AccountId, ()>;
+	type Lookup = Indices;
 	type Index = Index;
 	type BlockNumber = BlockNumber;
 	type Hash = Hash;
@@ -205,6 +208,7 @@ impl frame_system::Config for Runtime {
 	type SystemWeightInfo = ();
 	type SS58Prefix = SS58Prefix;
 	type OnSetCode = cumulus_pallet_parachain_system::ParachainSetCode<Self>;
+	type MaxConsumers = ConstU32<16>;
 }
 
 parameter_types! {
@@ -227,7 +231,7 @@ impl pallet_authorship::Config for Runtime {
 	type FindAuthor = pallet_session::FindAccountFromAuthorIndex<Self, Aura>;
 	type UncleGenerations = UncleGenerations;
 	type FilterUncle = ();
-	type EventHandler = (CollatorSelection,);
+	type EventHandler = (CollatorStaking,);
 }
 
 parameter_types! {
@@ -250,19 +254,135 @@ impl pallet_balances::Config for Runtime {
 	type ReserveIdentifier = [u8; 8];
 }
 
+parameter_types! {
+	pub const MinVestedTransfer: Balance = 100 * CENTS;
+}
+
+impl pallet_vesting::Config for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type BlockNumberToBalance = ConvertInto;
+	type MinVestedTransfer = MinVestedTransfer;
+	type WeightInfo = pallet_vesting::weights::SubstrateWeight<Runtime>;
+	const MAX_VESTING_SCHEDULES: u32 = 28;
+}
+
+parameter_types! {
+	pub const ExecutiveBody: BodyId = BodyId::Executive;
+}
+
+/// We allow root and the Relay Chain council to execute privileged operations, such as collator
+/// selection, fee and asset administration, pausing, scheduling and XCM budgets.
+pub type GovernanceOrigin = EitherOfDiverse<
+	EnsureRoot<AccountId>,
+	EnsureXcm<IsMajorityOfBody<KsmLocation, ExecutiveBody>>,
+>;
+
 parameter_types! {
 	/// Relay Chain `TransactionByteFee` / 10
 	pub const TransactionByteFee: Balance = 1 * MILLICENTS;
+	pub const OperationalFeeMultiplier: u8 = 5;
+	pub const MaxFeeCoefficients: u32 = 4;
+	// A tenth of the default fee for the base weight of an extrinsic.
+	pub const MinExtrinsicFee: Balance = CENTS / 1_000;
+	pub const MaxBlockFee: Balance = 10 * KPN;
+	// A tenth of the default fee per byte.
+	pub const MinByteFee: Balance = MILLICENTS / 10;
+	pub const MaxByteFee: Balance = 1 * CENTS;
+}
+
+impl pallet_fee_params::Config for Runtime {
+	type Event = Event;
+	type Balance = Balance;
+	type UpdateOrigin = GovernanceOrigin;
+	type DefaultWeightToFee = WeightToFee;
+	type DefaultByteFee = TransactionByteFee;
+	type MaxCoefficients = MaxFeeCoefficients;
+	type MinExtrinsicFee = MinExtrinsicFee;
+	type MaxBlockFee = MaxBlockFee;
+	type MinByteFee = MinByteFee;
+	type MaxByteFee = MaxByteFee;
+	type WeightInfo = weights::pallet_fee_params::WeightInfo<Runtime>;
 }
 
 impl pallet_transaction_payment::Config for Runtime {
 	type OnChargeTransaction =
-	pallet_transaction_payment::CurrencyAdapter<Balances, DealWithFees<Runtime>>;
-	type TransactionByteFee = TransactionByteFee;
-	type WeightToFee = WeightToFee;
+	pallet_transaction_payment::CurrencyAdapter<Balances, impls::DealWithFees<Runtime>>;
+	type OperationalFeeMultiplier = OperationalFeeMultiplier;
+	type WeightToFee = pallet_fee_params::WeightToFee<Runtime>;
+	type LengthToFee = ConstantMultiplier<Balance, pallet_fee_params::ByteFee<Runtime>>;
 	type FeeMultiplierUpdate = SlowAdjustingFeeUpdate<Self>;
 }
 
+parameter_types! {
+	pub const AssetDeposit: Balance = 10 * KPN;
+	pub const ApprovalDeposit: Balance = EXISTENTIAL_DEPOSIT;
+	pub const AssetsStringLimit: u32 = 50;
+	// One storage item; key size is 32; value is size 32+1+1+1+1 = 36 bytes.
+	pub const MetadataDepositBase: Balance = deposit(1, 68);
+	pub const MetadataDepositPerByte: Balance = deposit(0, 1);
+}
+
+impl pallet_assets::Config for Runtime {
+	type Event = Event;
+	type Balance = Balance;
+	type AssetId = u32;
+	type Currency = Balances;
+	type ForceOrigin = GovernanceOrigin;
+	type AssetDeposit = AssetDeposit;
+	type MetadataDepositBase = MetadataDepositBase;
+	type MetadataDepositPerByte = MetadataDepositPerByte;
+	type ApprovalDeposit = ApprovalDeposit;
+	type StringLimit = AssetsStringLimit;
+	type Freezer = ();
+	type Extra = ();
+	type WeightInfo = weights::pallet_assets::WeightInfo<Runtime>;
+}
+
+impl pallet_asset_tx_payment::Config for Runtime {
+	type Event = Event;
+	type UpdateOrigin = GovernanceOrigin;
+	type FeeReceiver = impls::BlockAuthor<Runtime>;
+	type WeightInfo = weights::pallet_asset_tx_payment::WeightInfo<Runtime>;
+}
+
+parameter_types! {
+	pub const MinServiceDeposit: Balance = 10 * CENTS;
+	pub const ServiceRefundDelay: BlockNumber = DAYS;
+}
+
+impl pallet_service_payments::Config for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type MinDeposit = MinServiceDeposit;
+	type RefundDelay = ServiceRefundDelay;
+	type WeightInfo = weights::pallet_service_payments::WeightInfo<Runtime>;
+}
+
+parameter_types! {
+	pub const MaxUsageReportsPerBlock: u32 = 10;
+}
+
+impl pallet_gateway_usage::Config for Runtime {
+	type Event = Event;
+	type RegistrarOrigin = GovernanceOrigin;
+	type MaxReportsPerBlock = MaxUsageReportsPerBlock;
+	type WeightInfo = weights::pallet_gateway_usage::WeightInfo<Runtime>;
+}
+
+parameter_types! {
+	// One storage item; key size is 20; value is size 32+16+1 = 49 bytes.
+	pub const IndexDeposit: Balance = deposit(1, 69);
+}
+
+impl pallet_indices::Config for Runtime {
+	type AccountIndex = AccountIndex;
+	type Currency = Balances;
+	type Deposit = IndexDeposit;
+	type Event = Event;
+	type WeightInfo = pallet_indices::weights::SubstrateWeight<Runtime>;
+}
+
 parameter_types! {
 	// One storage item; key size is 32; value is size 4+4+16+32 bytes = 56 bytes.
 	pub const DepositBase: Balance = deposit(1, 88);
@@ -287,6 +407,65 @@ impl pallet_utility::Config for Runtime {
 	type WeightInfo = weights::pallet_utility::WeightInfo<Runtime>;
 }
 
+/// Pallets whose calls cannot be paused, as blocks cannot be built without their inherents.
+pub struct UnpausablePallets;
+impl Contains<Vec<u8>> for UnpausablePallets {
+	fn contains(name: &Vec<u8>) -> bool {
+		[
+			<ParachainSystem as PalletInfoAccess>::name(),
+			<Timestamp as PalletInfoAccess>::name(),
+			<Authorship as PalletInfoAccess>::name(),
+		]
+		.iter()
+		.any(|unpausable| unpausable.as_bytes() == &name[..])
+	}
+}
+
+impl pallet_transaction_pause::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type PauseOrigin = GovernanceOrigin;
+	type UnpausablePallets = UnpausablePallets;
+}
+
+parameter_types! {
+	pub const PreimageMaxSize: u32 = 4096 * 1024;
+	pub const PreimageBaseDeposit: Balance = deposit(2, 64);
+	pub const PreimageByteDeposit: Balance = deposit(0, 1);
+}
+
+impl pallet_preimage::Config for Runtime {
+	type Event = Event;
+	type WeightInfo = pallet_preimage::weights::SubstrateWeight<Runtime>;
+	type Currency = Balances;
+	type ManagerOrigin = GovernanceOrigin;
+	type MaxSize = PreimageMaxSize;
+	type BaseDeposit = PreimageBaseDeposit;
+	type ByteDeposit = PreimageByteDeposit;
+}
+
+parameter_types! {
+	pub MaximumSchedulerWeight: Weight = Perbill::from_percent(80) *
+		RuntimeBlockWeights::get().max_block;
+	pub const MaxScheduledPerBlock: u32 = 50;
+	// Calls scheduled by hash whose preimage is missing are retried this many blocks later.
+	pub const NoPreimagePostponement: Option<BlockNumber> = Some(10);
+}
+
+impl pallet_scheduler::Config for Runtime {
+	type Event = Event;
+	type Origin = Origin;
+	type PalletsOrigin = OriginCaller;
+	type Call = Call;
+	type MaximumWeight = MaximumSchedulerWeight;
+	type ScheduleOrigin = GovernanceOrigin;
+	type MaxScheduledPerBlock = MaxScheduledPerBlock;
+	type WeightInfo = pallet_scheduler::weights::SubstrateWeight<Runtime>;
+	type OriginPrivilegeCmp = EqualPrivilegeOnly;
+	type PreimageProvider = Preimage;
+	type NoPreimagePostponement = NoPreimagePostponement;
+}
+
 parameter_types! {
 	pub const ReservedXcmpWeight: Weight = MAXIMUM_BLOCK_WEIGHT / 4;
 	pub const ReservedDmpWeight: Weight = MAXIMUM_BLOCK_WEIGHT / 4;
@@ -294,7 +473,7 @@ parameter_types! {
 
 impl cumulus_pallet_parachain_system::Config for Runtime {
 	type Event = Event;
-	type OnValidationData = ();
+	type OnSystemEvent = ();
 	type SelfParaId = parachain_info::Pallet<Runtime>;
 	type DmpMessageHandler = DmpQueue;
 	type ReservedDmpWeight = ReservedDmpWeight;
@@ -308,10 +487,10 @@ impl parachain_info::Config for Runtime {}
 impl cumulus_pallet_aura_ext::Config for Runtime {}
 
 parameter_types! {
-	pub const KsmLocation: MultiLocation = MultiLocation::X1(Junction::Parent);
+	pub const KsmLocation: MultiLocation = MultiLocation::parent();
 	pub const RelayNetwork: NetworkId = NetworkId::Kusama;
 	pub RelayChainOrigin: Origin = cumulus_pallet_xcm::Origin::Relay.into();
-	pub Ancestry: MultiLocation = Junction::Parachain(ParachainInfo::parachain_id().into()).into();
+	pub Ancestry: MultiLocation = Parachain(ParachainInfo::parachain_id().into()).into();
 }
 
 /// Type for specifying how a `MultiLocation` can be converted into an `AccountId`. This is used
@@ -319,11 +498,13 @@ parameter_types! {
 /// `Transact` in order to determine the dispatch Origin.
 pub type LocationToAccountId = (
 	// The parent (Relay-chain) origin converts to the default `AccountId`.
-	ParentIsDefault<AccountId>,
+	ParentIsPreset<AccountId>,
 	// Sibling parachain origins convert to AccountId via the `ParaId::into`.
 	SiblingParachainConvertsVia<Sibling, AccountId>,
 	// Straight up local `AccountId32` origins just alias directly to `AccountId`.
 	AccountId32Aliases<RelayNetwork, AccountId>,
+	// Accounts on sibling parachains convert to an `AccountId` derived from the sibling and account.
+	impls::SiblingAccountId32Derived<AccountId>,
 );
 
 /// Means for transacting assets on this chain.
@@ -365,24 +546,62 @@ pub type XcmOriginToTransactDispatchOrigin = (
 );
 
 parameter_types! {
-	// One XCM operation is 1_000_000_000 weight - almost certainly a conservative estimate.
-	pub UnitWeightCost: Weight = 1_000_000_000;
+	pub const MaxInstructions: u32 = 100;
+	// Messages a sibling may have executed per block, unless governance sets it otherwise.
+	pub const XcmMessageBudget: u32 = 20;
+	pub const MaxQueriesPrunedPerBlock: u32 = 50;
+	// Responses nobody takes are dropped after a day.
+	pub const QueryResponseRetention: BlockNumber = DAYS;
 }
 
 match_type! {
 	pub type ParentOrParentsExecutivePlurality: impl Contains<MultiLocation> = {
-		MultiLocation::X1(Junction::Parent) |
-		MultiLocation::X2(Junction::Parent, Junction::Plurality { id: BodyId::Executive, .. })
+		MultiLocation { parents: 1, interior: Here } |
+		MultiLocation { parents: 1, interior: X1(Plurality { id: BodyId::Executive, .. }) }
+	};
+}
+
+match_type! {
+	pub type SiblingParachains: impl Contains<MultiLocation> = {
+		MultiLocation { parents: 1, interior: X1(Parachain(_)) }
+	};
+}
+
+match_type! {
+	pub type ParentOrSiblings: impl Contains<MultiLocation> = {
+		MultiLocation { parents: 1, interior: Here } |
+		MultiLocation { parents: 1, interior: X1(Parachain(_)) }
 	};
 }
 
-pub type Barrier = (
+/// Kpron's barrier, for a runtime answering queries through `ResponseHandler`.
+pub type BarrierFor<ResponseHandler> = (
 	TakeWeightCredit,
-	AllowTopLevelPaidExecutionFrom<All<MultiLocation>>,
+	// The relay chain and siblings pay for their own execution, e.g. reserve transfers.
+	AllowTopLevelPaidExecutionFrom<ParentOrSiblings>,
+	// Users of sibling chains pay for their own execution, e.g. cross-chain API payments.
+	impls::AllowDescendedPaidExecutionFrom<SiblingParachains>,
 	AllowUnpaidExecutionFrom<ParentOrParentsExecutivePlurality>,
 	// ^^^ Parent and its exec plurality get free execution
+	// Expected responses are OK, both to our own queries and to version discovery.
+	AllowKnownQueryResponses<ResponseHandler>,
+	// Subscriptions for version tracking are OK, from chains we exchange messages with.
+	AllowSubscriptionsFrom<ParentOrSiblings>,
 );
 
+pub type Barrier = BarrierFor<XcmQuery>;
+
+/// Weighs XCM by the benchmarked weight of each instruction.
+pub type XcmWeigher = XcmWeigherFor<Call>;
+
+/// [`XcmWeigher`] for messages carrying calls of type `C`, so that the XCM simulator can weigh
+/// with it too.
+pub type XcmWeigherFor<C> = WeightInfoBounds<weights::xcm::KpronXcmWeight<C>, C, MaxInstructions>;
+
+/// Buys weight for XCM execution with KSM held in `Currency`, at one unit per unit of weight.
+pub type XcmTraderFor<Currency> =
+	UsingComponents<IdentityFee<Balance>, KsmLocation, AccountId, Currency, ()>;
+
 pub struct XcmConfig;
 impl Config for XcmConfig {
 	type Call = Call;
@@ -394,11 +613,17 @@ impl Config for XcmConfig {
 	type IsTeleporter = NativeAsset;	// <- should be enough to allow teleportation of KSM
 	type LocationInverter = LocationInverter<Ancestry>;
 	type Barrier = Barrier;
-	type Weigher = FixedWeightBounds<UnitWeightCost, Call>;
-	type Trader = UsingComponents<IdentityFee<Balance>, KsmLocation, AccountId, Balances, ()>;
-	type ResponseHandler = ();	// Don't handle responses for now.
+	type Weigher = XcmWeigher;
+	type Trader = XcmTraderFor<Balances>;
+	type ResponseHandler = XcmQuery;
+	type AssetTrap = PolkadotXcm;
+	type AssetClaims = PolkadotXcm;
+	type SubscriptionService = PolkadotXcm;
 }
 
+/// The XCM version used with destinations which have not told us theirs yet.
+pub const SAFE_XCM_VERSION: u32 = 2;
+
 parameter_types! {
 	pub const MaxDownwardMessageWeight: Weight = MAXIMUM_BLOCK_WEIGHT / 10;
 }
@@ -412,7 +637,7 @@ pub type LocalOriginToLocation = (
 /// queues.
 pub type XcmRouter = (
 	// Two routers - use UMP to communicate with the relay chain:
-	cumulus_primitives_utility::ParentAsUmp<ParachainSystem>,
+	cumulus_primitives_utility::ParentAsUmp<ParachainSystem, PolkadotXcm>,
 	// ..and XCMP to communicate with the sibling chains.
 	XcmpQueue,
 );
@@ -422,11 +647,16 @@ impl pallet_xcm::Config for Runtime {
 	type SendXcmOrigin = EnsureXcmOrigin<Origin, LocalOriginToLocation>;
 	type XcmRouter = XcmRouter;
 	type ExecuteXcmOrigin = EnsureXcmOrigin<Origin, LocalOriginToLocation>;
-	type XcmExecuteFilter = All<(MultiLocation, Xcm<Call>)>;
+	type XcmExecuteFilter = Everything;
 	type XcmExecutor = XcmExecutor<XcmConfig>;
-	type XcmTeleportFilter = All<(MultiLocation, Vec<MultiAsset>)>;
-	type XcmReserveTransferFilter = All<(MultiLocation, Vec<MultiAsset>)>;
-	type Weigher = FixedWeightBounds<UnitWeightCost, Call>;
+	type XcmTeleportFilter = Everything;
+	type XcmReserveTransferFilter = Everything;
+	type Weigher = XcmWeigher;
+	type LocationInverter = LocationInverter<Ancestry>;
+	type Origin = Origin;
+	type Call = Call;
+	const VERSION_DISCOVERY_QUEUE_SIZE: u32 = 100;
+	type AdvertisedXcmVersion = pallet_xcm::CurrentXcmVersion;
 }
 
 impl cumulus_pallet_xcm::Config for Runtime {
@@ -436,8 +666,17 @@ impl cumulus_pallet_xcm::Config for Runtime {
 
 impl cumulus_pallet_xcmp_queue::Config for Runtime {
 	type Event = Event;
-	type XcmExecutor = XcmExecutor<XcmConfig>;
+	// Each sibling only gets its budget of messages executed per block; the rest wait.
+	type XcmExecutor =
+		pallet_xcm_rate_limit::RateLimitedExecutor<Runtime, XcmExecutor<XcmConfig>, SiblingParachains>;
 	type ChannelInfo = ParachainSystem;
+	type VersionWrapper = PolkadotXcm;
+	// Messages too heavy to execute on arrival are kept, to be serviced by root.
+	type ExecuteOverweightOrigin = EnsureRoot<AccountId>;
+	// Suspending and resuming execution, and the queue thresholds, are up to governance.
+	type ControllerOrigin = GovernanceOrigin;
+	type ControllerOriginConverter = XcmOriginToTransactDispatchOrigin;
+	type WeightInfo = ();
 }
 
 impl cumulus_pallet_dmp_queue::Config for Runtime {
@@ -446,8 +685,42 @@ impl cumulus_pallet_dmp_queue::Config for Runtime {
 	type ExecuteOverweightOrigin = EnsureRoot<AccountId>;
 }
 
+impl pallet_xcm_query::Config for Runtime {
+	type Event = Event;
+	type Origin = Origin;
+	type Call = Call;
+	type LocationInverter = LocationInverter<Ancestry>;
+	// Anything we did not ask for ourselves is left to `pallet_xcm`'s version discovery.
+	type FallbackResponseHandler = PolkadotXcm;
+	type MaxPrunedPerBlock = MaxQueriesPrunedPerBlock;
+	type ResponseRetention = QueryResponseRetention;
+}
+
+impl pallet_xcm_rate_limit::Config for Runtime {
+	type Event = Event;
+	type DefaultBudget = XcmMessageBudget;
+	type BudgetOrigin = GovernanceOrigin;
+}
+
+parameter_types! {
+	// Index of `Hrmp` in the Kusama runtime.
+	pub const RelayHrmpPalletIndex: u8 = 60;
+	// 0.1 KSM, paid from our sovereign account on the relay chain.
+	pub const HrmpRelayFee: u128 = 100_000_000_000;
+	pub const HrmpRelayCallWeight: Weight = 1_000_000_000;
+}
+
+impl pallet_hrmp_manager::Config for Runtime {
+	type Event = Event;
+	type UpdateOrigin = GovernanceOrigin;
+	type XcmSender = XcmRouter;
+	type SelfParaId = ParachainInfo;
+	type RelayHrmpPalletIndex = RelayHrmpPalletIndex;
+	type RelayFee = HrmpRelayFee;
+	type RelayCallWeight = HrmpRelayCallWeight;
+}
+
 parameter_types! {
-	pub const DisabledValidatorsThreshold: Perbill = Perbill::from_percent(33);
 	pub const Period: u32 = 6 * HOURS;
 	pub const Offset: u32 = 0;
 }
@@ -456,46 +729,63 @@ impl pallet_session::Config for Runtime {
 	type Event = Event;
 	type ValidatorId = <Self as frame_system::Config>::AccountId;
 	// we don't have stash and controller, thus we don't need the convert as well.
-	type ValidatorIdOf = pallet_collator_selection::IdentityCollator;
+	type ValidatorIdOf = pallet_collator_staking::IdentityCollator;
 	type ShouldEndSession = pallet_session::PeriodicSessions<Period, Offset>;
 	type NextSessionRotation = pallet_session::PeriodicSessions<Period, Offset>;
-	type SessionManager = CollatorSelection;
+	type SessionManager = CollatorStaking;
 	// Essentially just Aura, but lets be pedantic.
 	type SessionHandler = <opaque::SessionKeys as sp_runtime::traits::OpaqueKeys>::KeyTypeIdProviders;
 	type Keys = opaque::SessionKeys;
-	type DisabledValidatorsThreshold = DisabledValidatorsThreshold;
 	type WeightInfo = weights::pallet_session::WeightInfo<Runtime>;
 }
 
+parameter_types! {
+	pub const MaxAuthorities: u32 = 100_000;
+}
+
 impl pallet_aura::Config for Runtime {
 	type AuthorityId = AuraId;
+	type DisabledValidators = ();
+	type MaxAuthorities = MaxAuthorities;
 }
 
 parameter_types! {
 	pub const PotId: PalletId = PalletId(*b"PotStake");
 	pub const MaxCandidates: u32 = 1000;
-	pub const SessionLength: BlockNumber = 6 * HOURS;
 	pub const MaxInvulnerables: u32 = 100;
-	pub const ExecutiveBody: BodyId = BodyId::Executive;
+	pub const MaxDelegatorsPerCandidate: u32 = 100;
+	pub const MaxDelegationsPerDelegator: u32 = 10;
+	pub const MinDelegation: Balance = 5 * KPN;
+	// One week of sessions.
+	pub const UnbondingDelay: u32 = 28;
+	pub const MaxUnlockingChunks: u32 = 32;
+	pub const SessionsPerYear: u32 = 365 * DAYS / Period::get();
+	pub const PerformanceHistoryDepth: u32 = 28;
+	pub const MaxKicksPerSession: u32 = 2;
+	pub const MaxPayoutsPerBlock: u32 = 4;
 }
 
-/// We allow root and the Relay Chain council to execute privileged collator selection operations.
-pub type CollatorSelectionUpdateOrigin = EnsureOneOf<
-	AccountId,
-	EnsureRoot<AccountId>,
-	EnsureXcm<IsMajorityOfBody<KsmLocation, ExecutiveBody>>,
->;
-
-impl pallet_collator_selection::Config for Runtime {
+impl pallet_collator_staking::Config for Runtime {
 	type Event = Event;
 	type Currency = Balances;
-	type UpdateOrigin = CollatorSelectionUpdateOrigin;
+	type UpdateOrigin = GovernanceOrigin;
 	type PotId = PotId;
 	type MaxCandidates = MaxCandidates;
 	type MaxInvulnerables = MaxInvulnerables;
-	// should be a multiple of session or things will get inconsistent
-	type KickThreshold = Period;
-	type WeightInfo = weights::pallet_collator_selection::WeightInfo<Runtime>;
+	type MaxDelegatorsPerCandidate = MaxDelegatorsPerCandidate;
+	type MaxDelegationsPerDelegator = MaxDelegationsPerDelegator;
+	type MinDelegation = MinDelegation;
+	type UnbondingDelay = UnbondingDelay;
+	type MaxUnlockingChunks = MaxUnlockingChunks;
+	type SessionsPerYear = SessionsPerYear;
+	type PerformanceHistoryDepth = PerformanceHistoryDepth;
+	type MaxKicksPerSession = MaxKicksPerSession;
+	type MaxPayoutsPerBlock = MaxPayoutsPerBlock;
+	type SlotSchedule = impls::AuraSlotSchedule<Runtime>;
+	type ValidatorId = <Self as frame_system::Config>::AccountId;
+	type ValidatorIdOf = pallet_collator_staking::IdentityCollator;
+	type ValidatorRegistration = Session;
+	type WeightInfo = weights::pallet_collator_staking::WeightInfo<Runtime>;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -511,27 +801,44 @@ construct_runtime!(
 		RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Pallet, Call, Storage} = 2,
 		Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent} = 3,
 		ParachainInfo: parachain_info::{Pallet, Storage, Config} = 4,
+		Indices: pallet_indices::{Pallet, Call, Storage, Config<T>, Event<T>} = 5,
 
 		// Monetary stuff.
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>} = 10,
 		TransactionPayment: pallet_transaction_payment::{Pallet, Storage} = 11,
+		AssetTxPayment: pallet_asset_tx_payment::{Pallet, Call, Storage, Event<T>} = 12,
+		FeeParams: pallet_fee_params::{Pallet, Call, Storage, Event<T>} = 13,
+		Vesting: pallet_vesting::{Pallet, Call, Storage, Event<T>, Config<T>} = 14,
 
 		// Collator support. the order of these 4 are important and shall not change.
 		Authorship: pallet_authorship::{Pallet, Call, Storage} = 20,
-		CollatorSelection: pallet_collator_selection::{Pallet, Call, Storage, Event<T>, Config<T>} = 21,
+		CollatorStaking: pallet_collator_staking::{Pallet, Call, Storage, Event<T>, Config<T>} = 21,
 		Session: pallet_session::{Pallet, Call, Storage, Event, Config<T>} = 22,
 		Aura: pallet_aura::{Pallet, Storage, Config<T>} = 23,
 		AuraExt: cumulus_pallet_aura_ext::{Pallet, Storage, Config} = 24,
 
 		// XCM helpers.
 		XcmpQueue: cumulus_pallet_xcmp_queue::{Pallet, Call, Storage, Event<T>} = 30,
-		PolkadotXcm: pallet_xcm::{Pallet, Call, Event<T>, Origin} = 31,
+		PolkadotXcm: pallet_xcm::{Pallet, Call, Storage, Event<T>, Origin, Config} = 31,
 		CumulusXcm: cumulus_pallet_xcm::{Pallet, Event<T>, Origin} = 32,
 		DmpQueue: cumulus_pallet_dmp_queue::{Pallet, Call, Storage, Event<T>} = 33,
+		XcmQuery: pallet_xcm_query::{Pallet, Storage, Event<T>, Origin} = 34,
+		XcmRateLimit: pallet_xcm_rate_limit::{Pallet, Call, Storage, Event<T>} = 35,
+		HrmpManager: pallet_hrmp_manager::{Pallet, Call, Event<T>} = 36,
 
 		// Handy utilities.
 		Utility: pallet_utility::{Pallet, Call, Event} = 40,
 		Multisig: pallet_multisig::{Pallet, Call, Storage, Event<T>} = 41,
+		TransactionPause: pallet_transaction_pause::{Pallet, Call, Storage, Event} = 42,
+		Scheduler: pallet_scheduler::{Pallet, Call, Storage, Event<T>} = 43,
+		Preimage: pallet_preimage::{Pallet, Call, Storage, Event<T>} = 44,
+
+		// Assets.
+		Assets: pallet_assets::{Pallet, Call, Storage, Event<T>} = 50,
+
+		// Apron services.
+		ServicePayments: pallet_service_payments::{Pallet, Call, Storage, Event<T>} = 60,
+		GatewayUsage: pallet_gateway_usage::{Pallet, Call, Storage, Event<T>} = 61,
 
         // other pallets
 		//TODO temporary remove contract pallet, wait kusama support
@@ -540,7 +847,7 @@ construct_runtime!(
 );
 
 /// The address format for describing accounts.
-pub type Address = sp_runtime::MultiAddress<AccountId, ()>;
+pub type Address = sp_runtime::MultiAddress<AccountId, AccountIndex>;
 /// Block type as expected by this runtime.
 pub type Block = generic::Block<Header, UncheckedExtrinsic>;
 /// A Block signed with a Justification
@@ -555,7 +862,10 @@ pub type SignedExtra = (
 	frame_system::CheckEra<Runtime>,
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
-	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	pallet_gateway_usage::FeelessUsageReports<
+		Runtime,
+		pallet_asset_tx_payment::ChargeAssetTxPayment<Runtime>,
+	>,
 );
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
@@ -567,17 +877,15 @@ pub type Executive = frame_executive::Executive<
 	Block,
 	frame_system::ChainContext<Runtime>,
 	Runtime,
-	AllPallets,
-	OnRuntimeUpgrade,
+	AllPalletsWithSystem,
+	Migrations,
 >;
 
-pub struct OnRuntimeUpgrade;
-impl frame_support::traits::OnRuntimeUpgrade for OnRuntimeUpgrade {
-	fn on_runtime_upgrade() -> u64 {
-		sp_io::storage::set(b":c", &[]);
-		RocksDbWeight::get().writes(1)
-	}
-}
+/// The storage migrations run on runtime upgrade, oldest first.
+pub type Migrations = migrations::Migrations<(
+	migrations::MigrateToCollatorStaking,
+	migrations::SetSafeXcmVersion,
+)>;
 
 impl_runtime_apis! {
 	impl sp_consensus_aura::AuraApi<Block, AuraId> for Runtime {
@@ -606,7 +914,7 @@ impl_runtime_apis! {
 
 	impl sp_api::Metadata<Block> for Runtime {
 		fn metadata() -> OpaqueMetadata {
-			Runtime::metadata().into()
+			OpaqueMetadata::new(Runtime::metadata().into())
 		}
 	}
 
@@ -635,8 +943,9 @@ impl_runtime_apis! {
 		fn validate_transaction(
 			source: TransactionSource,
 			tx: <Block as BlockT>::Extrinsic,
+			block_hash: <Block as BlockT>::Hash,
 		) -> TransactionValidity {
-			Executive::validate_transaction(source, tx)
+			Executive::validate_transaction(source, tx, block_hash)
 		}
 	}
 
@@ -711,7 +1020,15 @@ impl_runtime_apis! {
 			uxt: <Block as BlockT>::Extrinsic,
 			len: u32,
 		) -> pallet_transaction_payment_rpc_runtime_api::RuntimeDispatchInfo<Balance> {
-			TransactionPayment::query_info(uxt, len)
+			let asset = uxt.signature.as_ref().and_then(|(_, _, extra)| extra.6.inner().asset_id());
+			let mut info = TransactionPayment::query_info(uxt, len);
+			// Report the fee in the asset it is paid in.
+			if let Some(fee) =
+				asset.and_then(|asset| AssetTxPayment::to_asset_balance(asset, info.partial_fee))
+			{
+				info.partial_fee = fee;
+			}
+			info
 		}
 		fn query_fee_details(
 			uxt: <Block as BlockT>::Extrinsic,
@@ -721,24 +1038,154 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_fee_params_runtime_api::FeeParamsApi<
+		Block,
+		Balance,
+		pallet_fee_params::FeeCoefficient<Balance>,
+	> for Runtime {
+		fn weight_to_fee() -> Vec<pallet_fee_params::FeeCoefficient<Balance>> {
+			FeeParams::weight_to_fee()
+		}
+		fn byte_fee() -> Balance {
+			FeeParams::byte_fee()
+		}
+	}
+
+	impl pallet_collator_staking_runtime_api::CollatorStakingApi<
+		Block,
+		AccountId,
+		pallet_collator_staking::CollatorPerformance,
+	> for Runtime {
+		fn collator_performance(
+			who: AccountId,
+		) -> Vec<(u32, pallet_collator_staking::CollatorPerformance)> {
+			CollatorStaking::performance_history(&who)
+		}
+		fn session_performance(
+			session: u32,
+		) -> Vec<(AccountId, pallet_collator_staking::CollatorPerformance)> {
+			CollatorStaking::session_performance(session)
+		}
+	}
+
 	impl cumulus_primitives_core::CollectCollationInfo<Block> for Runtime {
 		fn collect_collation_info() -> cumulus_primitives_core::CollationInfo {
 			ParachainSystem::collect_collation_info()
 		}
 	}
 
+	#[cfg(feature = "try-runtime")]
+	impl frame_try_runtime::TryRuntime<Block> for Runtime {
+		fn on_runtime_upgrade() -> (Weight, Weight) {
+			log::info!("try-runtime::on_runtime_upgrade kpron.");
+			let weight = Executive::try_runtime_upgrade().unwrap();
+			(weight, RuntimeBlockWeights::get().max_block)
+		}
+
+		fn execute_block_no_check(block: Block) -> Weight {
+			Executive::execute_block_no_check(block)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
+		fn benchmark_metadata(extra: bool) -> (
+			Vec<frame_benchmarking::BenchmarkList>,
+			Vec<frame_support::traits::StorageInfo>,
+		) {
+			use frame_benchmarking::{Benchmarking, BenchmarkList, list_benchmark};
+			use frame_support::traits::StorageInfoTrait;
+
+			use cumulus_pallet_session_benchmarking::Pallet as SessionBench;
+			use frame_system_benchmarking::Pallet as SystemBench;
+
+			type XcmBalances = pallet_xcm_benchmarks::fungible::Pallet::<Runtime>;
+			type XcmGeneric = pallet_xcm_benchmarks::generic::Pallet::<Runtime>;
+
+			let mut list = Vec::<BenchmarkList>::new();
+
+			list_benchmark!(list, extra, frame_system, SystemBench::<Runtime>);
+			list_benchmark!(list, extra, pallet_asset_tx_payment, AssetTxPayment);
+			list_benchmark!(list, extra, pallet_assets, Assets);
+			list_benchmark!(list, extra, pallet_balances, Balances);
+			list_benchmark!(list, extra, pallet_collator_staking, CollatorStaking);
+			list_benchmark!(list, extra, pallet_fee_params, FeeParams);
+			list_benchmark!(list, extra, pallet_gateway_usage, GatewayUsage);
+			list_benchmark!(list, extra, pallet_indices, Indices);
+			list_benchmark!(list, extra, pallet_multisig, Multisig);
+			list_benchmark!(list, extra, pallet_preimage, Preimage);
+			list_benchmark!(list, extra, pallet_scheduler, Scheduler);
+			list_benchmark!(list, extra, pallet_service_payments, ServicePayments);
+			list_benchmark!(list, extra, pallet_session, SessionBench::<Runtime>);
+			list_benchmark!(list, extra, pallet_utility, Utility);
+			list_benchmark!(list, extra, pallet_vesting, Vesting);
+			list_benchmark!(list, extra, pallet_timestamp, Timestamp);
+			list_benchmark!(list, extra, pallet_xcm_benchmarks::fungible, XcmBalances);
+			list_benchmark!(list, extra, pallet_xcm_benchmarks::generic, XcmGeneric);
+
+			let storage_info = AllPalletsWithSystem::storage_info();
+
+			(list, storage_info)
+		}
+
 		fn dispatch_benchmark(
 			config: frame_benchmarking::BenchmarkConfig
 		) -> Result<Vec<frame_benchmarking::BenchmarkBatch>, sp_runtime::RuntimeString> {
-			use frame_benchmarking::{Benchmarking, BenchmarkBatch, add_benchmark, TrackedStorageKey};
+			use frame_benchmarking::{Benchmarking, BenchmarkBatch, BenchmarkError, add_benchmark, TrackedStorageKey};
 
-			use pallet_session_benchmarking::Pallet as SessionBench;
+			use cumulus_pallet_session_benchmarking::Pallet as SessionBench;
 			use frame_system_benchmarking::Pallet as SystemBench;
 
 			impl frame_system_benchmarking::Config for Runtime {}
-			impl pallet_session_benchmarking::Config for Runtime {}
+			impl cumulus_pallet_session_benchmarking::Config for Runtime {}
+
+			parameter_types! {
+				pub const CheckedAccount: Option<AccountId> = None;
+				pub const TrustedTeleporter: Option<(MultiLocation, MultiAsset)> = None;
+			}
+
+			impl pallet_xcm_benchmarks::Config for Runtime {
+				type XcmConfig = XcmConfig;
+				type AccountIdConverter = LocationToAccountId;
+				fn valid_destination() -> Result<MultiLocation, BenchmarkError> {
+					Ok(KsmLocation::get())
+				}
+				fn worst_case_holding() -> MultiAssets {
+					// Balances is the only asset kpron transacts.
+					vec![MultiAsset { id: Concrete(KsmLocation::get()), fun: Fungible(u128::MAX) }].into()
+				}
+			}
+
+			impl pallet_xcm_benchmarks::fungible::Config for Runtime {
+				type TransactAsset = Balances;
+				type CheckedAccount = CheckedAccount;
+				type TrustedTeleporter = TrustedTeleporter;
+				fn get_multi_asset() -> MultiAsset {
+					MultiAsset { id: Concrete(KsmLocation::get()), fun: Fungible(KPN) }
+				}
+			}
+
+			impl pallet_xcm_benchmarks::generic::Config for Runtime {
+				type Call = Call;
+				fn worst_case_response() -> (u64, Response) {
+					(0u64, Response::Version(Default::default()))
+				}
+				fn transact_origin() -> Result<MultiLocation, BenchmarkError> {
+					Ok(KsmLocation::get())
+				}
+				fn subscribe_origin() -> Result<MultiLocation, BenchmarkError> {
+					Ok(KsmLocation::get())
+				}
+				fn claimable_asset() -> Result<(MultiLocation, MultiLocation, MultiAssets), BenchmarkError> {
+					let origin = KsmLocation::get();
+					let assets: MultiAssets = (Concrete(KsmLocation::get()), 1_000 * KPN).into();
+					let ticket = MultiLocation { parents: 0, interior: Here };
+					Ok((origin, ticket, assets))
+				}
+			}
+
+			type XcmBalances = pallet_xcm_benchmarks::fungible::Pallet::<Runtime>;
+			type XcmGeneric = pallet_xcm_benchmarks::generic::Pallet::<Runtime>;
 
 			let whitelist: Vec<TrackedStorageKey> = vec![
 				// Block Number
@@ -757,15 +1204,23 @@ impl_runtime_apis! {
 			let params = (&config, &whitelist);
 
 			add_benchmark!(params, batches, frame_system, SystemBench::<Runtime>);
+			add_benchmark!(params, batches, pallet_asset_tx_payment, AssetTxPayment);
 			add_benchmark!(params, batches, pallet_assets, Assets);
 			add_benchmark!(params, batches, pallet_balances, Balances);
+			add_benchmark!(params, batches, pallet_collator_staking, CollatorStaking);
+			add_benchmark!(params, batches, pallet_fee_params, FeeParams);
+			add_benchmark!(params, batches, pallet_gateway_usage, GatewayUsage);
+			add_benchmark!(params, batches, pallet_indices, Indices);
 			add_benchmark!(params, batches, pallet_multisig, Multisig);
-			add_benchmark!(params, batches, pallet_proxy, Proxy);
+			add_benchmark!(params, batches, pallet_preimage, Preimage);
+			add_benchmark!(params, batches, pallet_scheduler, Scheduler);
+			add_benchmark!(params, batches, pallet_service_payments, ServicePayments);
 			add_benchmark!(params, batches, pallet_session, SessionBench::<Runtime>);
-			add_benchmark!(params, batches, pallet_uniques, Uniques);
 			add_benchmark!(params, batches, pallet_utility, Utility);
+			add_benchmark!(params, batches, pallet_vesting, Vesting);
 			add_benchmark!(params, batches, pallet_timestamp, Timestamp);
-			add_benchmark!(params, batches, pallet_collator_selection, CollatorSelection);
+			add_benchmark!(params, batches, pallet_xcm_benchmarks::fungible, XcmBalances);
+			add_benchmark!(params, batches, pallet_xcm_benchmarks::generic, XcmGeneric);
 
 			if batches.is_empty() { return Err("Benchmark not found for this pallet.".into()) }
 			Ok(batches)
@@ -777,7 +1232,7 @@ struct CheckInherents;
 
 impl cumulus_pallet_parachain_system::CheckInherents<Block> for CheckInherents {
 	fn check_inherents(
-		_: &[UncheckedExtrinsic],
+		_: &Block,
 		_: &cumulus_pallet_parachain_system::RelayChainStateProof,
 	) -> sp_inherents::CheckInherentsResult {
 		sp_inherents::CheckInherentsResult::new()
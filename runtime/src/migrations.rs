@@ -0,0 +1,374 @@
+//! Storage migrations, run once each on the first runtime upgrade that finds them pending.
+//!
+//! Every migration names the `spec_version` that introduced it and checks on-chain state, usually
+//! the storage version of the pallet it migrates, to tell whether it still has to run. Migrations
+//! run in the order they are listed in [`Migrations`], so a migration can rely on the ones
+//! before it. Once all live chains run a `spec_version` past a migration's, it can be removed.
+
+use super::*;
+use frame_support::{
+	traits::{GetStorageVersion, PalletInfoAccess, StorageVersion},
+	weights::Weight,
+};
+use sp_std::marker::PhantomData;
+
+const LOG_TARGET: &str = "runtime::migrations";
+
+/// A one-off storage migration.
+pub trait Migration {
+	/// Name under which the migration is logged.
+	const NAME: &'static str;
+	/// The `spec_version` of the runtime that introduced the migration in its current form.
+	const SPEC_VERSION: u32;
+
+	/// Whether the migration has yet to run on this chain.
+	fn is_pending() -> bool;
+
+	/// Migrate storage, returning the weight consumed. Must leave `is_pending` false.
+	fn migrate() -> Weight;
+
+	/// Check the state before `migrate` runs.
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<(), &'static str> {
+		Ok(())
+	}
+
+	/// Check the state after `migrate` ran.
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade() -> Result<(), &'static str> {
+		Ok(())
+	}
+}
+
+/// Whether the storage version of pallet `P` on chain is below `version`.
+pub fn pallet_below<P: GetStorageVersion>(version: u16) -> bool {
+	P::on_chain_storage_version() < StorageVersion::new(version)
+}
+
+/// An ordered list of migrations, as a tuple of [`Migration`]s.
+pub trait MigrationList {
+	/// Run the pending migrations in order, returning the weight consumed.
+	fn run() -> Weight;
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<(), &'static str>;
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade() -> Result<(), &'static str>;
+}
+
+/// Run `M` if it is pending, logging what ran.
+fn run_one<M: Migration>() -> Weight {
+	let check = RocksDbWeight::get().reads(1);
+	if !M::is_pending() {
+		log::debug!(target: LOG_TARGET, "{} (spec {}) already applied", M::NAME, M::SPEC_VERSION);
+		return check
+	}
+
+	log::info!(target: LOG_TARGET, "running {} (spec {})", M::NAME, M::SPEC_VERSION);
+	let weight = M::migrate();
+	if M::is_pending() {
+		log::error!(
+			target: LOG_TARGET,
+			"{} (spec {}) still pending after running",
+			M::NAME,
+			M::SPEC_VERSION,
+		);
+	}
+	log::info!(
+		target: LOG_TARGET,
+		"{} (spec {}) done, weight {}",
+		M::NAME,
+		M::SPEC_VERSION,
+		weight,
+	);
+	weight.saturating_add(check.saturating_mul(2))
+}
+
+/// Check `M` before the upgrade if it is pending, noting in temporary storage whether it was.
+#[cfg(feature = "try-runtime")]
+fn pre_upgrade_one<M: Migration>() -> Result<(), &'static str> {
+	use frame_support::traits::OnRuntimeUpgradeHelpersExt;
+	let pending = M::is_pending();
+	Migrations::<()>::set_temp_storage(pending, M::NAME);
+	if pending {
+		M::pre_upgrade()?;
+	}
+	Ok(())
+}
+
+/// Check `M` after the upgrade if it was pending before it, and so ran in this upgrade.
+#[cfg(feature = "try-runtime")]
+fn post_upgrade_one<M: Migration>() -> Result<(), &'static str> {
+	use frame_support::traits::OnRuntimeUpgradeHelpersExt;
+	let ran =
+		Migrations::<()>::get_temp_storage::<bool>(M::NAME).ok_or("pre_upgrade did not run")?;
+	if ran {
+		M::post_upgrade()?;
+	}
+	Ok(())
+}
+
+impl MigrationList for () {
+	fn run() -> Weight {
+		0
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<(), &'static str> {
+		Ok(())
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade() -> Result<(), &'static str> {
+		Ok(())
+	}
+}
+
+macro_rules! impl_migration_list {
+	($($migration:ident),+) => {
+		impl<$($migration: Migration),+> MigrationList for ($($migration,)+) {
+			fn run() -> Weight {
+				let mut weight: Weight = 0;
+				$( weight = weight.saturating_add(run_one::<$migration>()); )+
+				weight
+			}
+
+			#[cfg(feature = "try-runtime")]
+			fn pre_upgrade() -> Result<(), &'static str> {
+				$( pre_upgrade_one::<$migration>()?; )+
+				Ok(())
+			}
+
+			#[cfg(feature = "try-runtime")]
+			fn post_upgrade() -> Result<(), &'static str> {
+				$(
+					if $migration::is_pending() {
+						return Err(concat!(stringify!($migration), " still pending"))
+					}
+					post_upgrade_one::<$migration>()?;
+				)+
+				Ok(())
+			}
+		}
+	};
+}
+
+impl_migration_list!(A);
+impl_migration_list!(A, B);
+impl_migration_list!(A, B, C);
+impl_migration_list!(A, B, C, D);
+impl_migration_list!(A, B, C, D, E);
+impl_migration_list!(A, B, C, D, E, F);
+impl_migration_list!(A, B, C, D, E, F, G);
+impl_migration_list!(A, B, C, D, E, F, G, H);
+
+/// Runs the migrations `L` on runtime upgrade.
+pub struct Migrations<L>(PhantomData<L>);
+impl<L: MigrationList> frame_support::traits::OnRuntimeUpgrade for Migrations<L> {
+	fn on_runtime_upgrade() -> Weight {
+		L::run()
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<(), &'static str> {
+		L::pre_upgrade()
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade() -> Result<(), &'static str> {
+		L::post_upgrade()
+	}
+}
+
+/// Chains running before versioned XCM have no default version set for `pallet_xcm`, so
+/// messages to destinations that have not advertised a version yet could not be sent.
+pub struct SetSafeXcmVersion;
+impl SetSafeXcmVersion {
+	fn safe_xcm_version() -> Option<u32> {
+		frame_support::storage::migration::get_storage_value(
+			<PolkadotXcm as PalletInfoAccess>::name().as_bytes(),
+			b"SafeXcmVersion",
+			&[],
+		)
+	}
+}
+impl Migration for SetSafeXcmVersion {
+	const NAME: &'static str = "SetSafeXcmVersion";
+	const SPEC_VERSION: u32 = 6;
+
+	fn is_pending() -> bool {
+		Self::safe_xcm_version().is_none()
+	}
+
+	fn migrate() -> Weight {
+		let _ = PolkadotXcm::force_default_xcm_version(
+			frame_system::RawOrigin::Root.into(),
+			Some(SAFE_XCM_VERSION),
+		);
+		RocksDbWeight::get().writes(1)
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade() -> Result<(), &'static str> {
+		frame_support::ensure!(
+			Self::safe_xcm_version() == Some(SAFE_XCM_VERSION),
+			"safe XCM version not set",
+		);
+		Ok(())
+	}
+}
+
+/// Moves the invulnerables, desired candidate count and candidacy bond of the removed
+/// `CollatorSelection` pallet over to `CollatorStaking`, releasing the old candidacy deposits.
+pub struct MigrateToCollatorStaking;
+impl Migration for MigrateToCollatorStaking {
+	const NAME: &'static str = "MigrateToCollatorStaking";
+	const SPEC_VERSION: u32 = 4;
+
+	fn is_pending() -> bool {
+		pallet_below::<CollatorStaking>(1)
+	}
+
+	fn migrate() -> Weight {
+		pallet_collator_staking::migration::from_collator_selection::migrate::<Runtime>(
+			b"CollatorSelection",
+		)
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<(), &'static str> {
+		use frame_support::traits::OnRuntimeUpgradeHelpersExt;
+		let invulnerables =
+			pallet_collator_staking::migration::from_collator_selection::pre_migrate::<Runtime>(
+				b"CollatorSelection",
+			)?;
+		Migrations::<()>::set_temp_storage(invulnerables, "invulnerables");
+		Ok(())
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade() -> Result<(), &'static str> {
+		use frame_support::traits::OnRuntimeUpgradeHelpersExt;
+		let invulnerables = Migrations::<()>::get_temp_storage::<Option<u32>>("invulnerables")
+			.ok_or("pre_upgrade did not run")?;
+		pallet_collator_staking::migration::from_collator_selection::post_migrate::<Runtime>(
+			b"CollatorSelection",
+			invulnerables,
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_support::storage::unhashed;
+
+	struct First;
+	impl Migration for First {
+		const NAME: &'static str = "First";
+		const SPEC_VERSION: u32 = 1;
+		fn is_pending() -> bool {
+			unhashed::get::<u32>(b"first").is_none()
+		}
+		fn migrate() -> Weight {
+			unhashed::put(b"first", &unhashed::get::<u32>(b"runs").unwrap_or(0));
+			unhashed::put(b"runs", &(unhashed::get::<u32>(b"runs").unwrap_or(0) + 1));
+			10
+		}
+	}
+
+	struct Second;
+	impl Migration for Second {
+		const NAME: &'static str = "Second";
+		const SPEC_VERSION: u32 = 2;
+		fn is_pending() -> bool {
+			unhashed::get::<u32>(b"second").is_none()
+		}
+		fn migrate() -> Weight {
+			unhashed::put(b"second", &unhashed::get::<u32>(b"runs").unwrap_or(0));
+			unhashed::put(b"runs", &(unhashed::get::<u32>(b"runs").unwrap_or(0) + 1));
+			20
+		}
+	}
+
+	/// Checks after the upgrade that it ran in that same upgrade.
+	struct Checked;
+	impl Migration for Checked {
+		const NAME: &'static str = "Checked";
+		const SPEC_VERSION: u32 = 3;
+		fn is_pending() -> bool {
+			!unhashed::exists(b"checked")
+		}
+		fn migrate() -> Weight {
+			unhashed::put(b"checked", &());
+			unhashed::put(b"just_migrated", &());
+			0
+		}
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			frame_support::ensure!(unhashed::exists(b"just_migrated"), "checked without migrating");
+			Ok(())
+		}
+	}
+
+	#[test]
+	fn migrations_run_once_in_order() {
+		sp_io::TestExternalities::default().execute_with(|| {
+			let check = RocksDbWeight::get().reads(1);
+			assert_eq!(<(First, Second)>::run(), 30 + 4 * check);
+			assert_eq!(unhashed::get::<u32>(b"first"), Some(0));
+			assert_eq!(unhashed::get::<u32>(b"second"), Some(1));
+
+			assert_eq!(<(First, Second)>::run(), 2 * check);
+			assert_eq!(unhashed::get::<u32>(b"runs"), Some(2));
+		});
+	}
+
+	#[test]
+	fn only_pending_migrations_run() {
+		sp_io::TestExternalities::default().execute_with(|| {
+			unhashed::put(b"first", &7u32);
+			<(First, Second)>::run();
+			assert_eq!(unhashed::get::<u32>(b"first"), Some(7));
+			assert_eq!(unhashed::get::<u32>(b"second"), Some(0));
+		});
+	}
+
+	#[test]
+	fn safe_xcm_version_is_set_once() {
+		sp_io::TestExternalities::default().execute_with(|| {
+			assert!(SetSafeXcmVersion::is_pending());
+			<(SetSafeXcmVersion,)>::run();
+			assert_eq!(SetSafeXcmVersion::safe_xcm_version(), Some(SAFE_XCM_VERSION));
+			assert!(!SetSafeXcmVersion::is_pending());
+		});
+	}
+
+	#[test]
+	fn collator_staking_migration_bumps_storage_version() {
+		sp_io::TestExternalities::default().execute_with(|| {
+			assert!(MigrateToCollatorStaking::is_pending());
+			<(MigrateToCollatorStaking,)>::run();
+			assert_eq!(CollatorStaking::on_chain_storage_version(), StorageVersion::new(1));
+			assert!(!MigrateToCollatorStaking::is_pending());
+		});
+	}
+	#[cfg(feature = "try-runtime")]
+	#[test]
+	fn only_migrations_pending_before_the_upgrade_are_checked_after_it() {
+		use frame_support::{assert_ok, traits::OnRuntimeUpgrade};
+		type Upgrade = Migrations<(First, Checked)>;
+		sp_io::TestExternalities::default().execute_with(|| {
+			assert_ok!(Upgrade::pre_upgrade());
+			Upgrade::on_runtime_upgrade();
+			assert_ok!(Upgrade::post_upgrade());
+
+			// A later upgrade finds both applied and leaves their checks alone.
+			unhashed::kill(b"just_migrated");
+			assert_ok!(Upgrade::pre_upgrade());
+			Upgrade::on_runtime_upgrade();
+			assert_ok!(Upgrade::post_upgrade());
+		});
+	}
+}
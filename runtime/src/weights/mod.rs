@@ -1,9 +1,12 @@
+pub mod pallet_asset_tx_payment;
 pub mod pallet_assets;
 pub mod pallet_balances;
+pub mod pallet_collator_staking;
+pub mod pallet_fee_params;
+pub mod pallet_gateway_usage;
 pub mod pallet_multisig;
-pub mod pallet_collator_selection;
-pub mod pallet_proxy;
+pub mod pallet_service_payments;
 pub mod pallet_session;
 pub mod pallet_timestamp;
-pub mod pallet_uniques;
 pub mod pallet_utility;
+pub mod xcm;
@@ -0,0 +1,21 @@
+//! Weights for pallet_asset_tx_payment
+//!
+//! NOT BENCHMARKED FOR KPRON: these are the pallet's storage-based estimates, priced with the
+//! runtime's `DbWeight`. `scripts/benchmark.sh` overwrites this file with the results of the
+//! pallet's benchmarks on the reference machine.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::Weight};
+use sp_std::marker::PhantomData;
+
+/// Weight functions for pallet_asset_tx_payment.
+pub struct WeightInfo<T>(PhantomData<T>);
+impl<T: frame_system::Config> pallet_asset_tx_payment::WeightInfo for WeightInfo<T> {
+	fn set_fee_rate() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+}
@@ -53,4 +53,16 @@ impl<T: frame_system::Config> pallet_balances::WeightInfo for WeightInfo<T> {
 			.saturating_add(T::DbWeight::get().reads(2 as Weight))
 			.saturating_add(T::DbWeight::get().writes(2 as Weight))
 	}
+	// `transfer_all` and `force_unreserve` were added to `pallet_balances` after the run above;
+	// their weights are taken from Statemine's polkadot-v0.9.25 benchmarks.
+	fn transfer_all() -> Weight {
+		(74_195_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn force_unreserve() -> Weight {
+		(33_813_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
 }
@@ -1,75 +0,0 @@
-
-//! Autogenerated weights for pallet_collator_selection
-//!
-//! THIS FILE WAS AUTO-GENERATED USING THE SUBSTRATE BENCHMARK CLI VERSION 3.0.0
-//! DATE: 2021-05-31, STEPS: `[50, ]`, REPEAT: 20, LOW RANGE: `[]`, HIGH RANGE: `[]`
-//! EXECUTION: Some(Wasm), WASM-EXECUTION: Compiled, CHAIN: Some("statemine-dev"), DB CACHE: 128
-
-// Executed Command:
-// ./target/release/statemint
-// benchmark
-// --chain=statemine-dev
-// --execution=wasm
-// --wasm-execution=compiled
-// --pallet=pallet_collator_selection
-// --extrinsic=*
-// --steps=50
-// --repeat=20
-// --raw
-// --output=./runtime/statemine/src/weights/
-
-
-#![allow(unused_parens)]
-#![allow(unused_imports)]
-
-use frame_support::{traits::Get, weights::Weight};
-use sp_std::marker::PhantomData;
-
-/// Weight functions for pallet_collator_selection.
-pub struct WeightInfo<T>(PhantomData<T>);
-impl<T: frame_system::Config> pallet_collator_selection::WeightInfo for WeightInfo<T> {
-	fn set_invulnerables(b: u32, ) -> Weight {
-		(18_481_000 as Weight)
-			// Standard Error: 0
-			.saturating_add((67_000 as Weight).saturating_mul(b as Weight))
-			.saturating_add(T::DbWeight::get().writes(1 as Weight))
-	}
-	fn set_desired_candidates() -> Weight {
-		(16_376_000 as Weight)
-			.saturating_add(T::DbWeight::get().writes(1 as Weight))
-	}
-	fn set_candidacy_bond() -> Weight {
-		(17_031_000 as Weight)
-			.saturating_add(T::DbWeight::get().writes(1 as Weight))
-	}
-	fn register_as_candidate(c: u32, ) -> Weight {
-		(72_345_000 as Weight)
-			// Standard Error: 0
-			.saturating_add((197_000 as Weight).saturating_mul(c as Weight))
-			.saturating_add(T::DbWeight::get().reads(4 as Weight))
-			.saturating_add(T::DbWeight::get().writes(2 as Weight))
-	}
-	fn leave_intent(c: u32, ) -> Weight {
-		(55_446_000 as Weight)
-			// Standard Error: 0
-			.saturating_add((153_000 as Weight).saturating_mul(c as Weight))
-			.saturating_add(T::DbWeight::get().reads(1 as Weight))
-			.saturating_add(T::DbWeight::get().writes(2 as Weight))
-	}
-	fn note_author() -> Weight {
-		(71_828_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(3 as Weight))
-			.saturating_add(T::DbWeight::get().writes(4 as Weight))
-	}
-	fn new_session(r: u32, c: u32, ) -> Weight {
-		(0 as Weight)
-			// Standard Error: 1_004_000
-			.saturating_add((110_066_000 as Weight).saturating_mul(r as Weight))
-			// Standard Error: 1_004_000
-			.saturating_add((152_035_000 as Weight).saturating_mul(c as Weight))
-			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(r as Weight)))
-			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(c as Weight)))
-			.saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(r as Weight)))
-			.saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(c as Weight)))
-	}
-}
@@ -0,0 +1,25 @@
+//! Weights for pallet_fee_params
+//!
+//! NOT BENCHMARKED FOR KPRON: these are the pallet's storage-based estimates, priced with the
+//! runtime's `DbWeight`. `scripts/benchmark.sh` overwrites this file with the results of the
+//! pallet's benchmarks on the reference machine.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::Weight};
+use sp_std::marker::PhantomData;
+
+/// Weight functions for pallet_fee_params.
+pub struct WeightInfo<T>(PhantomData<T>);
+impl<T: frame_system::Config> pallet_fee_params::WeightInfo for WeightInfo<T> {
+	fn set_weight_to_fee(c: u32) -> Weight {
+		(12_000_000 as Weight)
+			.saturating_add((1_000_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn set_byte_fee() -> Weight {
+		(10_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+}
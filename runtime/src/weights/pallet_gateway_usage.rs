@@ -0,0 +1,31 @@
+//! Weights for pallet_gateway_usage
+//!
+//! NOT BENCHMARKED FOR KPRON: these are the pallet's storage-based estimates, priced with the
+//! runtime's `DbWeight`. `scripts/benchmark.sh` overwrites this file with the results of the
+//! pallet's benchmarks on the reference machine.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::Weight};
+use sp_std::marker::PhantomData;
+
+/// Weight functions for pallet_gateway_usage.
+pub struct WeightInfo<T>(PhantomData<T>);
+impl<T: frame_system::Config> pallet_gateway_usage::WeightInfo for WeightInfo<T> {
+	fn register_gateway() -> Weight {
+		(20_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	fn deregister_gateway() -> Weight {
+		(20_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	fn report_usage() -> Weight {
+		(25_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+}
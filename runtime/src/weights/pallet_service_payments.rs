@@ -0,0 +1,31 @@
+//! Weights for pallet_service_payments
+//!
+//! NOT BENCHMARKED FOR KPRON: these are the pallet's storage-based estimates, priced with the
+//! runtime's `DbWeight`. `scripts/benchmark.sh` overwrites this file with the results of the
+//! pallet's benchmarks on the reference machine.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::Weight};
+use sp_std::marker::PhantomData;
+
+/// Weight functions for pallet_service_payments.
+pub struct WeightInfo<T>(PhantomData<T>);
+impl<T: frame_system::Config> pallet_service_payments::WeightInfo for WeightInfo<T> {
+	fn deposit() -> Weight {
+		(40_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn charge() -> Weight {
+		(50_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	fn refund() -> Weight {
+		(40_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+}
@@ -41,4 +41,14 @@ impl<T: frame_system::Config> pallet_utility::WeightInfo for WeightInfo<T> {
 			// Standard Error: 0
 			.saturating_add((4_578_000 as Weight).saturating_mul(c as Weight))
 	}
+	// `dispatch_as` and `force_batch` were added to `pallet_utility` after the run above; their
+	// weights are taken from Statemine's polkadot-v0.9.25 benchmarks.
+	fn dispatch_as() -> Weight {
+		(11_245_000 as Weight)
+	}
+	fn force_batch(c: u32, ) -> Weight {
+		(18_442_000 as Weight)
+			// Standard Error: 2_000
+			.saturating_add((4_550_000 as Weight).saturating_mul(c as Weight))
+	}
 }
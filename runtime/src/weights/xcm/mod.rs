@@ -0,0 +1,170 @@
+mod pallet_xcm_benchmarks_fungible;
+mod pallet_xcm_benchmarks_generic;
+
+use crate::Runtime;
+use frame_support::weights::Weight;
+use xcm::{
+	latest::{prelude::*, Weight as XCMWeight},
+	DoubleEncoded,
+};
+
+use pallet_xcm_benchmarks_fungible::WeightInfo as XcmFungibleWeight;
+use pallet_xcm_benchmarks_generic::WeightInfo as XcmGeneric;
+
+/// Wildcard filters are weighed as if they matched this many assets.
+const MAX_ASSETS: u32 = 100;
+
+trait WeighMultiAssets {
+	fn weigh_multi_assets(&self, weight: Weight) -> Weight;
+}
+
+impl WeighMultiAssets for MultiAssetFilter {
+	fn weigh_multi_assets(&self, weight: Weight) -> Weight {
+		match self {
+			Self::Definite(assets) => (assets.len() as Weight).saturating_mul(weight),
+			Self::Wild(_) => (MAX_ASSETS as Weight).saturating_mul(weight),
+		}
+	}
+}
+
+impl WeighMultiAssets for MultiAssets {
+	fn weigh_multi_assets(&self, weight: Weight) -> Weight {
+		(self.len() as Weight).saturating_mul(weight)
+	}
+}
+
+/// Benchmarked weights of XCM instructions on kpron.
+///
+/// Instructions kpron cannot execute weigh `Weight::MAX`, so that messages containing them are
+/// refused before execution starts.
+pub struct KpronXcmWeight<Call>(core::marker::PhantomData<Call>);
+impl<Call> XcmWeightInfo<Call> for KpronXcmWeight<Call> {
+	fn withdraw_asset(assets: &MultiAssets) -> XCMWeight {
+		assets.weigh_multi_assets(XcmFungibleWeight::<Runtime>::withdraw_asset())
+	}
+	fn reserve_asset_deposited(assets: &MultiAssets) -> XCMWeight {
+		// Not benchmarked separately; like a teleport, it only places the assets in holding.
+		assets.weigh_multi_assets(XcmFungibleWeight::<Runtime>::receive_teleported_asset())
+	}
+	fn receive_teleported_asset(assets: &MultiAssets) -> XCMWeight {
+		assets.weigh_multi_assets(XcmFungibleWeight::<Runtime>::receive_teleported_asset())
+	}
+	fn query_response(_query_id: &u64, _response: &Response, _max_weight: &u64) -> XCMWeight {
+		XcmGeneric::<Runtime>::query_response()
+	}
+	fn transfer_asset(assets: &MultiAssets, _dest: &MultiLocation) -> XCMWeight {
+		assets.weigh_multi_assets(XcmFungibleWeight::<Runtime>::transfer_asset())
+	}
+	fn transfer_reserve_asset(
+		assets: &MultiAssets,
+		_dest: &MultiLocation,
+		_xcm: &Xcm<()>,
+	) -> XCMWeight {
+		assets.weigh_multi_assets(XcmFungibleWeight::<Runtime>::transfer_reserve_asset())
+	}
+	fn transact(
+		_origin_type: &OriginKind,
+		_require_weight_at_most: &u64,
+		_call: &DoubleEncoded<Call>,
+	) -> XCMWeight {
+		// The call's own weight is added by the weigher from `require_weight_at_most`.
+		XcmGeneric::<Runtime>::transact()
+	}
+	fn hrmp_new_channel_open_request(
+		_sender: &u32,
+		_max_message_size: &u32,
+		_max_capacity: &u32,
+	) -> XCMWeight {
+		// Only sent to the relay chain's executor.
+		Weight::MAX
+	}
+	fn hrmp_channel_accepted(_recipient: &u32) -> XCMWeight {
+		// Only sent to the relay chain's executor.
+		Weight::MAX
+	}
+	fn hrmp_channel_closing(_initiator: &u32, _sender: &u32, _recipient: &u32) -> XCMWeight {
+		// Only sent to the relay chain's executor.
+		Weight::MAX
+	}
+	fn clear_origin() -> XCMWeight {
+		XcmGeneric::<Runtime>::clear_origin()
+	}
+	fn descend_origin(_who: &InteriorMultiLocation) -> XCMWeight {
+		XcmGeneric::<Runtime>::descend_origin()
+	}
+	fn report_error(
+		_query_id: &QueryId,
+		_dest: &MultiLocation,
+		_max_response_weight: &u64,
+	) -> XCMWeight {
+		XcmGeneric::<Runtime>::report_error()
+	}
+	fn deposit_asset(
+		assets: &MultiAssetFilter,
+		_max_assets: &u32,
+		_dest: &MultiLocation,
+	) -> XCMWeight {
+		assets.weigh_multi_assets(XcmFungibleWeight::<Runtime>::deposit_asset())
+	}
+	fn deposit_reserve_asset(
+		assets: &MultiAssetFilter,
+		_max_assets: &u32,
+		_dest: &MultiLocation,
+		_xcm: &Xcm<()>,
+	) -> XCMWeight {
+		assets.weigh_multi_assets(XcmFungibleWeight::<Runtime>::deposit_reserve_asset())
+	}
+	fn exchange_asset(_give: &MultiAssetFilter, _receive: &MultiAssets) -> XCMWeight {
+		// Kpron has no asset exchange.
+		Weight::MAX
+	}
+	fn initiate_reserve_withdraw(
+		assets: &MultiAssetFilter,
+		_reserve: &MultiLocation,
+		_xcm: &Xcm<()>,
+	) -> XCMWeight {
+		assets.weigh_multi_assets(XcmGeneric::<Runtime>::initiate_reserve_withdraw())
+	}
+	fn initiate_teleport(
+		assets: &MultiAssetFilter,
+		_dest: &MultiLocation,
+		_xcm: &Xcm<()>,
+	) -> XCMWeight {
+		assets.weigh_multi_assets(XcmFungibleWeight::<Runtime>::initiate_teleport())
+	}
+	fn query_holding(
+		_query_id: &u64,
+		_dest: &MultiLocation,
+		_assets: &MultiAssetFilter,
+		_max_response_weight: &u64,
+	) -> XCMWeight {
+		XcmGeneric::<Runtime>::query_holding()
+	}
+	fn buy_execution(_fees: &MultiAsset, _weight_limit: &WeightLimit) -> XCMWeight {
+		XcmGeneric::<Runtime>::buy_execution()
+	}
+	fn refund_surplus() -> XCMWeight {
+		XcmGeneric::<Runtime>::refund_surplus()
+	}
+	fn set_error_handler(_xcm: &Xcm<Call>) -> XCMWeight {
+		XcmGeneric::<Runtime>::set_error_handler()
+	}
+	fn set_appendix(_xcm: &Xcm<Call>) -> XCMWeight {
+		XcmGeneric::<Runtime>::set_appendix()
+	}
+	fn clear_error() -> XCMWeight {
+		XcmGeneric::<Runtime>::clear_error()
+	}
+	fn claim_asset(_assets: &MultiAssets, _ticket: &MultiLocation) -> XCMWeight {
+		XcmGeneric::<Runtime>::claim_asset()
+	}
+	fn trap(_code: &u64) -> XCMWeight {
+		XcmGeneric::<Runtime>::trap()
+	}
+	fn subscribe_version(_query_id: &QueryId, _max_response_weight: &u64) -> XCMWeight {
+		XcmGeneric::<Runtime>::subscribe_version()
+	}
+	fn unsubscribe_version() -> XCMWeight {
+		XcmGeneric::<Runtime>::unsubscribe_version()
+	}
+}
@@ -0,0 +1,79 @@
+//! Weights for `pallet_xcm_benchmarks::generic`
+//!
+//! NOT BENCHMARKED FOR KPRON: these are Statemine's results (CHAIN: Some("statemine-dev"),
+//! DB CACHE: 128), as are the other weights in this directory. Statemine transacts its native
+//! token through the same `CurrencyAdapter` and has a similar storage layout, so they are a
+//! stand-in until `scripts/benchmark.sh` is run on the reference machine, which overwrites this
+//! file.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::Weight};
+use sp_std::marker::PhantomData;
+
+/// Weights for `pallet_xcm_benchmarks::generic`.
+pub struct WeightInfo<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo<T> {
+	pub(crate) fn query_holding() -> Weight {
+		(682_639_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	pub(crate) fn buy_execution() -> Weight {
+		(5_540_000 as Weight)
+	}
+	pub(crate) fn query_response() -> Weight {
+		(11_766_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	pub(crate) fn transact() -> Weight {
+		(22_190_000 as Weight)
+	}
+	pub(crate) fn refund_surplus() -> Weight {
+		(5_814_000 as Weight)
+	}
+	pub(crate) fn set_error_handler() -> Weight {
+		(2_063_000 as Weight)
+	}
+	pub(crate) fn set_appendix() -> Weight {
+		(2_000_000 as Weight)
+	}
+	pub(crate) fn clear_error() -> Weight {
+		(2_040_000 as Weight)
+	}
+	pub(crate) fn descend_origin() -> Weight {
+		(2_791_000 as Weight)
+	}
+	pub(crate) fn clear_origin() -> Weight {
+		(2_064_000 as Weight)
+	}
+	pub(crate) fn report_error() -> Weight {
+		(20_045_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	pub(crate) fn claim_asset() -> Weight {
+		(7_158_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	pub(crate) fn trap() -> Weight {
+		(2_074_000 as Weight)
+	}
+	pub(crate) fn subscribe_version() -> Weight {
+		(23_624_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	pub(crate) fn unsubscribe_version() -> Weight {
+		(3_223_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	pub(crate) fn initiate_reserve_withdraw() -> Weight {
+		(714_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+}
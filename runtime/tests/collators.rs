@@ -0,0 +1,100 @@
+//! Collator selection by `pallet_collator_staking` and session rotation.
+
+mod common;
+
+use common::*;
+use frame_support::{assert_ok, dispatch::Dispatchable};
+use kpron_runtime::{
+	constants::currency::KPN, Balance, Balances, Call, CollatorStaking, Origin, Period, Runtime,
+	Session,
+};
+use sp_keyring::Sr25519Keyring::{self, Alice, Bob, Charlie, Dave};
+use sp_runtime::{DispatchResult, Perbill};
+
+fn set_keys(who: Sr25519Keyring) {
+	let call =
+		Call::Session(pallet_session::Call::set_keys { keys: session_keys(who), proof: vec![] });
+	assert_ok!(apply(sign(who, call)).unwrap());
+}
+
+fn join_candidates(who: Sr25519Keyring, bond: Balance) -> DispatchResult {
+	let call = Call::CollatorStaking(pallet_collator_staking::Call::join_candidates {
+		bond,
+		commission: Perbill::from_percent(10),
+	});
+	apply(sign(who, call)).unwrap()
+}
+
+/// Run the hooks of the first block of session `index`.
+fn start_session(index: u32) {
+	new_block(index * Period::get());
+	assert_eq!(Session::current_index(), index);
+}
+
+#[test]
+fn genesis_collators_are_the_invulnerables() {
+	ExtBuilder::default().build().execute_with(|| {
+		let invulnerables = vec![Alice.to_account_id(), Bob.to_account_id()];
+		assert_eq!(CollatorStaking::invulnerables(), invulnerables);
+		assert_eq!(Session::validators(), invulnerables);
+	});
+}
+
+#[test]
+fn candidates_need_session_keys() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_eq!(
+			join_candidates(Charlie, 16 * KPN),
+			Err(pallet_collator_staking::Error::<Runtime>::ValidatorNotRegistered.into()),
+		);
+		assert_eq!(Balances::reserved_balance(Charlie.to_account_id()), 0);
+	});
+}
+
+#[test]
+fn candidates_collate_from_the_session_after_next() {
+	ExtBuilder::default().build().execute_with(|| {
+		set_keys(Charlie);
+		assert_ok!(join_candidates(Charlie, 16 * KPN));
+		assert_eq!(Balances::reserved_balance(Charlie.to_account_id()), 16 * KPN);
+
+		start_session(1);
+		assert!(!Session::validators().contains(&Charlie.to_account_id()));
+		assert!(Session::queued_keys().iter().any(|(who, _)| *who == Charlie.to_account_id()));
+
+		start_session(2);
+		assert!(Session::validators().contains(&Charlie.to_account_id()));
+	});
+}
+
+#[test]
+fn candidates_with_the_most_stake_are_selected() {
+	ExtBuilder::default().desired_candidates(1).build().execute_with(|| {
+		set_keys(Charlie);
+		set_keys(Dave);
+		assert_ok!(join_candidates(Charlie, 16 * KPN));
+		assert_ok!(join_candidates(Dave, 20 * KPN));
+
+		start_session(1);
+		start_session(2);
+		let validators = Session::validators();
+		assert!(validators.contains(&Dave.to_account_id()));
+		assert!(!validators.contains(&Charlie.to_account_id()));
+	});
+}
+
+#[test]
+fn governance_replaces_the_invulnerables() {
+	ExtBuilder::default().build().execute_with(|| {
+		set_keys(Charlie);
+		let call = Call::CollatorStaking(pallet_collator_staking::Call::set_invulnerables {
+			new: vec![Charlie.to_account_id()],
+		});
+		assert!(call.clone().dispatch(Origin::signed(Alice.to_account_id())).is_err());
+		assert_ok!(call.dispatch(Origin::root()));
+
+		start_session(1);
+		start_session(2);
+		assert_eq!(Session::validators(), vec![Charlie.to_account_id()]);
+	});
+}
@@ -0,0 +1,184 @@
+//! Genesis, block and signing helpers shared by the runtime integration tests.
+#![allow(dead_code)]
+
+use codec::Encode;
+use frame_support::weights::GetDispatchInfo;
+use kpron_runtime::{
+	common::{BlockNumber, Header},
+	constants::currency::{EXISTENTIAL_DEPOSIT, KPN},
+	opaque::SessionKeys,
+	AccountId, Address, Balance, BuildStorage, Call, CollatorStaking, Event, Executive,
+	SignedExtra, System, TransactionPayment, UncheckedExtrinsic,
+};
+use sp_consensus_aura::{Slot, AURA_ENGINE_ID};
+use sp_keyring::Sr25519Keyring;
+use sp_runtime::{
+	generic::{Digest, DigestItem, Era, SignedPayload},
+	traits::Header as _,
+	ApplyExtrinsicResult, Perbill,
+};
+
+/// The balance of every keyring account at genesis.
+pub const INITIAL_BALANCE: Balance = 1_000 * KPN;
+
+/// Builds the externalities of a fresh chain from the runtime's `GenesisConfig`.
+pub struct ExtBuilder {
+	balances: Vec<(AccountId, Balance)>,
+	invulnerables: Vec<Sr25519Keyring>,
+	desired_candidates: u32,
+	min_candidate_bond: Balance,
+}
+
+impl Default for ExtBuilder {
+	fn default() -> Self {
+		let balances = Sr25519Keyring::iter()
+			.map(|key| (key.to_account_id(), INITIAL_BALANCE))
+			// Fees below the existential deposit are lost on an empty pot.
+			.chain(Some((CollatorStaking::account_id(), EXISTENTIAL_DEPOSIT)))
+			.collect();
+		Self {
+			balances,
+			invulnerables: vec![Sr25519Keyring::Alice, Sr25519Keyring::Bob],
+			desired_candidates: 2,
+			min_candidate_bond: 16 * KPN,
+		}
+	}
+}
+
+impl ExtBuilder {
+	pub fn invulnerables(mut self, invulnerables: Vec<Sr25519Keyring>) -> Self {
+		self.invulnerables = invulnerables;
+		self
+	}
+
+	pub fn desired_candidates(mut self, desired_candidates: u32) -> Self {
+		self.desired_candidates = desired_candidates;
+		self
+	}
+
+	pub fn build(self) -> sp_io::TestExternalities {
+		let storage = kpron_runtime::GenesisConfig {
+			system: Default::default(),
+			balances: kpron_runtime::BalancesConfig { balances: self.balances },
+			parachain_info: kpron_runtime::ParachainInfoConfig { parachain_id: 2019.into() },
+			indices: kpron_runtime::IndicesConfig { indices: vec![] },
+			vesting: Default::default(),
+			collator_staking: kpron_runtime::CollatorStakingConfig {
+				invulnerables: self.invulnerables.iter().map(|key| key.to_account_id()).collect(),
+				desired_candidates: self.desired_candidates,
+				min_candidate_bond: self.min_candidate_bond,
+				inflation_rate: Perbill::zero(),
+			},
+			session: kpron_runtime::SessionConfig {
+				keys: self
+					.invulnerables
+					.iter()
+					.map(|key| (key.to_account_id(), key.to_account_id(), session_keys(*key)))
+					.collect(),
+			},
+			aura: Default::default(),
+			aura_ext: Default::default(),
+			parachain_system: Default::default(),
+			polkadot_xcm: kpron_runtime::PolkadotXcmConfig {
+				safe_xcm_version: Some(kpron_runtime::SAFE_XCM_VERSION),
+			},
+		}
+		.build_storage()
+		.unwrap();
+
+		let mut ext = sp_io::TestExternalities::new(storage);
+		ext.execute_with(|| new_block(1));
+		ext
+	}
+}
+
+/// The session keys of a keyring account.
+pub fn session_keys(key: Sr25519Keyring) -> SessionKeys {
+	SessionKeys { aura: key.public().into() }
+}
+
+/// Start block `number` through `Executive`, running the `on_initialize` hooks.
+pub fn new_block(number: BlockNumber) {
+	new_block_with_digest(number, Default::default());
+}
+
+/// Start block `number` authored in Aura slot `slot`.
+pub fn new_block_in_slot(number: BlockNumber, slot: u64) {
+	let slot = DigestItem::PreRuntime(AURA_ENGINE_ID, Slot::from(slot).encode());
+	new_block_with_digest(number, Digest { logs: vec![slot] });
+}
+
+fn new_block_with_digest(number: BlockNumber, digest: Digest) {
+	let header =
+		Header::new(number, Default::default(), Default::default(), System::parent_hash(), digest);
+	Executive::initialize_block(&header);
+}
+
+/// A balance transfer of `value` to `to`.
+pub fn transfer(to: Sr25519Keyring, value: Balance) -> Call {
+	transfer_to(to.to_account_id().into(), value)
+}
+
+/// A balance transfer of `value` to `dest`, which may be an account index.
+pub fn transfer_to(dest: Address, value: Balance) -> Call {
+	Call::Balances(pallet_balances::Call::transfer { dest, value })
+}
+
+/// Sign `call` by `signer` with the runtime's `SignedExtra`, at the signer's current nonce.
+pub fn sign(signer: Sr25519Keyring, call: Call) -> UncheckedExtrinsic {
+	sign_with_tip(signer, call, 0)
+}
+
+/// Sign `call` by `signer`, tipping `tip`.
+pub fn sign_with_tip(signer: Sr25519Keyring, call: Call, tip: Balance) -> UncheckedExtrinsic {
+	sign_with(signer, call, tip, None)
+}
+
+/// Sign `call` by `signer`, paying the fee in `asset`.
+pub fn sign_with_asset(signer: Sr25519Keyring, call: Call, asset: u32) -> UncheckedExtrinsic {
+	sign_with(signer, call, 0, Some(asset))
+}
+
+/// Sign `call` by `signer`, tipping `tip` and paying in `asset`, or natively if `None`.
+pub fn sign_with(
+	signer: Sr25519Keyring,
+	call: Call,
+	tip: Balance,
+	asset: Option<u32>,
+) -> UncheckedExtrinsic {
+	let who = signer.to_account_id();
+	let extra: SignedExtra = (
+		frame_system::CheckSpecVersion::new(),
+		frame_system::CheckTxVersion::new(),
+		frame_system::CheckGenesis::new(),
+		frame_system::CheckEra::from(Era::Immortal),
+		frame_system::CheckNonce::from(System::account_nonce(&who)),
+		frame_system::CheckWeight::new(),
+		pallet_asset_tx_payment::ChargeAssetTxPayment::from(tip, asset).into(),
+	);
+	let payload = SignedPayload::new(call, extra).expect("additional signed data is available");
+	let signature = payload.using_encoded(|payload| signer.sign(payload));
+	let (call, extra, _) = payload.deconstruct();
+	UncheckedExtrinsic::new_signed(call, who.into(), signature.into(), extra)
+}
+
+/// Apply `xt` through `Executive`.
+pub fn apply(xt: UncheckedExtrinsic) -> ApplyExtrinsicResult {
+	Executive::apply_extrinsic(xt)
+}
+
+/// The fee `xt` pays, tip excluded, if it uses all the weight it declares.
+pub fn fee_of(xt: &UncheckedExtrinsic) -> Balance {
+	TransactionPayment::compute_fee(xt.encode().len() as u32, &xt.get_dispatch_info(), 0)
+}
+
+/// Whether `event` was deposited in the current block.
+pub fn has_event(event: Event) -> bool {
+	System::events().iter().any(|record| record.event == event)
+}
+
+/// `signatories` sorted as `pallet_multisig` expects them.
+pub fn sorted(mut signatories: Vec<AccountId>) -> Vec<AccountId> {
+	signatories.sort();
+	signatories
+}
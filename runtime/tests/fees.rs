@@ -0,0 +1,234 @@
+//! Transaction fees charged through the runtime's `SignedExtra`.
+
+mod common;
+
+use codec::Encode;
+use common::*;
+use frame_support::{
+	assert_ok,
+	traits::fungibles::Inspect,
+	weights::{constants::ExtrinsicBaseWeight, GetDispatchInfo},
+};
+use kpron_runtime::{
+	constants::currency::{CENTS, KPN, MILLICENTS},
+	AssetTxPayment, Assets, Balance, Balances, Call, CollatorStaking, FeeParams, Origin, Runtime,
+};
+use pallet_fee_params::FeeCoefficient;
+use pallet_fee_params_runtime_api::runtime_decl_for_FeeParamsApi::FeeParamsApi;
+use pallet_transaction_payment_rpc_runtime_api::runtime_decl_for_TransactionPaymentApi::TransactionPaymentApi;
+use sp_keyring::Sr25519Keyring::{self, Alice, Bob, Ferdie};
+use sp_runtime::{
+	transaction_validity::{InvalidTransaction, TransactionValidityError},
+	FixedPointNumber, FixedU128, Perbill,
+};
+
+/// An asset fees can be paid in, at half a unit per KPN.
+const USDT: u32 = 1984;
+
+/// Create `USDT`, give `who` 1000 of it and let it pay fees.
+fn create_fee_asset(who: Sr25519Keyring) {
+	assert_ok!(Assets::force_create(Origin::root(), USDT, Alice.to_account_id().into(), true, 1));
+	assert_ok!(Assets::mint(
+		Origin::signed(Alice.to_account_id()),
+		USDT,
+		who.to_account_id().into(),
+		1_000 * KPN,
+	));
+	assert_ok!(AssetTxPayment::set_fee_rate(
+		Origin::root(),
+		USDT,
+		Some(FixedU128::saturating_from_rational(1, 2)),
+	));
+}
+
+fn usdt(who: &kpron_runtime::AccountId) -> Balance {
+	<Assets as Inspect<_>>::balance(USDT, who)
+}
+
+/// A native fee in `USDT`, rounded up.
+fn usdt_fee(fee: Balance) -> Balance {
+	(fee + 1) / 2
+}
+
+#[test]
+fn fees_go_to_the_staking_pot() {
+	ExtBuilder::default().build().execute_with(|| {
+		let pot = Balances::free_balance(CollatorStaking::account_id());
+		let xt = sign(Alice, transfer(Bob, 10 * KPN));
+		let fee = fee_of(&xt);
+		assert!(fee > 0);
+
+		assert_ok!(apply(xt).unwrap());
+
+		assert_eq!(Balances::free_balance(Alice.to_account_id()), INITIAL_BALANCE - 10 * KPN - fee);
+		assert_eq!(Balances::free_balance(Bob.to_account_id()), INITIAL_BALANCE + 10 * KPN);
+		assert_eq!(Balances::free_balance(CollatorStaking::account_id()), pot + fee);
+	});
+}
+
+#[test]
+fn tips_go_to_the_staking_pot() {
+	ExtBuilder::default().build().execute_with(|| {
+		let pot = Balances::free_balance(CollatorStaking::account_id());
+		let xt = sign_with_tip(Alice, transfer(Bob, 10 * KPN), KPN);
+		let fee = fee_of(&xt);
+
+		assert_ok!(apply(xt).unwrap());
+
+		assert_eq!(
+			Balances::free_balance(Alice.to_account_id()),
+			INITIAL_BALANCE - 10 * KPN - fee - KPN,
+		);
+		assert_eq!(Balances::free_balance(CollatorStaking::account_id()), pot + fee + KPN);
+	});
+}
+
+#[test]
+fn failed_calls_still_pay() {
+	ExtBuilder::default().build().execute_with(|| {
+		let xt = sign(Alice, transfer(Bob, 2 * INITIAL_BALANCE));
+		let fee = fee_of(&xt);
+
+		assert!(apply(xt).unwrap().is_err());
+
+		assert_eq!(Balances::free_balance(Alice.to_account_id()), INITIAL_BALANCE - fee);
+		assert_eq!(Balances::free_balance(Bob.to_account_id()), INITIAL_BALANCE);
+	});
+}
+
+#[test]
+fn nonces_are_checked() {
+	ExtBuilder::default().build().execute_with(|| {
+		let xt = sign(Alice, transfer(Bob, KPN));
+		assert_ok!(apply(xt.clone()).unwrap());
+
+		assert_eq!(
+			apply(xt),
+			Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)),
+		);
+	});
+}
+
+#[test]
+fn accounts_that_cannot_pay_are_refused() {
+	ExtBuilder::default().build().execute_with(|| {
+		let transfer_all = Call::Balances(pallet_balances::Call::transfer_all {
+			dest: Bob.to_account_id().into(),
+			keep_alive: false,
+		});
+		assert_ok!(apply(sign(Ferdie, transfer_all)).unwrap());
+		assert_eq!(Balances::free_balance(Ferdie.to_account_id()), 0);
+
+		assert_eq!(
+			apply(sign(Ferdie, transfer(Bob, KPN))),
+			Err(TransactionValidityError::Invalid(InvalidTransaction::Payment)),
+		);
+	});
+}
+
+#[test]
+fn fees_paid_in_an_asset_go_to_the_block_author() {
+	ExtBuilder::default().build().execute_with(|| {
+		// Bob authors the odd slots.
+		new_block_in_slot(2, 1);
+		create_fee_asset(Ferdie);
+		let xt = sign_with_asset(Ferdie, transfer(Bob, 10 * KPN), USDT);
+		let fee = usdt_fee(fee_of(&xt));
+		assert!(fee > 0);
+
+		assert_ok!(apply(xt).unwrap());
+
+		assert_eq!(Balances::free_balance(Ferdie.to_account_id()), INITIAL_BALANCE - 10 * KPN);
+		assert_eq!(usdt(&Ferdie.to_account_id()), 1_000 * KPN - fee);
+		assert_eq!(usdt(&Bob.to_account_id()), fee);
+		assert_eq!(usdt(&CollatorStaking::account_id()), 0);
+	});
+}
+
+#[test]
+fn accounts_without_native_balance_can_pay_in_an_asset() {
+	ExtBuilder::default().build().execute_with(|| {
+		create_fee_asset(Ferdie);
+		let transfer_all = Call::Balances(pallet_balances::Call::transfer_all {
+			dest: Bob.to_account_id().into(),
+			keep_alive: false,
+		});
+		assert_ok!(apply(sign(Ferdie, transfer_all)).unwrap());
+		assert_eq!(Balances::free_balance(Ferdie.to_account_id()), 0);
+
+		let transfer_usdt = Call::Assets(pallet_assets::Call::transfer {
+			id: USDT,
+			target: Bob.to_account_id().into(),
+			amount: KPN,
+		});
+		assert_eq!(
+			apply(sign(Ferdie, transfer_usdt.clone())),
+			Err(TransactionValidityError::Invalid(InvalidTransaction::Payment)),
+		);
+		let xt = sign_with_asset(Ferdie, transfer_usdt, USDT);
+		let fee = usdt_fee(fee_of(&xt));
+		assert_ok!(apply(xt).unwrap());
+		assert_eq!(usdt(&Ferdie.to_account_id()), 1_000 * KPN - KPN - fee);
+		assert_eq!(usdt(&Bob.to_account_id()), KPN);
+	});
+}
+
+#[test]
+fn assets_without_a_rate_cannot_pay() {
+	ExtBuilder::default().build().execute_with(|| {
+		create_fee_asset(Ferdie);
+		assert_ok!(AssetTxPayment::set_fee_rate(Origin::root(), USDT, None));
+
+		assert_eq!(
+			apply(sign_with_asset(Ferdie, transfer(Bob, KPN), USDT)),
+			Err(TransactionValidityError::Invalid(InvalidTransaction::Payment)),
+		);
+		assert_eq!(usdt(&Ferdie.to_account_id()), 1_000 * KPN);
+	});
+}
+
+#[test]
+fn query_info_reports_the_fee_in_the_asset_paid_in() {
+	ExtBuilder::default().build().execute_with(|| {
+		create_fee_asset(Ferdie);
+		let native = sign(Ferdie, transfer(Bob, KPN));
+		let in_usdt = sign_with_asset(Ferdie, transfer(Bob, KPN), USDT);
+		let (native_len, usdt_len) = (native.encode().len() as u32, in_usdt.encode().len() as u32);
+
+		assert_eq!(Runtime::query_info(native.clone(), native_len).partial_fee, fee_of(&native));
+		assert_eq!(
+			Runtime::query_info(in_usdt.clone(), usdt_len).partial_fee,
+			usdt_fee(fee_of(&in_usdt)),
+		);
+	});
+}
+
+#[test]
+fn governance_sets_the_fees_charged() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_eq!(Runtime::byte_fee(), MILLICENTS);
+		let linear = vec![FeeCoefficient {
+			integer: 1,
+			frac: Perbill::zero(),
+			negative: false,
+			degree: 1,
+		}];
+		assert_ok!(FeeParams::set_byte_fee(Origin::root(), Some(CENTS)));
+		assert_ok!(FeeParams::set_weight_to_fee(Origin::root(), Some(linear.clone())));
+		assert_eq!(Runtime::byte_fee(), CENTS);
+		assert_eq!(Runtime::weight_to_fee(), linear);
+
+		// Weight is now charged one to one, on top of the base weight.
+		let xt = sign(Alice, transfer(Bob, KPN));
+		let fee = fee_of(&xt);
+		assert_eq!(
+			fee,
+			xt.encode().len() as Balance * CENTS +
+				ExtrinsicBaseWeight::get() as Balance +
+				xt.get_dispatch_info().weight as Balance,
+		);
+
+		assert_ok!(apply(xt).unwrap());
+		assert_eq!(Balances::free_balance(Alice.to_account_id()), INITIAL_BALANCE - KPN - fee);
+	});
+}
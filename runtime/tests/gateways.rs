@@ -0,0 +1,64 @@
+//! Feeless usage reports by gateway operators through `pallet_gateway_usage`.
+
+mod common;
+
+use common::*;
+use frame_support::{assert_ok, traits::Get};
+use kpron_runtime::{Balances, Call, GatewayUsage, MaxUsageReportsPerBlock, Origin, Runtime};
+use sp_keyring::Sr25519Keyring::{Alice, Bob, Ferdie};
+use sp_runtime::transaction_validity::{InvalidTransaction, TransactionValidityError};
+
+const GATEWAY: u32 = 0;
+
+fn report(calls: u32) -> Call {
+	Call::GatewayUsage(pallet_gateway_usage::Call::report_usage {
+		gateway: GATEWAY,
+		consumer: Alice.to_account_id(),
+		calls,
+	})
+}
+
+fn register_ferdie() {
+	assert_ok!(GatewayUsage::register_gateway(Origin::root(), Ferdie.to_account_id()));
+}
+
+#[test]
+fn operators_report_without_fees() {
+	ExtBuilder::default().build().execute_with(|| {
+		register_ferdie();
+
+		assert_ok!(apply(sign(Ferdie, report(5))).unwrap());
+		assert_eq!(Balances::free_balance(Ferdie.to_account_id()), INITIAL_BALANCE);
+		assert_eq!(GatewayUsage::usage(GATEWAY, Alice.to_account_id()), 5);
+
+		// Anyone else pays, and the report fails.
+		let xt = sign(Bob, report(5));
+		let fee = fee_of(&xt);
+		assert_eq!(
+			apply(xt).unwrap(),
+			Err(pallet_gateway_usage::Error::<Runtime>::NotOperator.into()),
+		);
+		assert_eq!(Balances::free_balance(Bob.to_account_id()), INITIAL_BALANCE - fee);
+	});
+}
+
+#[test]
+fn operators_are_limited_to_their_quota_per_block() {
+	ExtBuilder::default().build().execute_with(|| {
+		register_ferdie();
+		let quota: u32 = MaxUsageReportsPerBlock::get();
+
+		for _ in 0..quota {
+			assert_ok!(apply(sign(Ferdie, report(1))).unwrap());
+		}
+		assert_eq!(
+			apply(sign(Ferdie, report(1))),
+			Err(TransactionValidityError::Invalid(InvalidTransaction::ExhaustsResources)),
+		);
+		assert_eq!(GatewayUsage::usage(GATEWAY, Alice.to_account_id()), quota as u64);
+
+		new_block(2);
+		assert_ok!(apply(sign(Ferdie, report(1))).unwrap());
+		assert_eq!(Balances::free_balance(Ferdie.to_account_id()), INITIAL_BALANCE);
+	});
+}
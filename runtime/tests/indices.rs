@@ -0,0 +1,56 @@
+//! Short account indices through `pallet_indices`.
+
+mod common;
+
+use common::*;
+use frame_support::{assert_ok, dispatch::DispatchError};
+use kpron_runtime::{
+	constants::currency::KPN, Address, Balance, Balances, Call, IndexDeposit, Indices,
+};
+use sp_keyring::Sr25519Keyring::{Alice, Bob, Charlie};
+
+/// Let `Bob` claim index 7, returning the fee he paid.
+fn bob_claims_seven() -> Balance {
+	let xt = sign(Bob, Call::Indices(pallet_indices::Call::claim { index: 7 }));
+	let fee = fee_of(&xt);
+	assert_ok!(apply(xt).unwrap());
+	assert_eq!(Indices::lookup_index(7), Some(Bob.to_account_id()));
+	fee
+}
+
+#[test]
+fn claimed_indices_address_accounts() {
+	ExtBuilder::default().build().execute_with(|| {
+		let fee = bob_claims_seven();
+		assert_eq!(Balances::reserved_balance(Bob.to_account_id()), IndexDeposit::get());
+
+		assert_ok!(apply(sign(Alice, transfer_to(Address::Index(7), 10 * KPN))).unwrap());
+		assert_eq!(
+			Balances::free_balance(Bob.to_account_id()),
+			INITIAL_BALANCE - fee - IndexDeposit::get() + 10 * KPN,
+		);
+	});
+}
+
+#[test]
+fn signers_can_be_given_by_index() {
+	ExtBuilder::default().build().execute_with(|| {
+		bob_claims_seven();
+
+		let mut xt = sign(Bob, transfer(Charlie, 10 * KPN));
+		xt.signature.as_mut().expect("signed").0 = Address::Index(7);
+		assert_ok!(apply(xt).unwrap());
+		assert_eq!(Balances::free_balance(Charlie.to_account_id()), INITIAL_BALANCE + 10 * KPN);
+	});
+}
+
+#[test]
+fn unclaimed_indices_do_not_resolve() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_eq!(
+			apply(sign(Alice, transfer_to(Address::Index(9), 10 * KPN))).unwrap(),
+			Err(DispatchError::CannotLookup),
+		);
+		assert_eq!(Balances::free_balance(Bob.to_account_id()), INITIAL_BALANCE);
+	});
+}
@@ -0,0 +1,76 @@
+//! Multisig accounts through `pallet_multisig`.
+
+mod common;
+
+use codec::Encode;
+use common::*;
+use frame_support::assert_ok;
+use kpron_runtime::{
+	constants::currency::KPN, Balances, Call, DepositBase, DepositFactor, Multisig, Runtime,
+};
+use sp_core::hashing::blake2_256;
+use sp_keyring::Sr25519Keyring::{Alice, Bob, Charlie, Dave};
+
+#[test]
+fn threshold_one_dispatches_as_the_multisig() {
+	ExtBuilder::default().build().execute_with(|| {
+		let signatories = sorted(vec![Alice.to_account_id(), Bob.to_account_id()]);
+		let multisig = Multisig::multi_account_id(&signatories, 1);
+		Balances::make_free_balance_be(&multisig, 100 * KPN);
+
+		let transfer = Call::Balances(pallet_balances::Call::transfer {
+			dest: Dave.to_account_id().into(),
+			value: 10 * KPN,
+		});
+		let call = Call::Multisig(pallet_multisig::Call::as_multi_threshold_1(
+			vec![Bob.to_account_id()],
+			Box::new(transfer),
+		));
+		assert_ok!(apply(sign(Alice, call)).unwrap());
+
+		assert_eq!(Balances::free_balance(&multisig), 90 * KPN);
+		assert_eq!(Balances::free_balance(Dave.to_account_id()), INITIAL_BALANCE + 10 * KPN);
+	});
+}
+
+#[test]
+fn approvals_reserve_a_deposit_until_cancelled() {
+	ExtBuilder::default().build().execute_with(|| {
+		let signatories =
+			sorted(vec![Alice.to_account_id(), Bob.to_account_id(), Charlie.to_account_id()]);
+		let multisig = Multisig::multi_account_id(&signatories, 2);
+		let others = sorted(vec![Bob.to_account_id(), Charlie.to_account_id()]);
+		let transfer = Call::Balances(pallet_balances::Call::transfer {
+			dest: Dave.to_account_id().into(),
+			value: 10 * KPN,
+		});
+		let call_hash = blake2_256(&transfer.encode());
+
+		let approve = Call::Multisig(pallet_multisig::Call::approve_as_multi {
+			threshold: 2,
+			other_signatories: others.clone(),
+			maybe_timepoint: None,
+			call_hash,
+			max_weight: 0,
+		});
+		assert_ok!(apply(sign(Alice, approve)).unwrap());
+
+		let deposit = DepositBase::get() + DepositFactor::get() * 2;
+		assert_eq!(Balances::reserved_balance(Alice.to_account_id()), deposit);
+		let pending = pallet_multisig::Multisigs::<Runtime>::get(&multisig, call_hash)
+			.expect("the approval is stored");
+		assert_eq!(pending.approvals, vec![Alice.to_account_id()]);
+
+		let cancel = Call::Multisig(pallet_multisig::Call::cancel_as_multi {
+			threshold: 2,
+			other_signatories: others,
+			timepoint: pending.when,
+			call_hash,
+		});
+		assert_ok!(apply(sign(Alice, cancel)).unwrap());
+
+		assert_eq!(Balances::reserved_balance(Alice.to_account_id()), 0);
+		assert!(pallet_multisig::Multisigs::<Runtime>::get(&multisig, call_hash).is_none());
+		assert_eq!(Balances::free_balance(Dave.to_account_id()), INITIAL_BALANCE);
+	});
+}
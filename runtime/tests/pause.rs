@@ -0,0 +1,87 @@
+//! Pausing calls through `pallet_transaction_pause` and the base call filter.
+
+mod common;
+
+use common::*;
+use frame_support::{assert_noop, assert_ok, dispatch::DispatchError};
+use kpron_runtime::{
+	constants::currency::KPN, Balances, Call, Event, Origin, Runtime, TransactionPause,
+};
+use sp_keyring::Sr25519Keyring::{Alice, Bob};
+
+fn pause(pallet: &[u8], call: Option<&[u8]>) -> frame_support::dispatch::DispatchResult {
+	TransactionPause::pause(Origin::root(), pallet.to_vec(), call.map(|c| c.to_vec()))
+}
+
+fn filtered() -> DispatchError {
+	frame_system::Error::<Runtime>::CallFiltered.into()
+}
+
+#[test]
+fn paused_calls_fail_but_pay_fees() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(pause(b"Balances", Some(b"transfer")));
+
+		let xt = sign(Alice, transfer(Bob, 10 * KPN));
+		let fee = fee_of(&xt);
+		assert_eq!(apply(xt).unwrap(), Err(filtered()));
+		assert_eq!(Balances::free_balance(Alice.to_account_id()), INITIAL_BALANCE - fee);
+		assert_eq!(Balances::free_balance(Bob.to_account_id()), INITIAL_BALANCE);
+
+		assert_ok!(TransactionPause::unpause(
+			Origin::root(),
+			b"Balances".to_vec(),
+			Some(b"transfer".to_vec()),
+		));
+		assert_ok!(apply(sign(Alice, transfer(Bob, 10 * KPN))).unwrap());
+		assert_eq!(Balances::free_balance(Bob.to_account_id()), INITIAL_BALANCE + 10 * KPN);
+	});
+}
+
+#[test]
+fn paused_calls_are_filtered_inside_batches() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(pause(b"Balances", None));
+
+		let batch =
+			Call::Utility(pallet_utility::Call::batch { calls: vec![transfer(Bob, 10 * KPN)] });
+		assert_ok!(apply(sign(Alice, batch)).unwrap());
+		assert!(has_event(Event::Utility(pallet_utility::Event::BatchInterrupted {
+			index: 0,
+			error: filtered(),
+		})));
+		assert_eq!(Balances::free_balance(Bob.to_account_id()), INITIAL_BALANCE);
+	});
+}
+
+#[test]
+fn pause_is_governed() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_noop!(
+			TransactionPause::pause(
+				Origin::signed(Alice.to_account_id()),
+				b"Balances".to_vec(),
+				None,
+			),
+			DispatchError::BadOrigin,
+		);
+		assert_ok!(pause(b"PolkadotXcm", Some(b"reserve_transfer_assets")));
+		assert!(has_event(Event::TransactionPause(pallet_transaction_pause::Event::CallsPaused(
+			b"PolkadotXcm".to_vec(),
+			Some(b"reserve_transfer_assets".to_vec()),
+		))));
+	});
+}
+
+#[test]
+fn pallets_needed_to_build_blocks_cannot_be_paused() {
+	ExtBuilder::default().build().execute_with(|| {
+		let unpausable: [&[u8]; 4] = [b"System", b"ParachainSystem", b"Timestamp", b"TransactionPause"];
+		for pallet in unpausable.iter() {
+			assert_noop!(
+				pause(pallet, None),
+				pallet_transaction_pause::Error::<Runtime>::Unpausable,
+			);
+		}
+	});
+}
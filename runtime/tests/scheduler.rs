@@ -0,0 +1,91 @@
+//! Calls scheduled for future blocks through `pallet_scheduler`.
+
+mod common;
+
+use codec::Encode;
+use common::*;
+use frame_support::{assert_noop, assert_ok, dispatch::DispatchError, traits::schedule::MaybeHashed};
+use kpron_runtime::{
+	constants::currency::{CENTS, MILLICENTS},
+	Call, Event, FeeParams, Origin, Preimage, Runtime, Scheduler,
+};
+use pallet_scheduler::CallOrHashOf;
+use sp_keyring::Sr25519Keyring::Alice;
+use sp_runtime::traits::{BlakeTwo256, Hash};
+
+fn set_byte_fee_call() -> Call {
+	Call::FeeParams(pallet_fee_params::Call::set_byte_fee { fee: Some(CENTS) })
+}
+
+fn set_byte_fee() -> Box<CallOrHashOf<Runtime>> {
+	Box::new(set_byte_fee_call().into())
+}
+
+#[test]
+fn scheduled_calls_are_dispatched_at_their_block() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(Scheduler::schedule(Origin::root(), 3, None, 0, set_byte_fee()));
+
+		new_block(2);
+		assert_eq!(FeeParams::byte_fee(), MILLICENTS);
+
+		new_block(3);
+		assert_eq!(FeeParams::byte_fee(), CENTS);
+		assert!(has_event(Event::Scheduler(pallet_scheduler::Event::Dispatched {
+			task: (3, 0),
+			id: None,
+			result: Ok(()),
+		})));
+	});
+}
+
+#[test]
+fn calls_scheduled_by_hash_wait_for_their_preimage() {
+	ExtBuilder::default().build().execute_with(|| {
+		let call = set_byte_fee_call();
+		let hash = BlakeTwo256::hash_of(&call);
+		let by_hash = Box::new(MaybeHashed::Hash(hash));
+		assert_ok!(Scheduler::schedule(Origin::root(), 3, None, 0, by_hash));
+
+		// Without the preimage the call is postponed by `NoPreimagePostponement` blocks.
+		new_block(3);
+		assert_eq!(FeeParams::byte_fee(), MILLICENTS);
+		assert!(has_event(Event::Scheduler(pallet_scheduler::Event::CallLookupFailed {
+			task: (3, 0),
+			id: None,
+			error: pallet_scheduler::LookupError::Unavailable,
+		})));
+
+		assert_ok!(Preimage::note_preimage(Origin::signed(Alice.to_account_id()), call.encode()));
+		new_block(13);
+		assert_eq!(FeeParams::byte_fee(), CENTS);
+	});
+}
+
+#[test]
+fn named_schedules_can_be_cancelled() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(Scheduler::schedule_named(
+			Origin::root(),
+			b"fees".to_vec(),
+			3,
+			None,
+			0,
+			set_byte_fee(),
+		));
+		assert_ok!(Scheduler::cancel_named(Origin::root(), b"fees".to_vec()));
+
+		new_block(3);
+		assert_eq!(FeeParams::byte_fee(), MILLICENTS);
+	});
+}
+
+#[test]
+fn scheduling_is_governed() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_noop!(
+			Scheduler::schedule(Origin::signed(Alice.to_account_id()), 3, None, 0, set_byte_fee()),
+			DispatchError::BadOrigin,
+		);
+	});
+}
@@ -0,0 +1,70 @@
+//! Batched calls through `pallet_utility`.
+
+mod common;
+
+use common::*;
+use frame_support::{assert_ok, dispatch::DispatchError};
+use kpron_runtime::{constants::currency::KPN, Balances, Call, Event};
+use sp_keyring::Sr25519Keyring::{Alice, Bob, Charlie};
+
+fn insufficient_balance() -> DispatchError {
+	pallet_balances::Error::<kpron_runtime::Runtime>::InsufficientBalance.into()
+}
+
+#[test]
+fn batch_dispatches_every_call() {
+	ExtBuilder::default().build().execute_with(|| {
+		let batch = Call::Utility(pallet_utility::Call::batch {
+			calls: vec![
+				transfer(Bob, 10 * KPN),
+				transfer(Charlie, 20 * KPN),
+			],
+		});
+		assert_ok!(apply(sign(Alice, batch)).unwrap());
+
+		assert_eq!(Balances::free_balance(Bob.to_account_id()), INITIAL_BALANCE + 10 * KPN);
+		assert_eq!(Balances::free_balance(Charlie.to_account_id()), INITIAL_BALANCE + 20 * KPN);
+		assert!(has_event(Event::Utility(pallet_utility::Event::BatchCompleted)));
+	});
+}
+
+#[test]
+fn batch_stops_at_the_first_failure() {
+	ExtBuilder::default().build().execute_with(|| {
+		let batch = Call::Utility(pallet_utility::Call::batch {
+			calls: vec![
+				transfer(Bob, 10 * KPN),
+				transfer(Charlie, 2 * INITIAL_BALANCE),
+				transfer(Charlie, 10 * KPN),
+			],
+		});
+		assert_ok!(apply(sign(Alice, batch)).unwrap());
+
+		assert_eq!(Balances::free_balance(Bob.to_account_id()), INITIAL_BALANCE + 10 * KPN);
+		assert_eq!(Balances::free_balance(Charlie.to_account_id()), INITIAL_BALANCE);
+		assert!(has_event(Event::Utility(pallet_utility::Event::BatchInterrupted {
+			index: 1,
+			error: insufficient_balance(),
+		})));
+	});
+}
+
+#[test]
+fn batch_all_reverts_on_failure() {
+	ExtBuilder::default().build().execute_with(|| {
+		let batch_all = Call::Utility(pallet_utility::Call::batch_all {
+			calls: vec![
+				transfer(Bob, 10 * KPN),
+				transfer(Charlie, 2 * INITIAL_BALANCE),
+			],
+		});
+		let xt = sign(Alice, batch_all);
+		let fee = fee_of(&xt);
+
+		assert_eq!(apply(xt).unwrap(), Err(insufficient_balance()));
+
+		assert_eq!(Balances::free_balance(Bob.to_account_id()), INITIAL_BALANCE);
+		let alice = Balances::free_balance(Alice.to_account_id());
+		assert!(alice < INITIAL_BALANCE && alice >= INITIAL_BALANCE - fee);
+	});
+}
@@ -0,0 +1,36 @@
+#!/usr/bin/env bash
+#
+# Regenerate the runtime's weights. Run from the repository root on the reference machine.
+
+set -e
+
+NODE=./target/release/kpron-node
+
+cargo build --release --features runtime-benchmarks
+
+benchmark() {
+	local pallet=$1 output=$2
+	shift 2
+	$NODE benchmark pallet \
+		--chain=dev \
+		--execution=wasm \
+		--wasm-execution=compiled \
+		--pallet="$pallet" \
+		--extrinsic='*' \
+		--steps=50 \
+		--repeat=20 \
+		--output="$output" \
+		"$@"
+}
+
+# Instruction weights used by `XcmWeigher`.
+for pallet in fungible generic; do
+	benchmark "pallet_xcm_benchmarks::$pallet" \
+		"./runtime/src/weights/xcm/pallet_xcm_benchmarks_$pallet.rs" \
+		--template=./scripts/templates/xcm-bench-template.hbs
+done
+
+# Pallet weights the runtime uses.
+for pallet in pallet_asset_tx_payment pallet_collator_staking pallet_fee_params pallet_gateway_usage pallet_service_payments; do
+	benchmark "$pallet" "./runtime/src/weights/$pallet.rs"
+done
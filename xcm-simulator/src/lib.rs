@@ -0,0 +1,400 @@
+//! A simulated Kusama network with kpron and a sibling parachain, for testing kpron's XCM
+//! configuration end to end.
+
+mod parachain;
+mod relay_chain;
+
+use polkadot_parachain::primitives::Id as ParaId;
+use sp_runtime::{traits::AccountIdConversion, AccountId32};
+use xcm_simulator::{decl_test_network, decl_test_parachain, decl_test_relay_chain};
+
+pub const ALICE: AccountId32 = AccountId32::new([0u8; 32]);
+pub const INITIAL_BALANCE: u128 = 1_000_000_000_000_000;
+
+pub const KPRON_ID: u32 = 2019;
+pub const SIBLING_ID: u32 = 2000;
+
+decl_test_parachain! {
+	pub struct Kpron {
+		Runtime = parachain::Runtime,
+		XcmpMessageHandler = parachain::MsgQueue,
+		DmpMessageHandler = parachain::MsgQueue,
+		new_ext = para_ext(KPRON_ID),
+	}
+}
+
+decl_test_parachain! {
+	pub struct Sibling {
+		Runtime = parachain::Runtime,
+		XcmpMessageHandler = parachain::MsgQueue,
+		DmpMessageHandler = parachain::MsgQueue,
+		new_ext = para_ext(SIBLING_ID),
+	}
+}
+
+decl_test_relay_chain! {
+	pub struct Relay {
+		Runtime = relay_chain::Runtime,
+		XcmConfig = relay_chain::XcmConfig,
+		new_ext = relay_ext(),
+	}
+}
+
+decl_test_network! {
+	pub struct MockNet {
+		relay_chain = Relay,
+		parachains = vec![
+			(2019, Kpron),
+			(2000, Sibling),
+		],
+	}
+}
+
+pub fn para_account_id(id: u32) -> relay_chain::AccountId {
+	ParaId::from(id).into_account()
+}
+
+pub fn para_ext(para_id: u32) -> sp_io::TestExternalities {
+	use parachain::{MsgQueue, Runtime, System};
+
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+
+	pallet_balances::GenesisConfig::<Runtime> { balances: vec![(ALICE, INITIAL_BALANCE)] }
+		.assimilate_storage(&mut t)
+		.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		MsgQueue::set_para_id(para_id.into());
+	});
+	ext
+}
+
+pub fn relay_ext() -> sp_io::TestExternalities {
+	use relay_chain::{Runtime, System};
+
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+
+	pallet_balances::GenesisConfig::<Runtime> {
+		balances: vec![
+			(ALICE, INITIAL_BALANCE),
+			(para_account_id(KPRON_ID), INITIAL_BALANCE),
+			(para_account_id(SIBLING_ID), INITIAL_BALANCE),
+		],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{
+		para_account_id, parachain, relay_chain, Kpron, MockNet, Relay, Sibling, ALICE,
+		INITIAL_BALANCE, KPRON_ID, SIBLING_ID,
+	};
+
+	use codec::Encode;
+	use frame_support::{
+		assert_ok,
+		traits::Currency,
+		weights::{GetDispatchInfo, Weight},
+	};
+	use xcm::{
+		latest::prelude::*, DoubleEncoded, VersionedMultiAssets, VersionedMultiLocation, VersionedXcm,
+	};
+	use xcm_executor::traits::WeightBounds;
+	use xcm_simulator::TestExt;
+
+	const PROVIDER: parachain::AccountId = parachain::AccountId::new([1u8; 32]);
+	const BOB: parachain::AccountId = parachain::AccountId::new([2u8; 32]);
+
+	/// Enough weight for any message in these tests to execute locally.
+	const MAX_WEIGHT: Weight = 1_000_000_000_000;
+
+	fn account(who: &parachain::AccountId) -> Junction {
+		AccountId32 { network: NetworkId::Any, id: who.clone().into() }
+	}
+
+	/// A call setting `who`'s free balance on a parachain, which only root may dispatch.
+	fn set_balance(who: parachain::AccountId, free: u128) -> DoubleEncoded<()> {
+		let call = pallet_balances::Call::set_balance { who, new_free: free, new_reserved: 0 };
+		parachain::Call::Balances(call).encode().into()
+	}
+
+	fn deposit_to(who: &parachain::AccountId) -> Instruction<()> {
+		DepositAsset { assets: All.into(), max_assets: 1, beneficiary: account(who).into() }
+	}
+
+	/// What kpron charges for executing `message`: its weight under kpron's weigher, bought at
+	/// one unit per unit of weight.
+	fn fee(message: &Xcm<()>) -> u128 {
+		let mut message: Xcm<parachain::Call> = message.clone().into();
+		<parachain::XcmWeigher as WeightBounds<parachain::Call>>::weight(&mut message)
+			.expect("the test messages are within the instruction limit; qed") as u128
+	}
+
+	#[test]
+	fn dmp_reserve_transfer_from_relay() {
+		MockNet::reset();
+
+		let amount = 1_000_000_000_000;
+		Relay::execute_with(|| {
+			assert_ok!(relay_chain::XcmPallet::limited_reserve_transfer_assets(
+				relay_chain::Origin::signed(ALICE),
+				Box::new(VersionedMultiLocation::from(MultiLocation::from(Parachain(KPRON_ID)))),
+				Box::new(VersionedMultiLocation::from(MultiLocation::from(account(&ALICE)))),
+				Box::new(VersionedMultiAssets::from(MultiAssets::from((Here, amount)))),
+				0,
+				Unlimited,
+			));
+			assert_eq!(relay_chain::Balances::free_balance(&ALICE), INITIAL_BALANCE - amount);
+			assert_eq!(
+				relay_chain::Balances::free_balance(&para_account_id(KPRON_ID)),
+				INITIAL_BALANCE + amount,
+			);
+		});
+
+		// The message the relay chain sends on to kpron.
+		let received = Xcm(vec![
+			ReserveAssetDeposited((Parent, amount).into()),
+			ClearOrigin,
+			BuyExecution { fees: (Parent, amount).into(), weight_limit: Unlimited },
+			deposit_to(&ALICE),
+		]);
+		Kpron::execute_with(|| {
+			assert_eq!(
+				parachain::Balances::free_balance(&ALICE),
+				INITIAL_BALANCE + amount - fee(&received),
+			);
+		});
+	}
+
+	#[test]
+	fn ump_reserve_withdraw_to_relay() {
+		MockNet::reset();
+
+		let amount = 123;
+		Kpron::execute_with(|| {
+			let message = Xcm(vec![
+				WithdrawAsset((Parent, amount).into()),
+				InitiateReserveWithdraw {
+					assets: All.into(),
+					reserve: Parent.into(),
+					xcm: Xcm(vec![
+						BuyExecution { fees: (Here, amount).into(), weight_limit: Unlimited },
+						deposit_to(&BOB),
+					]),
+				},
+			]);
+			assert_ok!(parachain::PolkadotXcm::execute(
+				parachain::Origin::signed(ALICE),
+				Box::new(VersionedXcm::from(message)),
+				MAX_WEIGHT,
+			));
+			// Executing locally is paid for by the transaction, not out of the withdrawn assets.
+			assert_eq!(parachain::Balances::free_balance(&ALICE), INITIAL_BALANCE - amount);
+		});
+
+		Relay::execute_with(|| {
+			assert_eq!(
+				relay_chain::Balances::free_balance(&para_account_id(KPRON_ID)),
+				INITIAL_BALANCE - amount,
+			);
+			assert_eq!(relay_chain::Balances::free_balance(&BOB), amount);
+		});
+	}
+
+	#[test]
+	fn xcmp_from_sibling_spends_its_sovereign_account() {
+		MockNet::reset();
+
+		let sovereign = Kpron::execute_with(|| {
+			use xcm_executor::traits::Convert;
+			let sovereign = kpron_runtime::LocationToAccountId::convert(
+				MultiLocation::new(1, X1(Parachain(SIBLING_ID))),
+			)
+			.unwrap();
+			let _ = parachain::Balances::deposit_creating(&sovereign, INITIAL_BALANCE);
+			sovereign
+		});
+
+		let amount = 1_000_000_000_000;
+		let message = Xcm(vec![
+			WithdrawAsset((Parent, amount).into()),
+			BuyExecution { fees: (Parent, amount).into(), weight_limit: Unlimited },
+			deposit_to(&BOB),
+		]);
+		Sibling::execute_with(|| {
+			assert_ok!(<parachain::XcmRouter as SendXcm>::send_xcm(
+				(Parent, Parachain(KPRON_ID)),
+				message.clone(),
+			));
+		});
+
+		Kpron::execute_with(|| {
+			assert_eq!(parachain::Balances::free_balance(&sovereign), INITIAL_BALANCE - amount);
+			assert_eq!(parachain::Balances::free_balance(&BOB), amount - fee(&message));
+		});
+	}
+
+	#[test]
+	fn parent_transacts_as_superuser() {
+		MockNet::reset();
+
+		Relay::execute_with(|| {
+			assert_ok!(<relay_chain::XcmRouter as SendXcm>::send_xcm(
+				Parachain(KPRON_ID),
+				Xcm(vec![Transact {
+					origin_type: OriginKind::Superuser,
+					require_weight_at_most: 1_000_000_000,
+					call: set_balance(BOB, 42),
+				}]),
+			));
+		});
+
+		Kpron::execute_with(|| {
+			assert_eq!(parachain::Balances::free_balance(&BOB), 42);
+		});
+	}
+
+	#[test]
+	fn siblings_cannot_transact_as_superuser() {
+		MockNet::reset();
+
+		Sibling::execute_with(|| {
+			assert_ok!(<parachain::XcmRouter as SendXcm>::send_xcm(
+				(Parent, Parachain(KPRON_ID)),
+				Xcm(vec![Transact {
+					origin_type: OriginKind::Superuser,
+					require_weight_at_most: 1_000_000_000,
+					call: set_balance(BOB, 42),
+				}]),
+			));
+		});
+
+		Kpron::execute_with(|| {
+			assert_eq!(parachain::Balances::free_balance(&BOB), 0);
+		});
+	}
+
+	#[test]
+	fn sibling_account_pays_for_api_calls_through_escrow() {
+		MockNet::reset();
+
+		let payer = Kpron::execute_with(|| parachain::sibling_account(SIBLING_ID, &ALICE));
+		let funds = 100_000_000_000_000;
+		let fee_budget = 10_000_000_000_000;
+		let escrowed = 1_000_000;
+		let price = 300_000;
+
+		Kpron::execute_with(|| {
+			let _ = parachain::Balances::deposit_creating(&payer, funds);
+		});
+
+		// Alice on the sibling puts funds in escrow for the provider, paying for execution out of
+		// her account on kpron and getting back what is left of the fee budget.
+		let call = parachain::Call::ServicePayments(pallet_service_payments::Call::deposit {
+			provider: PROVIDER,
+			amount: escrowed,
+		});
+		let message = Xcm(vec![
+			WithdrawAsset((Parent, fee_budget).into()),
+			BuyExecution { fees: (Parent, fee_budget).into(), weight_limit: Unlimited },
+			Transact {
+				origin_type: OriginKind::SovereignAccount,
+				require_weight_at_most: call.get_dispatch_info().weight,
+				call: call.encode().into(),
+			},
+			RefundSurplus,
+			DepositAsset {
+				assets: All.into(),
+				max_assets: 1,
+				beneficiary: (Parent, Parachain(SIBLING_ID), account(&ALICE)).into(),
+			},
+		]);
+		Sibling::execute_with(|| {
+			assert_ok!(parachain::PolkadotXcm::send(
+				parachain::Origin::signed(ALICE),
+				Box::new((Parent, Parachain(KPRON_ID)).into()),
+				Box::new(VersionedXcm::from(message.clone())),
+			));
+		});
+
+		// `send` prefixes the message with Alice's location on the sibling.
+		let mut received = message;
+		received.0.insert(0, DescendOrigin(X1(account(&ALICE))));
+		let fee = fee(&received);
+
+		Kpron::execute_with(|| {
+			assert_eq!(
+				parachain::ServicePayments::escrow(&payer, &PROVIDER).map(|e| e.amount),
+				Some(escrowed),
+			);
+			assert_eq!(parachain::Balances::reserved_balance(&payer), escrowed);
+			assert_eq!(parachain::Balances::free_balance(&payer), funds - escrowed - fee);
+
+			// The provider charges for the calls it served.
+			assert_ok!(parachain::ServicePayments::charge(
+				parachain::Origin::signed(PROVIDER),
+				payer.clone(),
+				price,
+			));
+			assert_eq!(parachain::Balances::free_balance(&PROVIDER), price);
+			assert_eq!(parachain::Balances::reserved_balance(&payer), escrowed - price);
+		});
+	}
+
+	#[test]
+	fn siblings_are_limited_to_their_budget_per_block() {
+		MockNet::reset();
+
+		let sovereign = Kpron::execute_with(|| {
+			use xcm_executor::traits::Convert;
+			let sibling = MultiLocation::new(1, X1(Parachain(SIBLING_ID)));
+			assert_ok!(parachain::XcmRateLimit::set_budget(
+				parachain::Origin::root(),
+				Box::new(sibling.clone().into()),
+				Some(1),
+			));
+			let sovereign = kpron_runtime::LocationToAccountId::convert(sibling).unwrap();
+			let _ = parachain::Balances::deposit_creating(&sovereign, INITIAL_BALANCE);
+			sovereign
+		});
+
+		let amount = 1_000_000_000_000;
+		let message = Xcm(vec![
+			WithdrawAsset((Parent, amount).into()),
+			BuyExecution { fees: (Parent, amount).into(), weight_limit: Unlimited },
+			deposit_to(&BOB),
+		]);
+		let send = || {
+			assert_ok!(<parachain::XcmRouter as SendXcm>::send_xcm(
+				(Parent, Parachain(KPRON_ID)),
+				message.clone(),
+			));
+		};
+
+		// Only the first of two messages delivered in the same block is executed.
+		Sibling::execute_with(|| {
+			send();
+			send();
+		});
+		Kpron::execute_with(|| {
+			assert_eq!(parachain::Balances::free_balance(&sovereign), INITIAL_BALANCE - amount);
+			assert_eq!(parachain::Balances::free_balance(&BOB), amount - fee(&message));
+			parachain::System::set_block_number(2);
+		});
+
+		// The budget is back in the next block.
+		Sibling::execute_with(send);
+		Kpron::execute_with(|| {
+			assert_eq!(parachain::Balances::free_balance(&sovereign), INITIAL_BALANCE - 2 * amount);
+			assert_eq!(parachain::Balances::free_balance(&BOB), 2 * (amount - fee(&message)));
+		});
+	}
+}